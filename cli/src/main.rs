@@ -2,26 +2,45 @@ use std::{error::Error, fs::File};
 use std::io::{self, prelude::*, BufReader};
 use clap::{
     crate_description, crate_name, crate_version, value_t, value_t_or_exit, App, AppSettings, Arg,
-    SubCommand,
+    ArgMatches, SubCommand,
 };
 use serde::{Deserialize, Serialize};
 use solana_clap_utils::{
     fee_payer::fee_payer_arg,
-    input_parsers::{pubkey_of, pubkey_of_signer, pubkeys_of, signer_of},
+    input_parsers::{pubkey_of, pubkeys_of, signer_of},
     input_validators::{is_amount, is_parsable, is_url, is_valid_pubkey, is_valid_signer},
     keypair::signer_from_path,
 };
-use solana_client::rpc_client::RpcClient;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::RpcFilterType,
+};
 use solana_program::{program_pack::Pack, pubkey::Pubkey , pubkey::ParsePubkeyError};
 use solana_sdk::{
-    commitment_config::CommitmentConfig, signature::Signer, transaction::Transaction,
+    commitment_config::CommitmentConfig,
+    instruction::AccountMeta,
+    signature::{Signature, Signer},
+    transaction::Transaction,
 };
+use solana_transaction_status::UiTransactionEncoding;
 use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
-use wave_dist::state::Distribution;
+use wave_dist::state::{
+    DenylistEntry, Distribution, DistributionKind, DistributionMetadata, DistributionV1,
+    ForwardAddress, Grant, HookFailurePolicy, ProgramConfig, RecipientReceipt, RoundSummary,
+};
 use bs58;
 use std::mem;  
 
-const DISTRIBUTE_CHUNK_SIZE: usize = 20;
+// Each recipient may need a new token account, which is expensive in
+// compute units, so chunks stay small
+const DISTRIBUTE_CHUNK_SIZE: usize = 6;
+const DENYLIST_CHUNK_SIZE: usize = 10;
+// NFT recipients name a pool item too and get an ATA per mint, so even fewer
+// fit in a transaction
+const NFT_CHUNK_SIZE: usize = 4;
+// Settling needs no new accounts, so more recipients fit
+const SETTLE_CHUNK_SIZE: usize = 8;
 const TOKEN_ADDRESS: &str = "7yzuYZdm4MyV8E3PwMWP9i7BR68sbh83MjuRbWvDbRgv";
 const DISTRIB_PROGRAM: &str = "kmKvdQWRAqekZPz4dqAdhfHBDEug4VnHs5wLyD2ybNN";
 
@@ -35,7 +54,6 @@ struct Config {
 struct StoredDistribution {
     pub program_id: Pubkey,
     pub project_name: String,
-    pub project_pubkey:Pubkey,
     pub dist_account: Pubkey,
     pub max_recipients: u16,
     pub dist_authority: Pubkey,
@@ -44,6 +62,20 @@ struct StoredDistribution {
     pub token_account: Pubkey,
     pub recipient_file: String,
 }
+// A voucher claim a recipient asked a relayer to submit, one per line of a
+// relay queue file
+#[derive(Serialize, Deserialize, Debug)]
+struct RelayRequest {
+    pub dist_account: Pubkey,
+    pub recipient: Pubkey,
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+    pub expiry: i64,
+    pub relayer_fee: u64,
+    pub voucher_signature: String,
+    pub recipient_signature: String,
+}
 #[derive(Serialize, Deserialize, Debug)]
 struct Participant {
     pub address: Pubkey
@@ -67,6 +99,489 @@ fn String_to_pubkey (mut str : String) -> Result<Pubkey,ParsePubkeyError> {
     
 
 }
+
+fn project_name_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("project_name")
+        .long("project-name")
+        .value_name("NAME")
+        .takes_value(true)
+        .requires("seed")
+        .help("The project name the distribution was created with.")
+}
+
+fn creator_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("creator")
+        .long("creator")
+        .value_name("ADDRESS")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .requires("seed")
+        .help(
+            "The account that created the distribution. \
+             Defaults to the fee payer.",
+        )
+}
+
+// Adds the arguments used to locate an existing distribution
+fn dist_account_args<'a, 'b>(subcommand: App<'a, 'b>) -> App<'a, 'b> {
+    subcommand
+        .arg(
+            Arg::with_name("state_file")
+                .long("state-file")
+                .value_name("PATH")
+                .takes_value(true)
+                .help(
+                    "A state file created by create-distribution to \
+                     fill out most required values automatically.",
+                ),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .validator(is_valid_pubkey)
+                .value_name("ADDRESS")
+                .takes_value(true)
+                .conflicts_with("dist_account")
+                .conflicts_with("state_file")
+                .help(
+                    "A pubkey that programmatically determines the \
+                     address of the distribution account.",
+                ),
+        )
+        .arg(
+            Arg::with_name("dist_account")
+                .long("dist-account")
+                .validator(is_valid_pubkey)
+                .value_name("ADDRESS")
+                .takes_value(true)
+                .required_unless_one(&["seed", "state_file"])
+                .conflicts_with("state_file")
+                .help("The distribution account address."),
+        )
+        .arg(project_name_arg())
+        .arg(creator_arg())
+}
+
+// Arguments describing a voucher of a distribution, besides its recipient
+fn voucher_args<'a, 'b>(subcommand: App<'a, 'b>) -> App<'a, 'b> {
+    subcommand
+        .arg(
+            Arg::with_name("amount")
+                .long("amount")
+                .validator(is_amount)
+                .value_name("AMOUNT")
+                .takes_value(true)
+                .required(true)
+                .help("The amount the voucher pays."),
+        )
+        .arg(
+            Arg::with_name("nonce")
+                .long("nonce")
+                .validator(is_parsable::<u64>)
+                .value_name("NUMBER")
+                .takes_value(true)
+                .required(true)
+                .help("A number the recipient hasn't claimed a voucher with yet."),
+        )
+        .arg(
+            Arg::with_name("expiry")
+                .long("expiry")
+                .validator(is_parsable::<i64>)
+                .value_name("UNIX_TIMESTAMP")
+                .takes_value(true)
+                .required(true)
+                .help("The time after which the voucher can't be claimed."),
+        )
+}
+
+// Reads the amount in base units, nonce and expiry of a voucher
+fn voucher_of(
+    arg_matches: &ArgMatches,
+    config: &Config,
+    dist: &Distribution,
+) -> Result<(u64, u64, i64), Box<dyn Error>> {
+    let ui_amount = value_t_or_exit!(arg_matches, "amount", f64);
+    let amount = spl_token::ui_amount_to_amount(ui_amount, mint_decimals_of(config, dist.token())?);
+    let nonce = value_t_or_exit!(arg_matches, "nonce", u64);
+    let expiry = value_t_or_exit!(arg_matches, "expiry", i64);
+    Ok((amount, nonce, expiry))
+}
+
+// Resolves the distribution account from the arguments added by dist_account_args
+fn dist_account_of(
+    arg_matches: &ArgMatches,
+    config: &Config,
+) -> Result<(Option<StoredDistribution>, Pubkey), Box<dyn Error>> {
+    if let Some(state_file_path) = arg_matches.value_of("state_file") {
+        let saved_state: StoredDistribution = serde_json::from_reader(&File::open(state_file_path)?)?;
+        let dist_account = saved_state.dist_account;
+        return Ok((Some(saved_state), dist_account));
+    }
+
+    if let Some(dist_account) = pubkey_of(arg_matches, "dist_account") {
+        return Ok((None, dist_account));
+    }
+
+    let seed = pubkey_of(arg_matches, "seed").unwrap();
+    let project_name = value_t_or_exit!(arg_matches, "project_name", String);
+    let creator = pubkey_of(arg_matches, "creator").unwrap_or_else(|| config.fee_payer.pubkey());
+    let (dist_account, _) =
+        wave_dist::find_distribution_address(&config.program_id, &seed, &project_name, &creator);
+    Ok((None, dist_account))
+}
+
+fn get_program_config(config: &Config) -> Result<Option<ProgramConfig>, Box<dyn Error>> {
+    let (config_account, _) = wave_dist::find_config_address(&config.program_id);
+    match config
+        .rpc_client
+        .get_account_with_commitment(&config_account, config.rpc_client.commitment())?
+        .value
+    {
+        Some(config_account_on_chain) => {
+            Ok(Some(ProgramConfig::unpack(&config_account_on_chain.data)?))
+        }
+        None => Ok(None),
+    }
+}
+
+fn mint_decimals_of(config: &Config, token_address: &Pubkey) -> Result<u8, Box<dyn Error>> {
+    let mint_account_on_chain = config.rpc_client.get_account(token_address)?;
+    Ok(spl_token::state::Mint::unpack(&mint_account_on_chain.data)?.decimals)
+}
+
+// Reads a file with one wallet address per line, ignoring blank lines
+fn recipient_wallets_of(path: &str) -> Result<Vec<Pubkey>, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut recipient_wallets = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        recipient_wallets.push(line.parse::<Pubkey>()?);
+    }
+    Ok(recipient_wallets)
+}
+
+// Reads a CSV of NFT assignments, one `mint,recipient` pair per line. A
+// header line is allowed.
+fn nft_assignments_of(path: &str) -> Result<Vec<(Pubkey, Pubkey)>, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut assignments = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || (i == 0 && line.starts_with("mint")) {
+            continue;
+        }
+        let (mint, recipient) = line
+            .split_once(',')
+            .ok_or_else(|| format!("line {}: expected `mint,recipient`", i + 1))?;
+        assignments.push((mint.trim().parse::<Pubkey>()?, recipient.trim().parse::<Pubkey>()?));
+    }
+    Ok(assignments)
+}
+
+// Collects the wallets given with --address and in the --file of a denylist command
+fn denylist_wallets_of(arg_matches: &ArgMatches) -> Result<Vec<Pubkey>, Box<dyn Error>> {
+    let mut wallets = pubkeys_of(arg_matches, "address").unwrap_or_default();
+    if let Some(path) = arg_matches.value_of("file") {
+        wallets.extend(recipient_wallets_of(path)?);
+    }
+    wallets.sort();
+    wallets.dedup();
+    Ok(wallets)
+}
+
+fn denylist_wallet_args<'a, 'b>(subcommand: App<'a, 'b>) -> App<'a, 'b> {
+    subcommand
+        .arg(
+            Arg::with_name("admin")
+                .long("admin")
+                .value_name("KEYPAIR")
+                .validator(is_valid_signer)
+                .takes_value(true)
+                .help("The config admin. Defaults to the fee payer."),
+        )
+        .arg(
+            Arg::with_name("address")
+                .long("address")
+                .value_name("ADDRESS")
+                .validator(is_valid_pubkey)
+                .takes_value(true)
+                .multiple(true)
+                .required_unless("file")
+                .help("A wallet address."),
+        )
+        .arg(
+            Arg::with_name("file")
+                .long("file")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("A file with one wallet address per line."),
+        )
+}
+
+fn grant_recipient_args<'a, 'b>(subcommand: App<'a, 'b>) -> App<'a, 'b> {
+    dist_account_args(subcommand).arg(
+        Arg::with_name("recipient")
+            .long("recipient")
+            .value_name("ADDRESS")
+            .validator(is_valid_pubkey)
+            .takes_value(true)
+            .required(true)
+            .help("The grant recipient's wallet address."),
+    )
+}
+
+// The treasury token account that collects the protocol fee, if there is one
+fn treasury_token_account_of(
+    config: &Config,
+    token_address: &Pubkey,
+) -> Result<Option<Pubkey>, Box<dyn Error>> {
+    Ok(get_program_config(config)?
+        .filter(|program_config| program_config.protocol_fee_bps > 0)
+        .map(|program_config| get_associated_token_address(&program_config.treasury, token_address)))
+}
+
+// The account a distribution pays recipients out of: the mint for a mint
+// distribution, the funder's token account when funded by delegation, and
+// the distribution's own token account otherwise. NFT batches pay out of
+// their pool items and don't need one.
+fn payout_source_of(dist: &Distribution, dist_account: &Pubkey) -> Pubkey {
+    match dist.kind() {
+        DistributionKind::Mint => *dist.token(),
+        DistributionKind::NftBatch => *dist_account,
+        DistributionKind::Escrow | DistributionKind::Redemption | DistributionKind::Raffle => dist
+            .funding_source()
+            .copied()
+            .unwrap_or_else(|| get_associated_token_address(dist_account, dist.token())),
+    }
+}
+
+// The gate mint recipients of a gated distribution must hold
+fn gate_mint_of(dist: &Distribution) -> Option<&Pubkey> {
+    dist.gate().map(|(gate_mint, _)| gate_mint)
+}
+
+// The extra accounts a distribution's payout hook is passed with each
+// recipient, the same for all of them
+fn hook_accounts_of(
+    dist: &Distribution,
+    hook_accounts: &[Pubkey],
+) -> Result<Vec<AccountMeta>, Box<dyn Error>> {
+    if dist.hook_accounts_len() != hook_accounts.len() {
+        return Err(format!(
+            "the payout hook takes {} extra accounts, {} given",
+            dist.hook_accounts_len(),
+            hook_accounts.len()
+        )
+        .into());
+    }
+    Ok(hook_accounts
+        .iter()
+        .map(|hook_account| AccountMeta::new(*hook_account, false))
+        .collect())
+}
+
+// The forward address each wallet set for a distribution, if any
+fn forward_addresses_of(
+    config: &Config,
+    dist_account: &Pubkey,
+    wallets: &[Pubkey],
+) -> Result<Vec<Option<Pubkey>>, Box<dyn Error>> {
+    let forward_accounts: Vec<Pubkey> = wallets
+        .iter()
+        .map(|wallet| wave_dist::find_forward_address(&config.program_id, dist_account, wallet).0)
+        .collect();
+    let mut forward_addresses = vec![];
+    // The RPC returns at most 100 accounts per request
+    for forward_accounts_chunk in forward_accounts.chunks(100) {
+        for forward_account in config.rpc_client.get_multiple_accounts(forward_accounts_chunk)? {
+            forward_addresses.push(match forward_account {
+                Some(forward_account) => {
+                    Some(ForwardAddress::unpack(&forward_account.data)?.destination)
+                }
+                None => None,
+            });
+        }
+    }
+    Ok(forward_addresses)
+}
+
+// The wallets that were paid in the given round of a distribution
+// Wallets paid in `round` that haven't been paid their settle bonus yet
+fn unsettled_wallets_of(
+    config: &Config,
+    dist_account: &Pubkey,
+    round: u32,
+    wallets: &[Pubkey],
+) -> Result<Vec<Pubkey>, Box<dyn Error>> {
+    let mut paid_wallets = vec![];
+    // The RPC returns at most 100 accounts per request
+    for wallets_chunk in wallets.chunks(100) {
+        let receipt_accounts: Vec<Pubkey> = wallets_chunk
+            .iter()
+            .map(|wallet| wave_dist::find_receipt_address(&config.program_id, dist_account, wallet).0)
+            .collect();
+        for (wallet, receipt_account) in wallets_chunk
+            .iter()
+            .zip(config.rpc_client.get_multiple_accounts(&receipt_accounts)?)
+        {
+            if let Some(receipt_account) = receipt_account {
+                let receipt = RecipientReceipt::unpack(&receipt_account.data)?;
+                if receipt.round == round && receipt.settle_bonus == 0 {
+                    paid_wallets.push(*wallet);
+                }
+            }
+        }
+    }
+    Ok(paid_wallets)
+}
+
+// Submits a relay request, with the fee payer as relayer
+fn relay(config: &Config, relay_request: &RelayRequest) -> Result<Signature, Box<dyn Error>> {
+    let relayer = config.fee_payer.pubkey();
+    let dist_account = relay_request.dist_account;
+    let recipient = relay_request.recipient;
+    if relay_request.relayer != relayer {
+        return Err(format!("claim was signed for relayer {}", relay_request.relayer).into());
+    }
+
+    let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+    let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+    let attester = *dist
+        .attester()
+        .ok_or_else(|| format!("distribution {} has no attester", dist_account))?;
+
+    let token_address = *dist.token();
+    let dist_token_account = payout_source_of(&dist, &dist_account);
+    let forward_address = forward_addresses_of(config, &dist_account, &[recipient])?.remove(0);
+    let recipient_token_account = forward_address
+        .unwrap_or_else(|| get_associated_token_address(&recipient, &token_address));
+    let relayer_token_account = get_associated_token_address(&relayer, &token_address);
+
+    let voucher_message = wave_dist::ed25519::voucher_message(
+        &dist_account,
+        &recipient,
+        relay_request.amount,
+        relay_request.nonce,
+        relay_request.expiry,
+    );
+    let relay_message = wave_dist::ed25519::relay_message(
+        &dist_account,
+        &recipient,
+        &relayer,
+        relay_request.nonce,
+        relay_request.relayer_fee,
+    );
+    let voucher_signature = relay_request.voucher_signature.parse::<Signature>()?;
+    let recipient_signature = relay_request.recipient_signature.parse::<Signature>()?;
+
+    let mut instructions = vec![];
+    if forward_address.is_none()
+        && config.rpc_client.get_token_account(&recipient_token_account)?.is_none()
+    {
+        instructions.push(
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &relayer,
+                &recipient,
+                &token_address,
+            ),
+        );
+    }
+    if relayer_token_account != recipient_token_account
+        && config.rpc_client.get_token_account(&relayer_token_account)?.is_none()
+    {
+        instructions.push(
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &relayer,
+                &relayer,
+                &token_address,
+            ),
+        );
+    }
+    // The claim must come right after the signature checks
+    instructions.push(wave_dist::ed25519::new_ed25519_instruction(&[
+        (
+            &attester,
+            &<[u8; 64]>::try_from(voucher_signature.as_ref())?,
+            &voucher_message,
+        ),
+        (
+            &recipient,
+            &<[u8; 64]>::try_from(recipient_signature.as_ref())?,
+            &relay_message,
+        ),
+    ]));
+    instructions.push(wave_dist::instruction::relay_claim(
+        &config.program_id,
+        &dist_account,
+        &dist_token_account,
+        &recipient_token_account,
+        &recipient,
+        &relayer,
+        &relayer_token_account,
+        relay_request.amount,
+        relay_request.nonce,
+        relay_request.expiry,
+        relay_request.relayer_fee,
+        gate_mint_of(&dist),
+    ));
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&relayer));
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+    transaction.sign(&[config.fee_payer.as_ref()], recent_blockhash);
+
+    Ok(config
+        .rpc_client
+        .send_and_confirm_transaction_with_spinner(&transaction)?)
+}
+
+// Reads the skip events a best-effort distribute logged, as pairs of the
+// recipient's index within the instruction and the reason it was skipped
+fn skipped_recipients_of(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+) -> Result<Vec<(usize, String)>, Box<dyn Error>> {
+    let transaction = rpc_client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(rpc_client.commitment()),
+            max_supported_transaction_version: None,
+        },
+    )?;
+    let log_messages = transaction
+        .transaction
+        .meta
+        .and_then(|meta| meta.log_messages)
+        .unwrap_or_default();
+
+    let mut skipped = vec![];
+    for log_message in log_messages {
+        let event = match log_message.strip_prefix("Program log: Skip: ") {
+            Some(event) => event,
+            None => continue,
+        };
+        let mut index = None;
+        let mut reason = None;
+        for field in event.split_whitespace() {
+            match field.split_once('=') {
+                Some(("index", value)) => index = value.parse::<usize>().ok(),
+                Some(("reason", value)) => reason = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+        if let (Some(index), Some(reason)) = (index, reason) {
+            skipped.push((index, reason));
+        }
+    }
+    Ok(skipped)
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     solana_logger::setup_with_default("solana=info");
 
@@ -199,63 +714,70 @@ fn main() -> Result<(), Box<dyn Error>> {
                             "A pubkey that programmatically determines the \
                              address of the distribution account.",
                         ),
-                ),
+                )
+                .arg(project_name_arg().required(true))
+                .arg(creator_arg()),
         )
         .subcommand(
-            SubCommand::with_name("change-dist-authority")
-                .about("Changes the distribution authority of a distribution")
-                .arg(
-                    Arg::with_name("state_file")
-                        .long("state-file")
-                        .value_name("PATH")
-                        .takes_value(true)
-                        .help(
-                            "A state file created by create-distribution to \
-                             fill out most required values automatically.",
-                        ),
-                )
-                .arg(
-                    Arg::with_name("seed")
-                        .long("seed")
-                        .validator(is_valid_pubkey)
-                        .value_name("ADDRESS")
-                        .takes_value(true)
-                        .conflicts_with("dist_account")
-                        .conflicts_with("state_file")
-                        .help(
-                            "A pubkey that programmatically determines the \
-                             address of the distribution account.",
-                        ),
-                )
-                .arg(
-                    Arg::with_name("dist_account")
-                        .long("dist-account")
-                        .validator(is_valid_pubkey)
-                        .value_name("ADDRESS")
-                        .takes_value(true)
-                        .required_unless("seed")
-                        .conflicts_with("state_file")
-                        .help("The distribution account address."),
-                )
-                .arg(
-                    Arg::with_name("dist_authority")
-                        .long("dist-authority")
-                        .value_name("KEYPAIR")
-                        .validator(is_valid_signer)
-                        .takes_value(true)
-                        .required(true)
-                        .conflicts_with("state_file")
-                        .help("The current account with distribution authority."),
-                )
-                .arg(
-                    Arg::with_name("new_dist_authority")
-                        .long("new-dist-authority")
-                        .value_name("KEYPAIR")
-                        .validator(is_valid_signer)
-                        .takes_value(true)
-                        .required(true)
-                        .help("The new account with distribution authority."),
+            dist_account_args(
+                SubCommand::with_name("propose-dist-authority").about(
+                    "Proposes a new distribution authority, which takes over once it accepts",
                 ),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help(
+                        "The current account with distribution authority. \
+                         Defaults to the fee payer.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("new_dist_authority")
+                    .long("new-dist-authority")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .required(true)
+                    .help("The proposed account with distribution authority."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("accept-dist-authority")
+                    .about("Accepts a proposed distribution authority"),
+            )
+            .arg(
+                Arg::with_name("new_dist_authority")
+                    .long("new-dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help(
+                        "The proposed account with distribution authority. \
+                         Defaults to the fee payer.",
+                    ),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("cancel-dist-authority")
+                    .about("Withdraws a proposed distribution authority"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help(
+                        "The current account with distribution authority. \
+                         Defaults to the fee payer.",
+                    ),
+            ),
         )
         .subcommand(
             SubCommand::with_name("show-distribution")
@@ -293,6 +815,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .conflicts_with("state_file")
                         .help("The distribution account address."),
                 )
+                .arg(project_name_arg())
+                .arg(creator_arg())
         )
         .subcommand(
             SubCommand::with_name("fund-distribution")
@@ -330,6 +854,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .conflicts_with("state_file")
                         .help("The distribution account address."),
                 )
+                .arg(project_name_arg())
+                .arg(creator_arg())
                 .arg(
                     Arg::with_name("amount")
                         .long("amount")
@@ -359,6 +885,16 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .takes_value(true)
                         .required(true)
                         .help("The account that will fund the distribution."),
+                )
+                .arg(
+                    Arg::with_name("delegate")
+                        .long("delegate")
+                        .takes_value(false)
+                        .help(
+                            "Keep the tokens in the funder's token account and \
+                             approve the distribution to spend them as delegate, \
+                             instead of transferring them to the distribution.",
+                        ),
                 ),
         )
         .subcommand(
@@ -398,6 +934,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .conflicts_with("state_file")
                         .help("The distribution account address."),
                 )
+                .arg(project_name_arg())
+                .arg(creator_arg())
                 .arg(
                     Arg::with_name("dist_authority")
                         .long("dist-authority")
@@ -459,6 +997,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .conflicts_with("state_file")
                         .help("The distribution account address."),
                 )
+                .arg(project_name_arg())
+                .arg(creator_arg())
                 .arg(
                     Arg::with_name("dist_authority")
                         .long("dist-authority")
@@ -499,77 +1039,976 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .takes_value(true)
                         .default_value("0")
                         .help("Skips the specified number of recipients. Useful to continue from failure."),
+                )
+                .arg(
+                    Arg::with_name("hook_account")
+                        .long("hook-account")
+                        .value_name("ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .multiple(true)
+                        .help(
+                            "An extra account passed writable to the payout hook with every \
+                             recipient, in the order given.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("best_effort")
+                        .long("best-effort")
+                        .takes_value(false)
+                        .help(
+                            "Skip recipients that can't be paid, such as frozen or \
+                             already paid accounts, instead of failing the batch.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("skip_report")
+                        .long("skip-report")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .requires("best_effort")
+                        .help("Write the skipped recipients to this CSV file for follow-up."),
                 ),
         )
-        .get_matches();
-
-    let mut wallet_manager = None;
-
-    let cli_config = if let Some(config_file) = matches.value_of("config_file") {
-        solana_cli_config::Config::load(config_file).unwrap_or_default()
-    } else {
-        solana_cli_config::Config::default()
-    };
-
-    let config = {
-        let json_rpc_url = value_t!(matches, "json_rpc_url", String)
-            .unwrap_or_else(|_| cli_config.json_rpc_url.clone());
-
-        let fee_payer = signer_from_path(
-            &matches,
-            matches
-                .value_of("fee_payer")
-                .unwrap_or(&cli_config.keypair_path),
-            "fee_payer",
-            &mut wallet_manager,
-        )?;
-        let program_id = String_to_pubkey(DISTRIB_PROGRAM.to_string())?;
-        Config {
-            rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
-            fee_payer,
-            program_id,
-        }
-    };
-
-    match matches.subcommand() {
-        ("create-and-fund", Some(arg_matches)) => {
-            // default seed to wallet
-            let seed = config.fee_payer.pubkey();
-            let token_address = String_to_pubkey(TOKEN_ADDRESS.to_string())?;
-            let dist_authority = config.fee_payer.pubkey();
-            let dist_authority_input: &String = &config.fee_payer.pubkey().to_string();
-            let mut project_name = value_t_or_exit!(arg_matches, "PROJECT_NAME",String);
-            let participants_file_path = project_name.to_owned() + ".txt";
-            let output_path = project_name.to_owned() + ".state";
-            let output_file = File::create(&output_path)?;
-            let ui_amount = value_t_or_exit!(arg_matches, "amount", f64);
-            // read user list
-            //let file = File::open(participants_file_path.clone())?;
-            //let reader = BufReader::new(file);
-            //let mut participants = vec![];
-            // Reading line by line
-            /*let mut count : u16 = 0;
-            for line in reader.lines() {
-                count = count +1 ;
-                participants.push(Participant{address: String_to_pubkey(line?)?});
-                
-            }
-            */
-            let max_recipients = 500; // count
-            let  saved_projectname = project_name.clone() ;
-            // Convert project_name to pubkey size
-            if project_name.len() < mem::size_of::<Pubkey>() {
-        
-                while project_name.len() != mem::size_of::<Pubkey>() {
-                    project_name = project_name + "0";
-                }
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-metadata")
+                    .about("Creates or updates the metadata of a distribution"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help(
+                        "The account with distribution authority. \
+                         Defaults to the fee payer.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("description")
+                    .long("description")
+                    .value_name("TEXT")
+                    .takes_value(true)
+                    .help("A description of the distribution."),
+            )
+            .arg(
+                Arg::with_name("uri")
+                    .long("uri")
+                    .value_name("URI")
+                    .takes_value(true)
+                    .help("A link to the off-chain campaign page."),
+            )
+            .arg(
+                Arg::with_name("creator_label")
+                    .long("creator-label")
+                    .value_name("TEXT")
+                    .takes_value(true)
+                    .help("A display name for the distribution creator."),
+            )
+            .arg(
+                Arg::with_name("category")
+                    .long("category")
+                    .value_name("TEXT")
+                    .takes_value(true)
+                    .help("The category the distribution belongs to."),
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("init-config")
+                .about("Initializes the program config. Must be signed by the program upgrade authority.")
+                .arg(
+                    Arg::with_name("upgrade_authority")
+                        .long("upgrade-authority")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help("The program upgrade authority. Defaults to the fee payer."),
+                )
+                .arg(
+                    Arg::with_name("admin")
+                        .long("admin")
+                        .value_name("ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .required(true)
+                        .help("The account that will administer the program config."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("update-config")
+                .about("Updates the program config")
+                .arg(
+                    Arg::with_name("admin")
+                        .long("admin")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help("The config admin. Defaults to the fee payer."),
+                )
+                .arg(
+                    Arg::with_name("paused")
+                        .long("paused")
+                        .value_name("BOOL")
+                        .validator(is_parsable::<bool>)
+                        .takes_value(true)
+                        .help("Pauses or resumes distributions program-wide."),
+                )
+                .arg(
+                    Arg::with_name("protocol_fee_bps")
+                        .long("protocol-fee-bps")
+                        .value_name("BPS")
+                        .validator(is_parsable::<u16>)
+                        .takes_value(true)
+                        .help("The protocol fee taken when funding, in basis points."),
+                )
+                .arg(
+                    Arg::with_name("treasury")
+                        .long("treasury")
+                        .value_name("ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The owner of the token accounts that collect the protocol fee."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("set-allowed-mints")
+                .about("Replaces the mints that may be distributed. Without mints, any mint is allowed.")
+                .arg(
+                    Arg::with_name("admin")
+                        .long("admin")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help("The config admin. Defaults to the fee payer."),
+                )
+                .arg(
+                    Arg::with_name("mint")
+                        .long("mint")
+                        .value_name("ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .multiple(true)
+                        .help("A token mint that may be distributed."),
+                ),
+        )
+        .subcommand(SubCommand::with_name("show-config").about("Shows the program config"))
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-release-rate")
+                    .about("Streams a distribution at a fixed amount per epoch. Must be set before it begins."),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("rate")
+                    .long("rate")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help(
+                        "The amount of tokens released to all recipients per epoch. \
+                         Zero makes it a one-shot distribution.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("recipients_file")
+                    .long("recipients-file")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .help(
+                        "A file with one recipient wallet address per line, the only wallets \
+                         the stream may drip to. Required with a non-zero rate.",
+                    ),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-decay")
+                    .about("Makes recipient shares shrink the longer they go unpaid. Must be set before it begins."),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("grace_epochs")
+                    .long("grace-epochs")
+                    .validator(is_parsable::<u64>)
+                    .value_name("EPOCHS")
+                    .takes_value(true)
+                    .default_value("0")
+                    .help("The epochs after the distribution begins before shares start to decay."),
+            )
+            .arg(
+                Arg::with_name("bps_per_epoch")
+                    .long("bps-per-epoch")
+                    .validator(is_parsable::<u16>)
+                    .value_name("BPS")
+                    .takes_value(true)
+                    .required(true)
+                    .help(
+                        "The basis points of its share an unpaid recipient loses per epoch. \
+                         Zero removes the decay.",
+                    ),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("settle")
+                    .about("Splits what a round forfeited to decay among the recipients it paid"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("recipients_file")
+                    .long("recipients-file")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .required(true)
+                    .help(
+                        "A file with one recipient wallet address per line. \
+                         Only the wallets paid in the current round and not settled yet are settled.",
+                    ),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("transfer-mint-authority")
+                    .about(
+                        "Hands mint authority over the distribution token to an unfunded \
+                         distribution, which then mints each recipient share.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("mint_authority")
+                    .long("mint-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The current mint authority of the token. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("supply_cap")
+                    .long("supply-cap")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The most tokens the distribution may ever mint."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("deposit-nfts")
+                    .about(
+                        "Deposits the NFTs of a CSV of assignments into an NFT batch \
+                         distribution, making an unfunded distribution an NFT batch.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("owner")
+                    .long("owner")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help(
+                        "The account holding the NFTs in its associated token accounts. \
+                         Defaults to the fee payer.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("assignments")
+                    .long("assignments")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .required(true)
+                    .help("A CSV file with one `mint,recipient` pair per line."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("distribute-nfts")
+                    .about(
+                        "Sends each recipient of an NFT batch distribution the NFT assigned \
+                         to it, creating associated token accounts where needed.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("assignments")
+                    .long("assignments")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .required(true)
+                    .help("A CSV file with one `mint,recipient` pair per line."),
+            )
+            .arg(
+                Arg::with_name("hook_account")
+                    .long("hook-account")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .multiple(true)
+                    .help(
+                        "An extra account passed writable to the payout hook with every \
+                         recipient, in the order given.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("best_effort")
+                    .long("best-effort")
+                    .takes_value(false)
+                    .help("Skip ineligible recipients instead of failing the whole batch."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-redemption")
+                    .about(
+                        "Lets anyone redeem an input token for the funded tokens of a \
+                         distribution at a fixed rate, up to a cap.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("input_mint")
+                    .long("input-mint")
+                    .validator(is_valid_pubkey)
+                    .value_name("ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The token mint redeemers give up."),
+            )
+            .arg(
+                Arg::with_name("rate_numerator")
+                    .long("rate-numerator")
+                    .validator(is_parsable::<u64>)
+                    .value_name("NUMBER")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Base units of the funded token paid per --rate-denominator input base units."),
+            )
+            .arg(
+                Arg::with_name("rate_denominator")
+                    .long("rate-denominator")
+                    .validator(is_parsable::<u64>)
+                    .value_name("NUMBER")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Base units of the input token redeemed for --rate-numerator base units."),
+            )
+            .arg(
+                Arg::with_name("cap")
+                    .long("cap")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The most funded tokens paid out to redeemers."),
+            )
+            .arg(
+                Arg::with_name("burn")
+                    .long("burn")
+                    .takes_value(false)
+                    .help("Burn redeemed input tokens instead of keeping them in the distribution's vault."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("redeem")
+                    .about("Redeems input tokens for the funded tokens of a redemption distribution"),
+            )
+            .arg(
+                Arg::with_name("redeemer")
+                    .long("redeemer")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account giving up the input tokens. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("amount")
+                    .long("amount")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The amount of input tokens to redeem."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("begin-raffle")
+                    .about("Begins a distribution as a raffle between the given entrants"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("entrants_file")
+                    .long("entrants-file")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .required(true)
+                    .help(
+                        "A file with one entrant wallet address per line. Keep it, \
+                         claiming prizes needs the same file.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("winners")
+                    .long("winners")
+                    .validator(is_parsable::<u16>)
+                    .value_name("NUMBER")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The number of winners to draw, who share the funded amount."),
+            ),
+        )
+        .subcommand(dist_account_args(
+            SubCommand::with_name("draw")
+                .about("Draws the winners of a raffle"),
+        ))
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("claim-raffle-prizes")
+                    .about("Pays the unclaimed prizes of a drawn raffle to its winners"),
+            )
+            .arg(
+                Arg::with_name("entrants_file")
+                    .long("entrants-file")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The entrants file the raffle was begun with."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("drip")
+                    .about("Pays recipients of a streaming distribution what has been released to them"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("recipients_file")
+                    .long("recipients-file")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .required(true)
+                    .help("A file with one recipient wallet address per line."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("start-round")
+                    .about("Closes the current round of a distribution and opens the next one"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-attester")
+                    .about("Sets the key whose signed vouchers recipients may claim with"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("attester")
+                    .long("attester")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .required_unless("remove")
+                    .help("The attester's public key."),
+            )
+            .arg(
+                Arg::with_name("remove")
+                    .long("remove")
+                    .takes_value(false)
+                    .conflicts_with("attester")
+                    .help("Stop accepting vouchers."),
+            ),
+        )
+        .subcommand(
+            voucher_args(dist_account_args(
+                SubCommand::with_name("sign-voucher")
+                    .about("Signs a voucher letting a recipient claim from a distribution"),
+            ))
+            .arg(
+                Arg::with_name("recipient")
+                    .long("recipient")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .required(true)
+                    .help("The recipient wallet the voucher pays."),
+            )
+            .arg(
+                Arg::with_name("attester")
+                    .long("attester")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The attester of the distribution. Defaults to the fee payer."),
+            ),
+        )
+        .subcommand(
+            voucher_args(dist_account_args(
+                SubCommand::with_name("claim-voucher")
+                    .about("Claims a voucher signed by the attester of a distribution"),
+            ))
+            .arg(
+                Arg::with_name("recipient")
+                    .long("recipient")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .required(true)
+                    .help("The recipient wallet the voucher pays."),
+            )
+            .arg(
+                Arg::with_name("signature")
+                    .long("signature")
+                    .value_name("SIGNATURE")
+                    .validator(is_parsable::<Signature>)
+                    .takes_value(true)
+                    .required(true)
+                    .help("The attester's signature of the voucher, as printed by sign-voucher."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-relay-fee-cap")
+                    .about("Sets the most relayers are reimbursed for each voucher claim they relay"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("relay_fee_cap")
+                    .long("relay-fee-cap")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The most a relayer is reimbursed per claim, in distribution tokens."),
+            ),
+        )
+        .subcommand(
+            voucher_args(dist_account_args(
+                SubCommand::with_name("sign-relay-claim")
+                    .about("Asks relayers to claim a voucher on a recipient's behalf"),
+            ))
+            .arg(
+                Arg::with_name("recipient")
+                    .long("recipient")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The recipient wallet the voucher pays. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("voucher_signature")
+                    .long("voucher-signature")
+                    .value_name("SIGNATURE")
+                    .validator(is_parsable::<Signature>)
+                    .takes_value(true)
+                    .required(true)
+                    .help("The attester's signature of the voucher, as printed by sign-voucher."),
+            )
+            .arg(
+                Arg::with_name("relayer")
+                    .long("relayer")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .required(true)
+                    .help("The relayer allowed to submit the claim, the fee payer of its relay."),
+            )
+            .arg(
+                Arg::with_name("relayer_fee")
+                    .long("relayer-fee")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .default_value("0")
+                    .help("The reimbursement the relayer may take from the distribution."),
+            )
+            .arg(
+                Arg::with_name("queue_file")
+                    .long("queue-file")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The relay queue file to add the signed claim to."),
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("relay")
+                .about("Submits the signed voucher claims of a relay queue file, paying their fees")
+                .arg(
+                    Arg::with_name("queue_file")
+                        .long("queue-file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("A relay queue file written by sign-relay-claim."),
+                ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-gate")
+                    .about("Only pays recipients holding enough of a gate token"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("gate_mint")
+                    .long("gate-mint")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .required_unless("remove")
+                    .help("The mint recipients must hold, such as a governance token or NFT."),
+            )
+            .arg(
+                Arg::with_name("min_balance")
+                    .long("min-balance")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .default_value("1")
+                    .help("The least recipients must hold of the gate mint."),
+            )
+            .arg(
+                Arg::with_name("remove")
+                    .long("remove")
+                    .takes_value(false)
+                    .conflicts_with("gate_mint")
+                    .help("Remove the gate."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-hook")
+                    .about("Has distribute and distribute-nfts call a program after paying each recipient"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("hook_program")
+                    .long("hook-program")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .required_unless("remove")
+                    .help("The program to call about each payout."),
+            )
+            .arg(
+                Arg::with_name("hook_accounts_len")
+                    .long("hook-accounts-len")
+                    .validator(is_parsable::<u8>)
+                    .value_name("COUNT")
+                    .takes_value(true)
+                    .default_value("0")
+                    .help("The number of extra accounts the hook program takes."),
+            )
+            .arg(
+                Arg::with_name("skip_unavailable")
+                    .long("skip-unavailable")
+                    .takes_value(false)
+                    .help(
+                        "Keep paying recipients if the hook program can no longer be called, \
+                         rather than failing.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("remove")
+                    .long("remove")
+                    .takes_value(false)
+                    .conflicts_with("hook_program")
+                    .help("Remove the hook."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("migrate")
+                    .about("Migrates a distribution created by an earlier version of the program"),
+            )
+            .arg(
+                Arg::with_name("dist_authority")
+                    .long("dist-authority")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The account with distribution authority, paying the rent for the larger account. Defaults to the fee payer."),
+            ),
+        )
+        .subcommand(
+            dist_account_args(
+                SubCommand::with_name("set-forward-address")
+                    .about("Has a recipient's payouts sent to a different token account"),
+            )
+            .arg(
+                Arg::with_name("wallet")
+                    .long("wallet")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help("The recipient wallet. Defaults to the fee payer."),
+            )
+            .arg(
+                Arg::with_name("destination")
+                    .long("destination")
+                    .value_name("ADDRESS")
+                    .validator(is_valid_pubkey)
+                    .takes_value(true)
+                    .required(true)
+                    .help("The token account to send the wallet's payouts to."),
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("denylist")
+                .about("Manages the wallets that no distribution may pay")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(denylist_wallet_args(
+                    SubCommand::with_name("add").about("Adds wallets to the denylist"),
+                ))
+                .subcommand(denylist_wallet_args(
+                    SubCommand::with_name("remove").about("Removes wallets from the denylist"),
+                ))
+                .subcommand(SubCommand::with_name("list").about("Lists the denylisted wallets")),
+        )
+        .subcommand(
+            SubCommand::with_name("grant")
+                .about("Manages revocable grants vesting to single recipients")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    grant_recipient_args(
+                        SubCommand::with_name("create")
+                            .about("Grants tokens from the dist authority's token account"),
+                    )
+                    .arg(
+                        Arg::with_name("dist_authority")
+                            .long("dist-authority")
+                            .value_name("KEYPAIR")
+                            .validator(is_valid_signer)
+                            .takes_value(true)
+                            .help("The account with distribution authority. Defaults to the fee payer."),
+                    )
+                    .arg(
+                        Arg::with_name("amount")
+                            .long("amount")
+                            .validator(is_amount)
+                            .value_name("AMOUNT")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The amount of tokens to grant."),
+                    )
+                    .arg(
+                        Arg::with_name("start_epoch")
+                            .long("start-epoch")
+                            .validator(is_parsable::<u64>)
+                            .value_name("EPOCH")
+                            .takes_value(true)
+                            .help("The epoch the grant starts vesting in. Defaults to the current epoch."),
+                    )
+                    .arg(
+                        Arg::with_name("end_epoch")
+                            .long("end-epoch")
+                            .validator(is_parsable::<u64>)
+                            .value_name("EPOCH")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The epoch the grant is fully vested in."),
+                    ),
+                )
+                .subcommand(grant_recipient_args(
+                    SubCommand::with_name("release")
+                        .about("Releases what has vested of a grant to its recipient"),
+                ))
+                .subcommand(
+                    grant_recipient_args(
+                        SubCommand::with_name("revoke")
+                            .about("Revokes a grant, returning its unvested tokens"),
+                    )
+                    .arg(
+                        Arg::with_name("dist_authority")
+                            .long("dist-authority")
+                            .value_name("KEYPAIR")
+                            .validator(is_valid_signer)
+                            .takes_value(true)
+                            .help("The account with distribution authority. Defaults to the fee payer."),
+                    )
+                    .arg(
+                        Arg::with_name("destination")
+                            .long("destination")
+                            .value_name("ADDRESS")
+                            .validator(is_valid_pubkey)
+                            .takes_value(true)
+                            .help(
+                                "The token account receiving the unvested tokens. \
+                                 Defaults to the dist authority's associated token account.",
+                            ),
+                    ),
+                )
+                .subcommand(dist_account_args(
+                    SubCommand::with_name("list").about("Lists the grants of a distribution"),
+                )),
+        )
+        .get_matches();
+
+    let mut wallet_manager = None;
+
+    let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+        solana_cli_config::Config::load(config_file).unwrap_or_default()
+    } else {
+        solana_cli_config::Config::default()
+    };
+
+    let config = {
+        let json_rpc_url = value_t!(matches, "json_rpc_url", String)
+            .unwrap_or_else(|_| cli_config.json_rpc_url.clone());
+
+        let fee_payer = signer_from_path(
+            &matches,
+            matches
+                .value_of("fee_payer")
+                .unwrap_or(&cli_config.keypair_path),
+            "fee_payer",
+            &mut wallet_manager,
+        )?;
+        let program_id = String_to_pubkey(DISTRIB_PROGRAM.to_string())?;
+        Config {
+            rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
+            fee_payer,
+            program_id,
+        }
+    };
+
+    match matches.subcommand() {
+        ("create-and-fund", Some(arg_matches)) => {
+            // default seed to wallet
+            let seed = config.fee_payer.pubkey();
+            let token_address = String_to_pubkey(TOKEN_ADDRESS.to_string())?;
+            let dist_authority = config.fee_payer.pubkey();
+            let dist_authority_input: &String = &config.fee_payer.pubkey().to_string();
+            let project_name = value_t_or_exit!(arg_matches, "PROJECT_NAME",String);
+            let participants_file_path = project_name.to_owned() + ".txt";
+            let output_path = project_name.to_owned() + ".state";
+            let output_file = File::create(&output_path)?;
+            let ui_amount = value_t_or_exit!(arg_matches, "amount", f64);
+            // read user list
+            //let file = File::open(participants_file_path.clone())?;
+            //let reader = BufReader::new(file);
+            //let mut participants = vec![];
+            // Reading line by line
+            /*let mut count : u16 = 0;
+            for line in reader.lines() {
+                count = count +1 ;
+                participants.push(Participant{address: String_to_pubkey(line?)?});
                 
             }
-            let encoded = bs58::encode(project_name.clone()).into_string();
-        
-            let project_pubkey= String_to_pubkey(encoded)?;
-            let (dist_account, seed_bump) =
-                Pubkey::find_program_address(&[seed.as_ref(),project_pubkey.as_ref()], &config.program_id);
+            */
+            let max_recipients = 500; // count
+            let  saved_projectname = project_name.clone() ;
+            let (dist_account, seed_bump) = wave_dist::find_distribution_address(
+                &config.program_id,
+                &seed,
+                &project_name,
+                &config.fee_payer.pubkey(),
+            );
 
             let dist_token_account = get_associated_token_address(&dist_account, &token_address);
 
@@ -580,12 +2019,11 @@ fn main() -> Result<(), Box<dyn Error>> {
             println!("  Token address: {}", token_address);
             println!("  Token account: {}", dist_token_account);
             println!("  Fee payer: {}", config.fee_payer.pubkey());
-            println!("Projectname to pubkey {}", project_name);
+            println!("  Project name: {}", project_name);
 
             let dist_json = StoredDistribution {
                 program_id: config.program_id,
                 project_name: saved_projectname,
-                project_pubkey: project_pubkey,
                 dist_account,
                 max_recipients,
                 dist_authority,
@@ -602,7 +2040,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                     &dist_account,
                     &config.fee_payer.pubkey(),
                     &seed,
-                    &project_pubkey,
+                    &project_name,
                     seed_bump,
                     max_recipients,
                     &dist_authority,
@@ -660,7 +2098,9 @@ fn main() -> Result<(), Box<dyn Error>> {
                 &funder_token_account,
                 &dist_account,
                 &dist_token_account,
+                treasury_token_account_of(&config, &token_address)?.as_ref(),
                 amount,
+                false,
             )];
 
             let mut transaction =
@@ -687,13 +2127,15 @@ fn main() -> Result<(), Box<dyn Error>> {
             let dist_authority = pubkey_of(arg_matches, "dist_authority").unwrap();
             let output_path = value_t_or_exit!(arg_matches, "output", String);
             let output_file = File::create(&output_path)?;
-            let mut project_name = value_t_or_exit!(arg_matches, "PROJECT_NAME",String);
+            let project_name = value_t_or_exit!(arg_matches, "PROJECT_NAME",String);
             let participants_file_path = project_name.to_owned() + ".txt";
 
-            // Seed can be replaced by user pubkey 
-            // 
-            let (dist_account, seed_bump) =
-                Pubkey::find_program_address(&[seed.as_ref()], &config.program_id);
+            let (dist_account, seed_bump) = wave_dist::find_distribution_address(
+                &config.program_id,
+                &seed,
+                &project_name,
+                &config.fee_payer.pubkey(),
+            );
 
             let dist_token_account = get_associated_token_address(&dist_account, &token_address);
 
@@ -705,22 +2147,10 @@ fn main() -> Result<(), Box<dyn Error>> {
             println!("  Token account: {}", dist_token_account);
             println!("  Fee payer: {}", config.fee_payer.pubkey());
             println!();
-             // Convert project_name to pubkey size
-             if project_name.len() < mem::size_of::<Pubkey>() {
-        
-                while project_name.len() != mem::size_of::<Pubkey>() {
-                    project_name = project_name + "0";
-                }
-                
-            }
-            let encoded = bs58::encode(project_name.clone()).into_string();
-        
-            let project_pubkey= String_to_pubkey(encoded)?;
 
             let dist_json = StoredDistribution {
                 program_id: config.program_id,
-                project_name: project_name,
-                project_pubkey: project_pubkey,
+                project_name: project_name.clone(),
                 dist_account,
                 max_recipients,
                 dist_authority,
@@ -729,8 +2159,6 @@ fn main() -> Result<(), Box<dyn Error>> {
                 token_account: dist_token_account,
                 recipient_file: participants_file_path
             };
-            let project_name = value_t_or_exit!(arg_matches, "PROJECT_NAME",String);
-            let project_pubkey = String_to_pubkey(project_name)?;
             let instructions = vec![
                 wave_dist::instruction::init_distribution(
                     &config.program_id,
@@ -738,7 +2166,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                     &dist_account,
                     &config.fee_payer.pubkey(),
                     &seed,
-                    &project_pubkey,
+                    &project_name,
                     seed_bump,
                     max_recipients,
                     &dist_authority,
@@ -769,8 +2197,15 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
         ("dist-account-from-seed", Some(arg_matches)) => {
             let seed = pubkey_of(arg_matches, "seed").unwrap();
-            let (dist_account, _) =
-                Pubkey::find_program_address(&[seed.as_ref()], &config.program_id);
+            let project_name = value_t_or_exit!(arg_matches, "project_name", String);
+            let creator =
+                pubkey_of(arg_matches, "creator").unwrap_or_else(|| config.fee_payer.pubkey());
+            let (dist_account, _) = wave_dist::find_distribution_address(
+                &config.program_id,
+                &seed,
+                &project_name,
+                &creator,
+            );
             println!("{}", dist_account);
         }
         ("show-distribution", Some(arg_matches)) => {
@@ -791,9 +2226,14 @@ fn main() -> Result<(), Box<dyn Error>> {
                 let dist_account = match dist_account {
                     Some(pubkey) => pubkey,
                     None => {
-                        let (dist_account, _) = Pubkey::find_program_address(
-                            &[seed.unwrap().as_ref()],
+                        let project_name = value_t_or_exit!(arg_matches, "project_name", String);
+                        let creator = pubkey_of(arg_matches, "creator")
+                            .unwrap_or_else(|| config.fee_payer.pubkey());
+                        let (dist_account, _) = wave_dist::find_distribution_address(
                             &config.program_id,
+                            &seed.unwrap(),
+                            &project_name,
+                            &creator,
                         );
                         dist_account
                     }
@@ -829,8 +2269,65 @@ fn main() -> Result<(), Box<dyn Error>> {
             );
 
             println!("Distribution {}", dist_account);
+            println!("  Project name: {}", dist.project_name());
+            println!("  Creator: {}", dist.creator());
             println!("  Dist authority: {}", dist.dist_authority());
+            if let Some(pending_dist_authority) = dist.pending_dist_authority() {
+                println!("  Pending dist authority: {}", pending_dist_authority);
+            }
             println!("  Token address: {}", dist.token());
+            if let Some(funding_source) = dist.funding_source() {
+                println!("  Funding source: {}", funding_source);
+            }
+            if dist.kind() == DistributionKind::Mint {
+                let ui_supply_cap = spl_token::amount_to_ui_amount(
+                    dist.supply_cap(),
+                    dist_token_account_on_chain.token_amount.decimals,
+                );
+                println!("  Mints payouts up to: {}", ui_supply_cap);
+            }
+            if dist.kind() == DistributionKind::NftBatch {
+                println!("  NFTs in pool: {}", dist.unpaid_amount());
+            }
+            if dist.kind() == DistributionKind::Redemption {
+                let (rate_numerator, rate_denominator) = dist.redemption_rate();
+                let ui_redemption_cap = spl_token::amount_to_ui_amount(
+                    dist.redemption_cap(),
+                    dist_token_account_on_chain.token_amount.decimals,
+                );
+                println!("  Redeems: {}", dist.input_mint());
+                println!(
+                    "  Redemption rate (base units): {} per {}",
+                    rate_numerator, rate_denominator
+                );
+                println!("  Redemption cap: {}", ui_redemption_cap);
+                println!("  Burns input: {}", dist.burn_input());
+            }
+            if let Some((gate_mint, gate_min_balance)) = dist.gate() {
+                println!("  Gate mint: {}", gate_mint);
+                println!("  Gate min balance (base units): {}", gate_min_balance);
+            }
+            if let Some(attester) = dist.attester() {
+                println!("  Attester: {}", attester);
+                println!("  Relay fee cap (base units): {}", dist.relay_fee_cap());
+            }
+            if let Some(hook_program) = dist.hook_program() {
+                println!("  Hook program: {}", hook_program);
+                println!(
+                    "  Hook authority: {}",
+                    wave_dist::find_hook_authority_address(&config.program_id, &dist_account).0
+                );
+                println!("  Hook extra accounts: {}", dist.hook_accounts_len());
+                println!("  Hook failure policy: {:?}", dist.hook_failure_policy());
+            }
+            if dist.kind() == DistributionKind::Raffle {
+                println!("  Entrants root: {}", dist.entrants_root());
+                println!("  Entrants: {}", dist.num_entrants());
+                println!("  Draw slot: {}", dist.raffle_draw_slot());
+                if dist.raffle_drawn() {
+                    println!("  Winning entrants: {:?}", dist.raffle_winners());
+                }
+            }
             println!("  Max recipients: {}", dist.max_recipients());
             println!("  Has started: {}", dist.has_started());
             println!("  Num recipients: {}", dist.num_recipients());
@@ -839,6 +2336,68 @@ fn main() -> Result<(), Box<dyn Error>> {
             println!("  Sent recipients: {}", dist.sent_recipients());
             println!("  Recipient share: {}", ui_recipient_share);
             println!("  Recipient share (base units): {}", dist.recipient_share());
+            println!("  Paid amount (base units): {}", dist.paid_amount());
+            println!("  Round: {}", dist.round());
+            if dist.is_streaming() {
+                let ui_release_rate = spl_token::amount_to_ui_amount(
+                    dist.release_rate(),
+                    dist_token_account_on_chain.token_amount.decimals,
+                );
+                println!("  Release rate per epoch: {}", ui_release_rate);
+                println!("  Release rate per epoch (base units): {}", dist.release_rate());
+                if dist.has_started() {
+                    println!("  Release start epoch: {}", dist.release_start_epoch());
+                }
+            }
+            if let Some((grace_epochs, bps_per_epoch)) = dist.decay() {
+                println!("  Decay grace epochs: {}", grace_epochs);
+                println!("  Decay per epoch (bps): {}", bps_per_epoch);
+                println!("  Forfeited amount (base units): {}", dist.forfeited_amount());
+            }
+            if dist.is_settled() {
+                println!("  Settle bonus (base units): {}", dist.settle_bonus());
+            }
+
+            let (metadata_account, _) =
+                wave_dist::find_metadata_address(&config.program_id, &dist_account);
+            if let Some(metadata_account_on_chain) = config
+                .rpc_client
+                .get_account_with_commitment(&metadata_account, config.rpc_client.commitment())?
+                .value
+            {
+                let metadata = DistributionMetadata::unpack(&metadata_account_on_chain.data)?;
+                println!("Metadata {}", metadata_account);
+                println!("  Description: {}", metadata.description.as_str());
+                println!("  URI: {}", metadata.uri.as_str());
+                println!("  Creator label: {}", metadata.creator_label.as_str());
+                println!("  Category: {}", metadata.category.as_str());
+            }
+
+            if dist.round() > 0 {
+                println!("Rounds");
+                for round in 0..dist.round() {
+                    let (round_account, _) =
+                        wave_dist::find_round_address(&config.program_id, &dist_account, round);
+                    let round_account_on_chain = config.rpc_client.get_account(&round_account)?;
+                    let summary = RoundSummary::unpack(&round_account_on_chain.data)?;
+                    println!(
+                        "  #{}: {} recipients, {} sent, funded {}, paid {} (base units)",
+                        summary.round,
+                        summary.num_recipients,
+                        summary.sent_recipients,
+                        summary.funded_amount,
+                        summary.paid_amount,
+                    );
+                }
+                println!(
+                    "  #{} (current): {} recipients, {} sent, funded {}, paid {} (base units)",
+                    dist.round(),
+                    dist.num_recipients(),
+                    dist.sent_recipients(),
+                    dist.funded_amount(),
+                    dist.paid_amount(),
+                );
+            }
         }
         ("fund-distribution", Some(arg_matches)) => {
             let state_file_path = arg_matches.value_of("state_file");
@@ -864,9 +2423,14 @@ fn main() -> Result<(), Box<dyn Error>> {
                 let dist_account = match dist_account {
                     Some(pubkey) => pubkey,
                     None => {
-                        let (dist_account, _) = Pubkey::find_program_address(
-                            &[seed.unwrap().as_ref()],
+                        let project_name = value_t_or_exit!(arg_matches, "project_name", String);
+                        let creator = pubkey_of(arg_matches, "creator")
+                            .unwrap_or_else(|| config.fee_payer.pubkey());
+                        let (dist_account, _) = wave_dist::find_distribution_address(
                             &config.program_id,
+                            &seed.unwrap(),
+                            &project_name,
+                            &creator,
                         );
                         dist_account
                     }
@@ -897,6 +2461,9 @@ fn main() -> Result<(), Box<dyn Error>> {
                 funder_token_account_on_chain.token_amount.decimals,
             );
 
+            let delegated = arg_matches.is_present("delegate");
+            let treasury_token_account = treasury_token_account_of(&config, &token_address)?;
+
             println!("Funding distribution {}", dist_account);
             println!("  Program ID: {}", config.program_id);
             println!("  Token address: {}", token_address);
@@ -904,18 +2471,41 @@ fn main() -> Result<(), Box<dyn Error>> {
             println!("  Funding token account: {}", funder_token_account);
             println!("  Amount: {}", ui_amount);
             println!("  Amount (base units): {}", amount);
-            println!("  Token account: {}", dist_token_account);
+            if delegated {
+                println!("  Delegated to: {}", dist_account);
+            } else {
+                println!("  Token account: {}", dist_token_account);
+            }
             println!("  Fee payer: {}", config.fee_payer.pubkey());
             println!();
 
-            let instructions = vec![wave_dist::instruction::fund_distribution(
+            let mut instructions = vec![];
+            if delegated {
+                // The delegation has to cover everything the distribution
+                // still owes, not just this funding, since approving replaces
+                // any earlier delegation.
+                let dist = Distribution::unpack(&config.rpc_client.get_account_data(&dist_account)?)?;
+                let protocol_fee = get_program_config(&config)?
+                    .map_or(0, |program_config| program_config.protocol_fee(amount));
+                instructions.push(spl_token::instruction::approve(
+                    &spl_token::id(),
+                    &funder_token_account,
+                    &dist_account,
+                    &funder.pubkey(),
+                    &[],
+                    dist.unpaid_amount() + amount - protocol_fee,
+                )?);
+            }
+            instructions.push(wave_dist::instruction::fund_distribution(
                 &config.program_id,
                 &funder.pubkey(),
                 &funder_token_account,
                 &dist_account,
                 &dist_token_account,
+                treasury_token_account.as_ref(),
                 amount,
-            )];
+                delegated,
+            ));
 
             let mut transaction =
                 Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
@@ -931,74 +2521,115 @@ fn main() -> Result<(), Box<dyn Error>> {
 
             println!("Success!");
         }
-        ("change-dist-authority", Some(arg_matches)) => {
-            let state_file_path = arg_matches.value_of("state_file");
-
-            let saved_state: Option<StoredDistribution> =
-                if let Some(state_file_path) = state_file_path {
-                    serde_json::from_reader(&File::open(state_file_path)?)?
-                } else {
-                    None
-                };
+        ("propose-dist-authority", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
 
-            let dist_account = if let Some(saved_state) = &saved_state {
-                saved_state.dist_account
-            } else {
-                let seed = pubkey_of(arg_matches, "seed");
-                let dist_account = pubkey_of(arg_matches, "dist_account");
-                let dist_account = match dist_account {
-                    Some(pubkey) => pubkey,
-                    None => {
-                        let (dist_account, _) = Pubkey::find_program_address(
-                            &[seed.unwrap().as_ref()],
-                            &config.program_id,
-                        );
-                        dist_account
-                    }
-                };
-                dist_account
-            };
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
 
-            let dist_authority = if let Some(saved_state) = &saved_state {
-               /* let dist_authority = signer_from_path(
-                    arg_matches,
-                    &saved_state.dist_authority_input,
-                    "dist_authority",
-                    &mut wallet_manager,
-                )?;
-                if saved_state.dist_authority.ne(&dist_authority.pubkey()) {
-                    panic!("state file's dist authority input {} does not lead to intended dist authority {}", saved_state.dist_authority_input, saved_state.dist_authority);
-                }*/
-                saved_state.dist_authority
-            } else {
-               let (dist_authority, _) =
-                    signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
-                let dist_authority = dist_authority.unwrap();
-                
-                dist_authority.pubkey()
-            };
-            
-            let new_dist_authority =
-                pubkey_of_signer(arg_matches, "new_dist_authority", &mut wallet_manager)?.unwrap();
+            let new_dist_authority = pubkey_of(arg_matches, "new_dist_authority").unwrap();
 
-            println!("Modifying distribution {}", dist_account);
+            println!("Proposing dist authority for distribution {}", dist_account);
             println!("  Program ID: {}", config.program_id);
-            println!("  Current dist authority: {}", dist_authority);
-            println!("  New dist authority: {}", new_dist_authority);
+            println!("  Current dist authority: {}", dist_authority_pubkey);
+            println!("  Proposed dist authority: {}", new_dist_authority);
             println!("  Fee payer: {}", config.fee_payer.pubkey());
             println!();
 
-            let instructions = vec![wave_dist::instruction::set_dist_authority(
+            let instructions = vec![wave_dist::instruction::propose_dist_authority(
                 &config.program_id,
                 &dist_account,
-                &dist_authority,
+                &dist_authority_pubkey,
                 &new_dist_authority,
             )];
 
             let mut transaction =
                 Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
 
-            let signers = vec![config.fee_payer];
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success! {} must now run accept-dist-authority.", new_dist_authority);
+        }
+        ("accept-dist-authority", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (new_dist_authority, _) =
+                signer_of(arg_matches, "new_dist_authority", &mut wallet_manager)?;
+            let new_dist_authority_pubkey = new_dist_authority
+                .as_ref()
+                .map(|new_dist_authority| new_dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            println!("Accepting dist authority for distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  New dist authority: {}", new_dist_authority_pubkey);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![wave_dist::instruction::accept_dist_authority(
+                &config.program_id,
+                &dist_account,
+                &new_dist_authority_pubkey,
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(new_dist_authority) = new_dist_authority.as_ref() {
+                signers.push(new_dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("cancel-dist-authority", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            println!("Cancelling dist authority proposal for distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![wave_dist::instruction::cancel_dist_authority(
+                &config.program_id,
+                &dist_account,
+                &dist_authority_pubkey,
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
 
             let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
             transaction.sign(&signers, recent_blockhash);
@@ -1020,24 +2651,17 @@ fn main() -> Result<(), Box<dyn Error>> {
             } else {
                 let seed = pubkey_of(arg_matches, "seed");
                 let dist_account = pubkey_of(arg_matches, "dist_account");
-                let mut project_name = value_t_or_exit!(arg_matches, "project_name",String);
-                  // Convert project_name to pubkey size
-                if project_name.len() < mem::size_of::<Pubkey>() {
-            
-                    while project_name.len() != mem::size_of::<Pubkey>() {
-                        project_name = project_name + "0";
-                    }
-                    
-                }
-                let encoded = bs58::encode(project_name.clone()).into_string();
-                let project_pubkey= String_to_pubkey(encoded)?;
                 let dist_account = match dist_account {
                     Some(pubkey) => pubkey,
                     None => {
-                        let (dist_account, _) = Pubkey::find_program_address(
-                            &[seed.unwrap().as_ref(), project_pubkey.as_ref()],
-
+                        let project_name = value_t_or_exit!(arg_matches, "project_name", String);
+                        let creator = pubkey_of(arg_matches, "creator")
+                            .unwrap_or_else(|| config.fee_payer.pubkey());
+                        let (dist_account, _) = wave_dist::find_distribution_address(
                             &config.program_id,
+                            &seed.unwrap(),
+                            &project_name,
+                            &creator,
                         );
                         dist_account
                     }
@@ -1088,10 +2712,16 @@ fn main() -> Result<(), Box<dyn Error>> {
             println!("  Fee payer: {}", config.fee_payer.pubkey());
             println!();
 
+            let dist = Distribution::unpack(&config.rpc_client.get_account_data(&dist_account)?)?;
+            if let Some(funding_source) = dist.funding_source() {
+                println!("  Funding source: {}", funding_source);
+            }
+
             let instructions = vec![wave_dist::instruction::begin_distribution(
                 &config.program_id,
                 &dist_account,
                 &dist_authority,
+                dist.funding_source(),
                 num_recipients,
             )];
 
@@ -1132,24 +2762,17 @@ fn main() -> Result<(), Box<dyn Error>> {
             } else {
                 let seed = pubkey_of(arg_matches, "seed");
                 let dist_account = pubkey_of(arg_matches, "dist_account");
-                let mut project_name = value_t_or_exit!(arg_matches, "project_name",String);
-                  // Convert project_name to pubkey size
-                if project_name.len() < mem::size_of::<Pubkey>() {
-            
-                    while project_name.len() != mem::size_of::<Pubkey>() {
-                        project_name = project_name + "0";
-                    }
-                    
-                }
-                let encoded = bs58::encode(project_name.clone()).into_string();
-                let project_pubkey= String_to_pubkey(encoded)?;
                 let dist_account = match dist_account {
                     Some(pubkey) => pubkey,
                     None => {
-                        let (dist_account, _) = Pubkey::find_program_address(
-                            &[seed.unwrap().as_ref(), project_pubkey.as_ref()],
-
+                        let project_name = value_t_or_exit!(arg_matches, "project_name", String);
+                        let creator = pubkey_of(arg_matches, "creator")
+                            .unwrap_or_else(|| config.fee_payer.pubkey());
+                        let (dist_account, _) = wave_dist::find_distribution_address(
                             &config.program_id,
+                            &seed.unwrap(),
+                            &project_name,
+                            &creator,
                         );
                         dist_account
                     }
@@ -1157,10 +2780,14 @@ fn main() -> Result<(), Box<dyn Error>> {
                 dist_account
             };
 
-            let dist_token_account = if let Some(saved_state) = &saved_state {
-                saved_state.token_account
-            } else {
-                get_associated_token_address(&dist_account, &token_address)
+            let dist = Distribution::unpack(&config.rpc_client.get_account_data(&dist_account)?)?;
+            let dist_token_account = match &saved_state {
+                Some(saved_state)
+                    if dist.kind() == DistributionKind::Escrow && dist.funding_source().is_none() =>
+                {
+                    saved_state.token_account
+                }
+                _ => payout_source_of(&dist, &dist_account),
             };
 
             let dist_authority = if let Some(saved_state) = &saved_state {
@@ -1209,48 +2836,75 @@ fn main() -> Result<(), Box<dyn Error>> {
 
             let skip = value_t_or_exit!(arg_matches, "skip", usize);
 
-            let mut recipient_token_accounts = Vec::new();
-
-            for recipient in &participants[skip..] {
-                let recipient_token_account =
-                    get_associated_token_address(&recipient.address, &token_address);
-                recipient_token_accounts.push(recipient_token_account);
-            }
+            let recipient_wallets: Vec<Pubkey> = participants[skip..]
+                .iter()
+                .map(|recipient| recipient.address)
+                .collect();
 
             let fee_payer_pubkey = config.fee_payer.pubkey();
 
+            let forward_addresses = forward_addresses_of(&config, &dist_account, &recipient_wallets)?;
+
+            let hook_accounts = hook_accounts_of(
+                &dist,
+                &pubkeys_of(arg_matches, "hook_account").unwrap_or_default(),
+            )?;
+
             let signers = vec![config.fee_payer];
 
-            let recipient_token_accounts_chunks = recipient_token_accounts
-                .as_slice()
-                .chunks(DISTRIBUTE_CHUNK_SIZE);
+            let best_effort = arg_matches.is_present("best_effort");
+            let mut skipped_recipients = vec![];
 
-            for (i, recipient_token_accounts_chunk) in recipient_token_accounts_chunks.enumerate() {
+            let recipient_wallets_chunks = recipient_wallets.as_slice().chunks(DISTRIBUTE_CHUNK_SIZE);
+            let forward_addresses_chunks = forward_addresses.chunks(DISTRIBUTE_CHUNK_SIZE);
+
+            for (i, (recipient_wallets_chunk, forward_addresses)) in
+                recipient_wallets_chunks.zip(forward_addresses_chunks).enumerate()
+            {
                 println!(
                     "Distributing {} (recipients {}..{})",
                     dist_account,
                     1 + skip + i * DISTRIBUTE_CHUNK_SIZE,
-                    skip + (i + 1) * DISTRIBUTE_CHUNK_SIZE,
+                    skip + i * DISTRIBUTE_CHUNK_SIZE + recipient_wallets_chunk.len(),
                 );
                 println!("  Program ID: {}", config.program_id);
                 println!("  Dist authority: {}", &dist_authority_pubkey);
                 println!("  Skip index: {}", skip + DISTRIBUTE_CHUNK_SIZE * i);
                 println!("  Recipients:");
-                for recipient in &participants {
-                    println!("    {}", recipient.address);
+                for (recipient_wallet, forward_address) in
+                    recipient_wallets_chunk.iter().zip(forward_addresses)
+                {
+                    match forward_address {
+                        Some(forward_address) => {
+                            println!("    {} -> {}", recipient_wallet, forward_address)
+                        }
+                        None => println!("    {}", recipient_wallet),
+                    }
                 }
                 println!("  Fee payer: {}", &fee_payer_pubkey);
-                println!("  Dist Authority: {}", &fee_payer_pubkey);
                 println!();
 
-                let instructions = vec![wave_dist::instruction::distribute(
+                let recipient_hook_accounts = vec![hook_accounts.clone(); recipient_wallets_chunk.len()];
+
+                // Recipients without a token account get one, paid for by the fee payer
+                let instructions = vec![wave_dist::instruction::distribute_to_wallets(
                     &config.program_id,
                     &dist_account,
                     &dist_authority_pubkey,
                     &dist_token_account,
-                    &recipient_token_accounts_chunk
+                    &fee_payer_pubkey,
+                    &token_address,
+                    &recipient_wallets_chunk
                         .iter()
-                        .collect::<Vec<&Pubkey>>(),
+                        .zip(forward_addresses)
+                        .map(|(recipient_wallet, forward_address)| {
+                            (recipient_wallet, forward_address.as_ref())
+                        })
+                        .collect::<Vec<_>>(),
+                    gate_mint_of(&dist),
+                    dist.hook_program()
+                        .map(|hook_program| (hook_program, &recipient_hook_accounts[..])),
+                    best_effort,
                 )];
 
                 let mut transaction =
@@ -1259,13 +2913,1977 @@ fn main() -> Result<(), Box<dyn Error>> {
                 let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
                 transaction.sign(&signers, recent_blockhash);
 
-                config
+                let signature = config
                     .rpc_client
                     .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+                if best_effort {
+                    for (index, reason) in skipped_recipients_of(&config.rpc_client, &signature)? {
+                        if let Some(recipient_wallet) = recipient_wallets_chunk.get(index) {
+                            println!("  Skipped {}: {}", recipient_wallet, reason);
+                            skipped_recipients.push((
+                                skip + i * DISTRIBUTE_CHUNK_SIZE + index,
+                                *recipient_wallet,
+                                reason,
+                            ));
+                        }
+                    }
+                }
+            }
+
+            if !skipped_recipients.is_empty() {
+                println!("Skipped {} recipients:", skipped_recipients.len());
+                for (position, recipient_wallet, reason) in &skipped_recipients {
+                    println!("  #{} {}: {}", position, recipient_wallet, reason);
+                }
+
+                if let Some(skip_report_path) = arg_matches.value_of("skip_report") {
+                    let mut skip_report = File::create(skip_report_path)?;
+                    writeln!(skip_report, "index,recipient,reason")?;
+                    for (position, recipient_wallet, reason) in &skipped_recipients {
+                        writeln!(skip_report, "{},{},{}", position, recipient_wallet, reason)?;
+                    }
+                    println!("Skip report written to {}", skip_report_path);
+                }
+            }
+
+            println!("Success!");
+        }
+        ("set-metadata", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            // Fields that aren't given keep their current value
+            let (metadata_account, _) =
+                wave_dist::find_metadata_address(&config.program_id, &dist_account);
+            let metadata = match config
+                .rpc_client
+                .get_account_with_commitment(&metadata_account, config.rpc_client.commitment())?
+                .value
+            {
+                Some(metadata_account_on_chain) => {
+                    DistributionMetadata::unpack(&metadata_account_on_chain.data)?
+                }
+                None => DistributionMetadata::default(),
+            };
+
+            let description = arg_matches
+                .value_of("description")
+                .unwrap_or_else(|| metadata.description.as_str());
+            let uri = arg_matches
+                .value_of("uri")
+                .unwrap_or_else(|| metadata.uri.as_str());
+            let creator_label = arg_matches
+                .value_of("creator_label")
+                .unwrap_or_else(|| metadata.creator_label.as_str());
+            let category = arg_matches
+                .value_of("category")
+                .unwrap_or_else(|| metadata.category.as_str());
+
+            println!("Setting metadata of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Metadata account: {}", metadata_account);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Description: {}", description);
+            println!("  URI: {}", uri);
+            println!("  Creator label: {}", creator_label);
+            println!("  Category: {}", category);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![wave_dist::instruction::set_metadata(
+                &config.program_id,
+                &dist_account,
+                &dist_authority_pubkey,
+                &config.fee_payer.pubkey(),
+                description,
+                uri,
+                creator_label,
+                category,
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("init-config", Some(arg_matches)) => {
+            let (upgrade_authority, _) =
+                signer_of(arg_matches, "upgrade_authority", &mut wallet_manager)?;
+            let upgrade_authority_pubkey = upgrade_authority
+                .as_ref()
+                .map(|upgrade_authority| upgrade_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+            let admin = pubkey_of(arg_matches, "admin").unwrap();
+            let (config_account, _) = wave_dist::find_config_address(&config.program_id);
+
+            println!("Initializing program config {}", config_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Upgrade authority: {}", upgrade_authority_pubkey);
+            println!("  Admin: {}", admin);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![wave_dist::instruction::initialize_config(
+                &config.program_id,
+                &upgrade_authority_pubkey,
+                &admin,
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(upgrade_authority) = upgrade_authority.as_ref() {
+                signers.push(upgrade_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("update-config", Some(arg_matches)) => {
+            let (admin, _) = signer_of(arg_matches, "admin", &mut wallet_manager)?;
+            let admin_pubkey = admin
+                .as_ref()
+                .map(|admin| admin.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            // Settings that aren't given keep their current value
+            let program_config =
+                get_program_config(&config)?.expect("program config does not exist");
+            let paused = value_t!(arg_matches, "paused", bool).unwrap_or(program_config.paused);
+            let protocol_fee_bps = value_t!(arg_matches, "protocol_fee_bps", u16)
+                .unwrap_or(program_config.protocol_fee_bps);
+            let treasury = pubkey_of(arg_matches, "treasury").unwrap_or(program_config.treasury);
+
+            println!("Updating program config");
+            println!("  Program ID: {}", config.program_id);
+            println!("  Admin: {}", admin_pubkey);
+            println!("  Paused: {}", paused);
+            println!("  Protocol fee (bps): {}", protocol_fee_bps);
+            println!("  Treasury: {}", treasury);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![wave_dist::instruction::update_config(
+                &config.program_id,
+                &admin_pubkey,
+                paused,
+                protocol_fee_bps,
+                &treasury,
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(admin) = admin.as_ref() {
+                signers.push(admin.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("set-allowed-mints", Some(arg_matches)) => {
+            let (admin, _) = signer_of(arg_matches, "admin", &mut wallet_manager)?;
+            let admin_pubkey = admin
+                .as_ref()
+                .map(|admin| admin.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+            let mints = pubkeys_of(arg_matches, "mint").unwrap_or_default();
+
+            println!("Setting allowed mints");
+            println!("  Program ID: {}", config.program_id);
+            println!("  Admin: {}", admin_pubkey);
+            println!("  Mints:");
+            for mint in &mints {
+                println!("    {}", mint);
+            }
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![wave_dist::instruction::set_allowed_mints(
+                &config.program_id,
+                &admin_pubkey,
+                &mints,
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(admin) = admin.as_ref() {
+                signers.push(admin.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("show-config", Some(_arg_matches)) => {
+            let (config_account, _) = wave_dist::find_config_address(&config.program_id);
+            match get_program_config(&config)? {
+                Some(program_config) => {
+                    println!("Program config {}", config_account);
+                    println!("  Admin: {}", program_config.admin);
+                    println!("  Paused: {}", program_config.paused);
+                    println!("  Protocol fee (bps): {}", program_config.protocol_fee_bps);
+                    println!("  Treasury: {}", program_config.treasury);
+                    println!("  Allowed mints:");
+                    for mint in program_config.allowed_mints() {
+                        println!("    {}", mint);
+                    }
+                }
+                None => println!("Program config {} is not initialized", config_account),
+            }
+        }
+        ("set-release-rate", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let ui_release_rate = value_t_or_exit!(arg_matches, "rate", f64);
+            let release_rate = spl_token::ui_amount_to_amount(
+                ui_release_rate,
+                mint_decimals_of(&config, dist.token())?,
+            );
+
+            let recipients_root = match arg_matches.value_of("recipients_file") {
+                Some(recipients_file) => {
+                    let leaves: Vec<_> = recipient_wallets_of(recipients_file)?
+                        .iter()
+                        .map(wave_dist::merkle::recipient_leaf)
+                        .collect();
+                    wave_dist::merkle::root(&leaves)
+                }
+                None if release_rate > 0 => {
+                    return Err("a streaming distribution needs a recipients file".into());
+                }
+                None => Default::default(),
+            };
+
+            println!("Setting release rate of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Release rate per epoch: {}", ui_release_rate);
+            println!("  Release rate per epoch (base units): {}", release_rate);
+            println!("  Recipients root: {}", recipients_root);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![wave_dist::instruction::set_release_rate(
+                &config.program_id,
+                &dist_account,
+                &dist_authority_pubkey,
+                release_rate,
+                &recipients_root,
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("transfer-mint-authority", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let (mint_authority, _) = signer_of(arg_matches, "mint_authority", &mut wallet_manager)?;
+            let mint_authority_pubkey = mint_authority
+                .as_ref()
+                .map(|mint_authority| mint_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+            let token_address = *dist.token();
+
+            let ui_supply_cap = value_t_or_exit!(arg_matches, "supply_cap", f64);
+            let supply_cap = spl_token::ui_amount_to_amount(
+                ui_supply_cap,
+                mint_decimals_of(&config, &token_address)?,
+            );
+
+            println!("Transferring mint authority to distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Token address: {}", token_address);
+            println!("  Mint authority: {}", mint_authority_pubkey);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Supply cap: {}", ui_supply_cap);
+            println!("  Supply cap (base units): {}", supply_cap);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![
+                spl_token::instruction::set_authority(
+                    &spl_token::id(),
+                    &token_address,
+                    Some(&dist_account),
+                    spl_token::instruction::AuthorityType::MintTokens,
+                    &mint_authority_pubkey,
+                    &[],
+                )?,
+                wave_dist::instruction::set_mint_distribution(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    &token_address,
+                    supply_cap,
+                ),
+            ];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+            if let Some(mint_authority) = mint_authority.as_ref() {
+                signers.push(mint_authority.as_ref());
             }
 
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
             println!("Success!");
         }
+        ("deposit-nfts", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let (owner, _) = signer_of(arg_matches, "owner", &mut wallet_manager)?;
+            let owner_pubkey = owner
+                .as_ref()
+                .map(|owner| owner.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let assignments_file = arg_matches.value_of("assignments").unwrap();
+            let mints: Vec<Pubkey> = nft_assignments_of(assignments_file)?
+                .into_iter()
+                .map(|(mint, _)| mint)
+                .collect();
+
+            let fee_payer_pubkey = config.fee_payer.pubkey();
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+            if let Some(owner) = owner.as_ref() {
+                signers.push(owner.as_ref());
+            }
+
+            for (i, mints_chunk) in mints.chunks(NFT_CHUNK_SIZE).enumerate() {
+                println!(
+                    "Depositing NFTs into {} (NFTs {}..{})",
+                    dist_account,
+                    1 + i * NFT_CHUNK_SIZE,
+                    i * NFT_CHUNK_SIZE + mints_chunk.len(),
+                );
+                println!("  Program ID: {}", config.program_id);
+                println!("  Dist authority: {}", dist_authority_pubkey);
+                println!("  Owner: {}", owner_pubkey);
+                println!("  Mints:");
+                for mint in mints_chunk {
+                    println!("    {}", mint);
+                }
+                println!("  Fee payer: {}", fee_payer_pubkey);
+                println!();
+
+                let token_accounts: Vec<Pubkey> = mints_chunk
+                    .iter()
+                    .map(|mint| get_associated_token_address(&owner_pubkey, mint))
+                    .collect();
+                let nfts: Vec<(&Pubkey, &Pubkey)> =
+                    mints_chunk.iter().zip(token_accounts.iter()).collect();
+
+                let instructions = vec![wave_dist::instruction::deposit_nfts(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    &owner_pubkey,
+                    &fee_payer_pubkey,
+                    &nfts,
+                )];
+
+                let mut transaction =
+                    Transaction::new_with_payer(&instructions, Some(&fee_payer_pubkey));
+
+                let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+                transaction.sign(&signers, recent_blockhash);
+
+                config
+                    .rpc_client
+                    .send_and_confirm_transaction_with_spinner(&transaction)?;
+            }
+
+            println!("Success!");
+        }
+        ("distribute-nfts", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let best_effort = arg_matches.is_present("best_effort");
+
+            let assignments_file = arg_matches.value_of("assignments").unwrap();
+            let assignments = nft_assignments_of(assignments_file)?;
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let hook_accounts = hook_accounts_of(
+                &dist,
+                &pubkeys_of(arg_matches, "hook_account").unwrap_or_default(),
+            )?;
+
+            let fee_payer_pubkey = config.fee_payer.pubkey();
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            for (i, assignments_chunk) in assignments.chunks(NFT_CHUNK_SIZE).enumerate() {
+                println!(
+                    "Distributing NFTs of {} (recipients {}..{})",
+                    dist_account,
+                    1 + i * NFT_CHUNK_SIZE,
+                    i * NFT_CHUNK_SIZE + assignments_chunk.len(),
+                );
+                println!("  Program ID: {}", config.program_id);
+                println!("  Dist authority: {}", dist_authority_pubkey);
+                println!("  Best effort: {}", best_effort);
+                println!("  Recipients:");
+                for (mint, recipient_wallet) in assignments_chunk {
+                    println!("    {} gets {}", recipient_wallet, mint);
+                }
+                println!("  Fee payer: {}", fee_payer_pubkey);
+                println!();
+
+                let recipient_token_accounts: Vec<Pubkey> = assignments_chunk
+                    .iter()
+                    .map(|(mint, recipient_wallet)| {
+                        get_associated_token_address(recipient_wallet, mint)
+                    })
+                    .collect();
+
+                let mut instructions = vec![];
+                for ((mint, recipient_wallet), recipient_token_account) in
+                    assignments_chunk.iter().zip(recipient_token_accounts.iter())
+                {
+                    if config
+                        .rpc_client
+                        .get_account_with_commitment(recipient_token_account, config.rpc_client.commitment())?
+                        .value
+                        .is_none()
+                    {
+                        instructions.push(
+                            spl_associated_token_account::instruction::create_associated_token_account(
+                                &fee_payer_pubkey,
+                                recipient_wallet,
+                                mint,
+                            ),
+                        );
+                    }
+                }
+
+                let recipients: Vec<(&Pubkey, &Pubkey, &Pubkey)> = assignments_chunk
+                    .iter()
+                    .zip(recipient_token_accounts.iter())
+                    .map(|((mint, recipient_wallet), recipient_token_account)| {
+                        (recipient_token_account, recipient_wallet, mint)
+                    })
+                    .collect();
+                let recipient_hook_accounts = vec![hook_accounts.clone(); recipients.len()];
+
+                instructions.push(wave_dist::instruction::distribute_nfts(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    &fee_payer_pubkey,
+                    &recipients,
+                    gate_mint_of(&dist),
+                    dist.hook_program()
+                        .map(|hook_program| (hook_program, &recipient_hook_accounts[..])),
+                    best_effort,
+                ));
+
+                let mut transaction =
+                    Transaction::new_with_payer(&instructions, Some(&fee_payer_pubkey));
+
+                let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+                transaction.sign(&signers, recent_blockhash);
+
+                let signature = config
+                    .rpc_client
+                    .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+                if best_effort {
+                    for (index, reason) in skipped_recipients_of(&config.rpc_client, &signature)? {
+                        let (mint, recipient_wallet) = assignments_chunk[index];
+                        println!("  Skipped {} ({}): {}", recipient_wallet, mint, reason);
+                    }
+                }
+            }
+
+            println!("Success!");
+        }
+        ("set-redemption", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let input_mint = pubkey_of(arg_matches, "input_mint").unwrap();
+            let rate_numerator = value_t_or_exit!(arg_matches, "rate_numerator", u64);
+            let rate_denominator = value_t_or_exit!(arg_matches, "rate_denominator", u64);
+            let burn_input = arg_matches.is_present("burn");
+
+            let ui_cap = value_t_or_exit!(arg_matches, "cap", f64);
+            let cap = spl_token::ui_amount_to_amount(ui_cap, mint_decimals_of(&config, dist.token())?);
+
+            let input_vault = get_associated_token_address(&dist_account, &input_mint);
+
+            println!("Setting redemption of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Input mint: {}", input_mint);
+            println!("  Rate (base units): {} per {}", rate_numerator, rate_denominator);
+            println!("  Cap: {}", ui_cap);
+            println!("  Cap (base units): {}", cap);
+            if burn_input {
+                println!("  Burns input: true");
+            } else {
+                println!("  Input vault: {}", input_vault);
+            }
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            // The program creates the input vault if it's missing
+            let instructions = vec![wave_dist::instruction::set_redemption(
+                &config.program_id,
+                &dist_account,
+                &dist_authority_pubkey,
+                &config.fee_payer.pubkey(),
+                &input_mint,
+                rate_numerator,
+                rate_denominator,
+                cap,
+                burn_input,
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("redeem", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (redeemer, _) = signer_of(arg_matches, "redeemer", &mut wallet_manager)?;
+            let redeemer_pubkey = redeemer
+                .as_ref()
+                .map(|redeemer| redeemer.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let input_mint = *dist.input_mint();
+            let ui_amount = value_t_or_exit!(arg_matches, "amount", f64);
+            let amount = spl_token::ui_amount_to_amount(ui_amount, mint_decimals_of(&config, &input_mint)?);
+
+            let input_token_account = get_associated_token_address(&redeemer_pubkey, &input_mint);
+            let output_token_account = get_associated_token_address(&redeemer_pubkey, dist.token());
+            let dist_token_account = payout_source_of(&dist, &dist_account);
+
+            println!("Redeeming from distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Redeemer: {}", redeemer_pubkey);
+            println!("  Input mint: {}", input_mint);
+            println!("  Amount: {}", ui_amount);
+            println!("  Amount (base units): {}", amount);
+            if let Some(output) = dist.redemption_output(amount) {
+                println!("  Receives (base units): {}", output);
+            }
+            println!("  Receiving token account: {}", output_token_account);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut instructions = vec![];
+            if config.rpc_client.get_token_account(&output_token_account)?.is_none() {
+                instructions.push(
+                    spl_associated_token_account::instruction::create_associated_token_account(
+                        &config.fee_payer.pubkey(),
+                        &redeemer_pubkey,
+                        dist.token(),
+                    ),
+                );
+            }
+            instructions.push(wave_dist::instruction::redeem(
+                &config.program_id,
+                &dist_account,
+                &redeemer_pubkey,
+                &dist_token_account,
+                &input_mint,
+                &input_token_account,
+                &output_token_account,
+                &redeemer_pubkey,
+                amount,
+            ));
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(redeemer) = redeemer.as_ref() {
+                signers.push(redeemer.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("begin-raffle", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let entrants_file = arg_matches.value_of("entrants_file").unwrap();
+            let entrants = recipient_wallets_of(entrants_file)?;
+            let leaves: Vec<_> = entrants
+                .iter()
+                .enumerate()
+                .map(|(index, wallet)| wave_dist::merkle::entrant_leaf(index as u32, wallet))
+                .collect();
+            let entrants_root = wave_dist::merkle::root(&leaves);
+
+            let num_winners = value_t_or_exit!(arg_matches, "winners", u16);
+
+            println!("Beginning raffle {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Entrants: {}", entrants.len());
+            println!("  Entrants root: {}", entrants_root);
+            println!("  Winners: {}", num_winners);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::begin_raffle(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    dist.funding_source(),
+                    num_winners,
+                    wave_dist::instruction::RaffleCommit {
+                        entrants_root,
+                        num_entrants: entrants.len() as u32,
+                    },
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("draw", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            println!("Drawing raffle {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::draw(&config.program_id, &dist_account)],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&[config.fee_payer.as_ref()], recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+            if dist.raffle_drawn() {
+                println!("Winning entrants: {:?}", dist.raffle_winners());
+            } else {
+                println!(
+                    "The draw slot had no hash left, draw again after slot {}",
+                    dist.raffle_draw_slot()
+                );
+            }
+
+            println!("Success!");
+        }
+        ("claim-raffle-prizes", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            if !dist.raffle_drawn() {
+                return Err(format!("raffle {} has not been drawn", dist_account).into());
+            }
+
+            let entrants_file = arg_matches.value_of("entrants_file").unwrap();
+            let entrants = recipient_wallets_of(entrants_file)?;
+            let leaves: Vec<_> = entrants
+                .iter()
+                .enumerate()
+                .map(|(index, wallet)| wave_dist::merkle::entrant_leaf(index as u32, wallet))
+                .collect();
+            if wave_dist::merkle::root(&leaves) != *dist.entrants_root() {
+                return Err(format!(
+                    "{} does not match the entrants the raffle was begun with",
+                    entrants_file
+                )
+                .into());
+            }
+
+            let token_address = *dist.token();
+            let dist_token_account = payout_source_of(&dist, &dist_account);
+            let fee_payer_pubkey = config.fee_payer.pubkey();
+
+            println!("Claiming raffle prizes of {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Fee payer: {}", fee_payer_pubkey);
+            println!();
+
+            for (position, &index) in dist.raffle_winners().iter().enumerate() {
+                if dist.is_raffle_prize_claimed(position) {
+                    continue;
+                }
+
+                let winner = entrants[index as usize];
+                let forward_address =
+                    forward_addresses_of(&config, &dist_account, &[winner])?.remove(0);
+                let winner_token_account = forward_address
+                    .unwrap_or_else(|| get_associated_token_address(&winner, &token_address));
+                let proof = wave_dist::merkle::proof(&leaves, index as usize).unwrap();
+
+                let mut instructions = vec![];
+                if forward_address.is_none()
+                    && config.rpc_client.get_token_account(&winner_token_account)?.is_none()
+                {
+                    instructions.push(
+                        spl_associated_token_account::instruction::create_associated_token_account(
+                            &fee_payer_pubkey,
+                            &winner,
+                            &token_address,
+                        ),
+                    );
+                }
+                instructions.push(wave_dist::instruction::claim_raffle_prize(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_token_account,
+                    &winner_token_account,
+                    &winner,
+                    &fee_payer_pubkey,
+                    index,
+                    proof,
+                    gate_mint_of(&dist),
+                ));
+
+                let mut transaction =
+                    Transaction::new_with_payer(&instructions, Some(&fee_payer_pubkey));
+
+                let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+                transaction.sign(&[config.fee_payer.as_ref()], recent_blockhash);
+
+                let signature = config
+                    .rpc_client
+                    .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+                println!("  Paid entrant {} ({}): {}", index, winner, signature);
+            }
+
+            println!("Success!");
+        }
+        ("set-gate", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let (gate_mint, min_balance) = match pubkey_of(arg_matches, "gate_mint") {
+                Some(gate_mint) => {
+                    let ui_min_balance = value_t_or_exit!(arg_matches, "min_balance", f64);
+                    let min_balance = spl_token::ui_amount_to_amount(
+                        ui_min_balance,
+                        mint_decimals_of(&config, &gate_mint)?,
+                    );
+                    (gate_mint, min_balance)
+                }
+                None => (Pubkey::default(), 0),
+            };
+
+            println!("Setting gate of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            if gate_mint == Pubkey::default() {
+                println!("  Gate: none");
+            } else {
+                println!("  Gate mint: {}", gate_mint);
+                println!("  Min balance (base units): {}", min_balance);
+            }
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::set_gate(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    &gate_mint,
+                    min_balance,
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("set-hook", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let hook_program = pubkey_of(arg_matches, "hook_program").unwrap_or_default();
+            let hook_accounts_len = value_t_or_exit!(arg_matches, "hook_accounts_len", u8);
+            let failure_policy = if arg_matches.is_present("skip_unavailable") {
+                HookFailurePolicy::Skip
+            } else {
+                HookFailurePolicy::Abort
+            };
+
+            println!("Setting hook of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            if hook_program == Pubkey::default() {
+                println!("  Hook: none");
+            } else {
+                println!("  Hook program: {}", hook_program);
+                println!(
+                    "  Hook authority: {}",
+                    wave_dist::find_hook_authority_address(&config.program_id, &dist_account).0
+                );
+                println!("  Hook extra accounts: {}", hook_accounts_len);
+                println!("  Hook failure policy: {:?}", failure_policy);
+            }
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::set_hook(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    &hook_program,
+                    hook_accounts_len,
+                    failure_policy,
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("migrate", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let dist = DistributionV1::unpack(&config.rpc_client.get_account_data(&dist_account)?)
+                .map_err(|_| format!("{} is not a version 1 distribution", dist_account))?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            println!("Migrating distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Recipients paid: {}/{}", dist.sent_recipients, dist.num_recipients);
+            if dist.sent_recipients > 0 {
+                println!("  The distribution moves on to a new round with its unpaid funds");
+            }
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::migrate_distribution(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("set-attester", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let attester = pubkey_of(arg_matches, "attester").unwrap_or_default();
+
+            println!("Setting attester of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            if attester == Pubkey::default() {
+                println!("  Attester: none");
+            } else {
+                println!("  Attester: {}", attester);
+            }
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::set_attester(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    &attester,
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("sign-voucher", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let (attester, _) = signer_of(arg_matches, "attester", &mut wallet_manager)?;
+            let attester = attester.as_ref().unwrap_or(&config.fee_payer);
+            if dist.attester() != Some(&attester.pubkey()) {
+                return Err(format!(
+                    "{} is not the attester of distribution {}",
+                    attester.pubkey(),
+                    dist_account
+                )
+                .into());
+            }
+
+            let recipient = pubkey_of(arg_matches, "recipient").unwrap();
+            let (amount, nonce, expiry) = voucher_of(arg_matches, &config, &dist)?;
+            let message = wave_dist::ed25519::voucher_message(
+                &dist_account,
+                &recipient,
+                amount,
+                nonce,
+                expiry,
+            );
+            let signature = attester.try_sign_message(&message)?;
+
+            println!("Voucher of distribution {}", dist_account);
+            println!("  Attester: {}", attester.pubkey());
+            println!("  Recipient: {}", recipient);
+            println!("  Amount (base units): {}", amount);
+            println!("  Nonce: {}", nonce);
+            println!("  Expiry: {}", expiry);
+            println!("  Signature: {}", signature);
+        }
+        ("claim-voucher", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let attester = *dist
+                .attester()
+                .ok_or_else(|| format!("distribution {} has no attester", dist_account))?;
+
+            let recipient = pubkey_of(arg_matches, "recipient").unwrap();
+            let (amount, nonce, expiry) = voucher_of(arg_matches, &config, &dist)?;
+            let signature = value_t_or_exit!(arg_matches, "signature", Signature);
+            let message = wave_dist::ed25519::voucher_message(
+                &dist_account,
+                &recipient,
+                amount,
+                nonce,
+                expiry,
+            );
+
+            let token_address = *dist.token();
+            let dist_token_account = payout_source_of(&dist, &dist_account);
+            let forward_address =
+                forward_addresses_of(&config, &dist_account, &[recipient])?.remove(0);
+            let recipient_token_account = forward_address
+                .unwrap_or_else(|| get_associated_token_address(&recipient, &token_address));
+            let fee_payer_pubkey = config.fee_payer.pubkey();
+
+            println!("Claiming voucher of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Recipient: {}", recipient);
+            println!("  Recipient token account: {}", recipient_token_account);
+            println!("  Amount (base units): {}", amount);
+            println!("  Nonce: {}", nonce);
+            println!("  Fee payer: {}", fee_payer_pubkey);
+            println!();
+
+            let mut instructions = vec![];
+            if forward_address.is_none()
+                && config.rpc_client.get_token_account(&recipient_token_account)?.is_none()
+            {
+                instructions.push(
+                    spl_associated_token_account::instruction::create_associated_token_account(
+                        &fee_payer_pubkey,
+                        &recipient,
+                        &token_address,
+                    ),
+                );
+            }
+            // The claim must come right after the signature check
+            instructions.push(wave_dist::ed25519::new_ed25519_instruction(&[(
+                &attester,
+                &<[u8; 64]>::try_from(signature.as_ref())?,
+                &message,
+            )]));
+            instructions.push(wave_dist::instruction::claim_with_voucher(
+                &config.program_id,
+                &dist_account,
+                &dist_token_account,
+                &recipient_token_account,
+                &recipient,
+                &fee_payer_pubkey,
+                amount,
+                nonce,
+                expiry,
+                gate_mint_of(&dist),
+            ));
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&fee_payer_pubkey));
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&[config.fee_payer.as_ref()], recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("set-relay-fee-cap", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let ui_relay_fee_cap = value_t_or_exit!(arg_matches, "relay_fee_cap", f64);
+            let relay_fee_cap = spl_token::ui_amount_to_amount(
+                ui_relay_fee_cap,
+                mint_decimals_of(&config, dist.token())?,
+            );
+
+            println!("Setting relay fee cap of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Relay fee cap: {}", ui_relay_fee_cap);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::set_relay_fee_cap(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    relay_fee_cap,
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("sign-relay-claim", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let (recipient, _) = signer_of(arg_matches, "recipient", &mut wallet_manager)?;
+            let recipient = recipient.as_ref().unwrap_or(&config.fee_payer);
+
+            let (amount, nonce, expiry) = voucher_of(arg_matches, &config, &dist)?;
+            let voucher_signature = value_t_or_exit!(arg_matches, "voucher_signature", Signature);
+            let relayer = pubkey_of(arg_matches, "relayer").unwrap();
+            let ui_relayer_fee = value_t_or_exit!(arg_matches, "relayer_fee", f64);
+            let relayer_fee = spl_token::ui_amount_to_amount(
+                ui_relayer_fee,
+                mint_decimals_of(&config, dist.token())?,
+            );
+            if relayer_fee > dist.relay_fee_cap() {
+                return Err(format!(
+                    "relayer fee is over the cap of {} base units",
+                    dist.relay_fee_cap()
+                )
+                .into());
+            }
+
+            let message = wave_dist::ed25519::relay_message(
+                &dist_account,
+                &recipient.pubkey(),
+                &relayer,
+                nonce,
+                relayer_fee,
+            );
+            let recipient_signature = recipient.try_sign_message(&message)?;
+
+            let relay_request = RelayRequest {
+                dist_account,
+                recipient: recipient.pubkey(),
+                relayer,
+                amount,
+                nonce,
+                expiry,
+                relayer_fee,
+                voucher_signature: voucher_signature.to_string(),
+                recipient_signature: recipient_signature.to_string(),
+            };
+
+            let queue_file = arg_matches.value_of("queue_file").unwrap();
+            let mut output_file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(queue_file)?;
+            writeln!(output_file, "{}", serde_json::to_string(&relay_request)?)?;
+
+            println!("Signed relay claim of distribution {}", dist_account);
+            println!("  Recipient: {}", recipient.pubkey());
+            println!("  Amount (base units): {}", amount);
+            println!("  Nonce: {}", nonce);
+            println!("  Relayer: {}", relayer);
+            println!("  Relayer fee (base units): {}", relayer_fee);
+            println!();
+            println!("Added to {}", queue_file);
+        }
+        ("relay", Some(arg_matches)) => {
+            let queue_file = arg_matches.value_of("queue_file").unwrap();
+            let reader = BufReader::new(File::open(queue_file)?);
+            let mut relay_requests = vec![];
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                relay_requests.push(serde_json::from_str::<RelayRequest>(&line)?);
+            }
+
+            println!("Relaying {} claims from {}", relay_requests.len(), queue_file);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Relayer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut failed = 0;
+            for relay_request in &relay_requests {
+                match relay(&config, relay_request) {
+                    Ok(signature) => println!(
+                        "  Relayed nonce {} of {}: {}",
+                        relay_request.nonce, relay_request.recipient, signature
+                    ),
+                    Err(err) => {
+                        failed += 1;
+                        println!(
+                            "  Failed nonce {} of {}: {}",
+                            relay_request.nonce, relay_request.recipient, err
+                        );
+                    }
+                }
+            }
+
+            if failed > 0 {
+                return Err(format!("{} of {} claims failed", failed, relay_requests.len()).into());
+            }
+
+            println!("Success!");
+        }
+        ("set-forward-address", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (wallet, _) = signer_of(arg_matches, "wallet", &mut wallet_manager)?;
+            let wallet_pubkey = wallet
+                .as_ref()
+                .map(|wallet| wallet.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let destination = pubkey_of(arg_matches, "destination").unwrap();
+
+            println!("Setting forward address for distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Wallet: {}", wallet_pubkey);
+            println!("  Destination: {}", destination);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::set_forward_address(
+                    &config.program_id,
+                    &dist_account,
+                    &wallet_pubkey,
+                    &destination,
+                    &config.fee_payer.pubkey(),
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(wallet) = wallet.as_ref() {
+                signers.push(wallet.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("drip", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let token_address = *dist.token();
+            let dist_token_account = payout_source_of(&dist, &dist_account);
+
+            let recipients_file = arg_matches.value_of("recipients_file").unwrap();
+            let recipient_wallets = recipient_wallets_of(recipients_file)?;
+            let leaves: Vec<_> = recipient_wallets
+                .iter()
+                .map(wave_dist::merkle::recipient_leaf)
+                .collect();
+            if wave_dist::merkle::root(&leaves) != *dist.recipients_root() {
+                return Err(format!(
+                    "{} does not match the recipients the release rate was set with",
+                    recipients_file
+                )
+                .into());
+            }
+
+            let fee_payer_pubkey = config.fee_payer.pubkey();
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            for (i, recipient_wallets_chunk) in
+                recipient_wallets.chunks(DISTRIBUTE_CHUNK_SIZE).enumerate()
+            {
+                println!(
+                    "Dripping {} (recipients {}..{})",
+                    dist_account,
+                    1 + i * DISTRIBUTE_CHUNK_SIZE,
+                    i * DISTRIBUTE_CHUNK_SIZE + recipient_wallets_chunk.len(),
+                );
+                println!("  Program ID: {}", config.program_id);
+                println!("  Dist authority: {}", dist_authority_pubkey);
+                println!("  Recipients:");
+                for recipient_wallet in recipient_wallets_chunk {
+                    println!("    {}", recipient_wallet);
+                }
+                println!("  Fee payer: {}", fee_payer_pubkey);
+                println!();
+
+                let recipient_token_accounts: Vec<Pubkey> =
+                    forward_addresses_of(&config, &dist_account, recipient_wallets_chunk)?
+                        .into_iter()
+                        .zip(recipient_wallets_chunk.iter())
+                        .map(|(forward_address, recipient_wallet)| {
+                            forward_address.unwrap_or_else(|| {
+                                get_associated_token_address(recipient_wallet, &token_address)
+                            })
+                        })
+                        .collect();
+                let recipients: Vec<(&Pubkey, &Pubkey)> = recipient_token_accounts
+                    .iter()
+                    .zip(recipient_wallets_chunk.iter())
+                    .collect();
+                let proofs = (0..recipient_wallets_chunk.len())
+                    .map(|j| {
+                        wave_dist::merkle::proof(&leaves, i * DISTRIBUTE_CHUNK_SIZE + j).unwrap()
+                    })
+                    .collect();
+
+                let instructions = vec![wave_dist::instruction::drip(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    &dist_token_account,
+                    &fee_payer_pubkey,
+                    &recipients,
+                    proofs,
+                    gate_mint_of(&dist),
+                )];
+
+                let mut transaction =
+                    Transaction::new_with_payer(&instructions, Some(&fee_payer_pubkey));
+
+                let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+                transaction.sign(&signers, recent_blockhash);
+
+                config
+                    .rpc_client
+                    .send_and_confirm_transaction_with_spinner(&transaction)?;
+            }
+
+            println!("Success!");
+        }
+        ("set-decay", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let grace_epochs = value_t_or_exit!(arg_matches, "grace_epochs", u64);
+            let bps_per_epoch = value_t_or_exit!(arg_matches, "bps_per_epoch", u16);
+
+            println!("Setting decay of distribution {}", dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Grace epochs: {}", grace_epochs);
+            println!("  Decay per epoch (bps): {}", bps_per_epoch);
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let mut transaction = Transaction::new_with_payer(
+                &[wave_dist::instruction::set_decay(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    grace_epochs,
+                    bps_per_epoch,
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success!");
+        }
+        ("settle", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let token_address = *dist.token();
+            let dist_token_account = payout_source_of(&dist, &dist_account);
+
+            let recipients_file = arg_matches.value_of("recipients_file").unwrap();
+            let paid_wallets = unsettled_wallets_of(
+                &config,
+                &dist_account,
+                dist.round(),
+                &recipient_wallets_of(recipients_file)?,
+            )?;
+            let forward_addresses = forward_addresses_of(&config, &dist_account, &paid_wallets)?;
+
+            let fee_payer_pubkey = config.fee_payer.pubkey();
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            // The first call settles the round, even with no one to pay
+            let mut paid_wallets_chunks: Vec<&[Pubkey]> =
+                paid_wallets.chunks(SETTLE_CHUNK_SIZE).collect();
+            if paid_wallets_chunks.is_empty() {
+                paid_wallets_chunks.push(&[]);
+            }
+
+            for (i, (paid_wallets_chunk, forward_addresses)) in paid_wallets_chunks
+                .into_iter()
+                .zip(forward_addresses.chunks(SETTLE_CHUNK_SIZE).chain(Some(&[][..])))
+                .enumerate()
+            {
+                println!(
+                    "Settling {} (recipients {}..{})",
+                    dist_account,
+                    1 + i * SETTLE_CHUNK_SIZE,
+                    i * SETTLE_CHUNK_SIZE + paid_wallets_chunk.len(),
+                );
+                println!("  Program ID: {}", config.program_id);
+                println!("  Dist authority: {}", dist_authority_pubkey);
+                println!("  Recipients:");
+                for paid_wallet in paid_wallets_chunk {
+                    println!("    {}", paid_wallet);
+                }
+                println!("  Fee payer: {}", fee_payer_pubkey);
+                println!();
+
+                let recipient_token_accounts: Vec<Pubkey> = paid_wallets_chunk
+                    .iter()
+                    .zip(forward_addresses)
+                    .map(|(paid_wallet, forward_address)| {
+                        forward_address.unwrap_or_else(|| {
+                            get_associated_token_address(paid_wallet, &token_address)
+                        })
+                    })
+                    .collect();
+                let recipients: Vec<(&Pubkey, &Pubkey)> = recipient_token_accounts
+                    .iter()
+                    .zip(paid_wallets_chunk.iter())
+                    .collect();
+
+                let instructions = vec![wave_dist::instruction::settle(
+                    &config.program_id,
+                    &dist_account,
+                    &dist_authority_pubkey,
+                    &dist_token_account,
+                    &recipients,
+                )];
+
+                let mut transaction =
+                    Transaction::new_with_payer(&instructions, Some(&fee_payer_pubkey));
+
+                let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+                transaction.sign(&signers, recent_blockhash);
+
+                config
+                    .rpc_client
+                    .send_and_confirm_transaction_with_spinner(&transaction)?;
+            }
+
+            println!("Success!");
+        }
+        ("start-round", Some(arg_matches)) => {
+            let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+            let (dist_authority, _) = signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+            let dist_authority_pubkey = dist_authority
+                .as_ref()
+                .map(|dist_authority| dist_authority.pubkey())
+                .unwrap_or_else(|| config.fee_payer.pubkey());
+
+            let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+            let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+
+            let (round_account, _) =
+                wave_dist::find_round_address(&config.program_id, &dist_account, dist.round());
+
+            println!("Starting round {} of distribution {}", dist.round() + 1, dist_account);
+            println!("  Program ID: {}", config.program_id);
+            println!("  Dist authority: {}", dist_authority_pubkey);
+            println!("  Closing round: {}", dist.round());
+            println!("  Closing round account: {}", round_account);
+            println!(
+                "  Unpaid amount carried over (base units): {}",
+                dist.funded_amount().saturating_sub(dist.paid_amount())
+            );
+            println!("  Fee payer: {}", config.fee_payer.pubkey());
+            println!();
+
+            let instructions = vec![wave_dist::instruction::start_round(
+                &config.program_id,
+                &dist_account,
+                &dist_authority_pubkey,
+                &config.fee_payer.pubkey(),
+                dist.round(),
+            )];
+
+            let mut transaction =
+                Transaction::new_with_payer(&instructions, Some(&config.fee_payer.pubkey()));
+
+            let mut signers = vec![config.fee_payer.as_ref()];
+            if let Some(dist_authority) = dist_authority.as_ref() {
+                signers.push(dist_authority.as_ref());
+            }
+
+            let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+            transaction.sign(&signers, recent_blockhash);
+
+            config
+                .rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+            println!("Success! Begin and fund the new round to pay it out.");
+        }
+        ("denylist", Some(arg_matches)) => match arg_matches.subcommand() {
+            ("list", Some(_arg_matches)) => {
+                let denylist_entries = config.rpc_client.get_program_accounts_with_config(
+                    &config.program_id,
+                    RpcProgramAccountsConfig {
+                        filters: Some(vec![RpcFilterType::DataSize(DenylistEntry::LEN as u64)]),
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(config.rpc_client.commitment()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        with_context: None,
+                    },
+                )?;
+
+                let mut wallets: Vec<Pubkey> = denylist_entries
+                    .iter()
+                    .filter_map(|(_, account)| DenylistEntry::unpack(&account.data).ok())
+                    .map(|entry| entry.wallet)
+                    .collect();
+                wallets.sort();
+
+                println!("Denylisted wallets: {}", wallets.len());
+                for wallet in wallets {
+                    println!("  {}", wallet);
+                }
+            }
+            (command, Some(arg_matches)) => {
+                let (admin, _) = signer_of(arg_matches, "admin", &mut wallet_manager)?;
+                let admin_pubkey = admin
+                    .as_ref()
+                    .map(|admin| admin.pubkey())
+                    .unwrap_or_else(|| config.fee_payer.pubkey());
+                let fee_payer_pubkey = config.fee_payer.pubkey();
+
+                let wallets = denylist_wallets_of(arg_matches)?;
+
+                let mut signers = vec![config.fee_payer.as_ref()];
+                if let Some(admin) = admin.as_ref() {
+                    signers.push(admin.as_ref());
+                }
+
+                for wallets_chunk in wallets.chunks(DENYLIST_CHUNK_SIZE) {
+                    let instructions = if command == "add" {
+                        println!("Adding wallets to the denylist");
+                        vec![wave_dist::instruction::add_to_denylist(
+                            &config.program_id,
+                            &admin_pubkey,
+                            &fee_payer_pubkey,
+                            wallets_chunk,
+                        )]
+                    } else {
+                        println!("Removing wallets from the denylist");
+                        vec![wave_dist::instruction::remove_from_denylist(
+                            &config.program_id,
+                            &admin_pubkey,
+                            &fee_payer_pubkey,
+                            wallets_chunk,
+                        )]
+                    };
+                    println!("  Program ID: {}", config.program_id);
+                    println!("  Admin: {}", admin_pubkey);
+                    println!("  Wallets:");
+                    for wallet in wallets_chunk {
+                        println!("    {}", wallet);
+                    }
+                    println!("  Fee payer: {}", fee_payer_pubkey);
+                    println!();
+
+                    let mut transaction =
+                        Transaction::new_with_payer(&instructions, Some(&fee_payer_pubkey));
+
+                    let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+                    transaction.sign(&signers, recent_blockhash);
+
+                    config
+                        .rpc_client
+                        .send_and_confirm_transaction_with_spinner(&transaction)?;
+                }
+
+                println!("Success!");
+            }
+            _ => unreachable!(),
+        },
+        ("grant", Some(arg_matches)) => match arg_matches.subcommand() {
+            ("list", Some(arg_matches)) => {
+                let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+
+                let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+                let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+                let decimals = mint_decimals_of(&config, dist.token())?;
+
+                let grant_accounts = config.rpc_client.get_program_accounts_with_config(
+                    &config.program_id,
+                    RpcProgramAccountsConfig {
+                        filters: Some(vec![RpcFilterType::DataSize(Grant::LEN as u64)]),
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(config.rpc_client.commitment()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        with_context: None,
+                    },
+                )?;
+
+                let mut grants: Vec<Grant> = grant_accounts
+                    .iter()
+                    .filter_map(|(_, account)| Grant::unpack(&account.data).ok())
+                    .filter(|grant| grant.distribution == dist_account)
+                    .collect();
+                grants.sort_by_key(|grant| grant.recipient);
+
+                let epoch = config.rpc_client.get_epoch_info()?.epoch;
+
+                println!("Grants of distribution {}: {}", dist_account, grants.len());
+                for grant in grants {
+                    println!("  {}", grant.recipient);
+                    println!(
+                        "    Amount: {}",
+                        spl_token::amount_to_ui_amount(grant.amount, decimals)
+                    );
+                    println!(
+                        "    Vested: {}",
+                        spl_token::amount_to_ui_amount(grant.vested_amount(epoch), decimals)
+                    );
+                    println!(
+                        "    Released: {}",
+                        spl_token::amount_to_ui_amount(grant.released, decimals)
+                    );
+                    println!("    Vesting epochs: {} to {}", grant.start_epoch, grant.end_epoch);
+                    if grant.revoked {
+                        println!("    Revoked");
+                    }
+                }
+            }
+            (command, Some(arg_matches)) => {
+                let (_, dist_account) = dist_account_of(arg_matches, &config)?;
+                let recipient = pubkey_of(arg_matches, "recipient").unwrap();
+
+                let (dist_authority, _) =
+                    signer_of(arg_matches, "dist_authority", &mut wallet_manager)?;
+                let dist_authority_pubkey = dist_authority
+                    .as_ref()
+                    .map(|dist_authority| dist_authority.pubkey())
+                    .unwrap_or_else(|| config.fee_payer.pubkey());
+                let fee_payer_pubkey = config.fee_payer.pubkey();
+
+                let dist_account_on_chain = config.rpc_client.get_account(&dist_account)?;
+                let dist = Distribution::unpack(&dist_account_on_chain.data)?;
+                let token_address = *dist.token();
+
+                let mut instructions = vec![];
+                match command {
+                    "create" => {
+                        let ui_amount = value_t_or_exit!(arg_matches, "amount", f64);
+                        let amount = spl_token::ui_amount_to_amount(
+                            ui_amount,
+                            mint_decimals_of(&config, &token_address)?,
+                        );
+                        let start_epoch = match value_t!(arg_matches, "start_epoch", u64) {
+                            Ok(start_epoch) => start_epoch,
+                            Err(_) => config.rpc_client.get_epoch_info()?.epoch,
+                        };
+                        let end_epoch = value_t_or_exit!(arg_matches, "end_epoch", u64);
+                        let source_token_account =
+                            get_associated_token_address(&dist_authority_pubkey, &token_address);
+
+                        println!("Creating grant of distribution {}", dist_account);
+                        println!("  Program ID: {}", config.program_id);
+                        println!("  Dist authority: {}", dist_authority_pubkey);
+                        println!("  Recipient: {}", recipient);
+                        println!("  Amount: {}", ui_amount);
+                        println!("  Amount (base units): {}", amount);
+                        println!("  Vesting epochs: {} to {}", start_epoch, end_epoch);
+                        println!("  Fee payer: {}", fee_payer_pubkey);
+                        println!();
+
+                        instructions.push(wave_dist::instruction::create_grant(
+                            &config.program_id,
+                            &dist_account,
+                            &dist_authority_pubkey,
+                            &source_token_account,
+                            &token_address,
+                            &fee_payer_pubkey,
+                            &recipient,
+                            amount,
+                            start_epoch,
+                            end_epoch,
+                        ));
+                    }
+                    "release" => {
+                        let recipient_token_account =
+                            get_associated_token_address(&recipient, &token_address);
+
+                        println!("Releasing grant of distribution {}", dist_account);
+                        println!("  Program ID: {}", config.program_id);
+                        println!("  Recipient: {}", recipient);
+                        println!("  Receiving token account: {}", recipient_token_account);
+                        println!("  Fee payer: {}", fee_payer_pubkey);
+                        println!();
+
+                        if config.rpc_client.get_token_account(&recipient_token_account)?.is_none() {
+                            instructions.push(
+                                spl_associated_token_account::instruction::create_associated_token_account(
+                                    &fee_payer_pubkey,
+                                    &recipient,
+                                    &token_address,
+                                ),
+                            );
+                        }
+                        instructions.push(wave_dist::instruction::release_grant(
+                            &config.program_id,
+                            &dist_account,
+                            &recipient,
+                            &recipient_token_account,
+                        ));
+                    }
+                    _ => {
+                        let destination = pubkey_of(arg_matches, "destination").unwrap_or_else(|| {
+                            get_associated_token_address(&dist_authority_pubkey, &token_address)
+                        });
+
+                        println!("Revoking grant of distribution {}", dist_account);
+                        println!("  Program ID: {}", config.program_id);
+                        println!("  Dist authority: {}", dist_authority_pubkey);
+                        println!("  Recipient: {}", recipient);
+                        println!("  Unvested tokens to: {}", destination);
+                        println!("  Fee payer: {}", fee_payer_pubkey);
+                        println!();
+
+                        instructions.push(wave_dist::instruction::revoke_grant(
+                            &config.program_id,
+                            &dist_account,
+                            &dist_authority_pubkey,
+                            &recipient,
+                            &destination,
+                        ));
+                    }
+                }
+
+                let mut transaction =
+                    Transaction::new_with_payer(&instructions, Some(&fee_payer_pubkey));
+
+                let mut signers = vec![config.fee_payer.as_ref()];
+                if let Some(dist_authority) = dist_authority.as_ref() {
+                    signers.push(dist_authority.as_ref());
+                }
+
+                let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+                transaction.sign(&signers, recent_blockhash);
+
+                config
+                    .rpc_client
+                    .send_and_confirm_transaction_with_spinner(&transaction)?;
+
+                println!("Success!");
+            }
+            _ => unreachable!(),
+        },
         _ => unreachable!(),
     }
 
@@ -0,0 +1,1798 @@
+//! Cross-program invocation helpers
+//!
+//! Programs calling this one give the accounts of an instruction as a typed
+//! struct, in place of an account list put together by hand, and invoke it
+//! with the function named after the instruction, or its `_signed` variant
+//! when some of the signers are PDAs of the calling program.
+//!
+//! The accounts are checked as far as can be done cheaply before invoking:
+//! program and sysvar accounts must be the expected ones, accounts the
+//! instruction writes to must be writable, and payout instructions must be
+//! given a gate token account and payout hook accounts exactly when the
+//! distribution has a gate or a hook. Everything else, such as the derived
+//! addresses, is checked by the program itself.
+//!
+//! `ClaimWithVoucher` and `RelayClaim` read the Ed25519 program instruction
+//! right before the transaction instruction that calls them.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::{get_return_data, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+use crate::{
+    instruction::{DistInstruction, RaffleCommit},
+    query::{DistributionInfo, RecipientPaid},
+    state::{Distribution, DistributionKind, HookFailurePolicy},
+};
+
+/// The accounts of an instruction being put together, along with the
+/// account infos to invoke it with.
+struct AccountList<'info> {
+    metas: Vec<AccountMeta>,
+    infos: Vec<AccountInfo<'info>>,
+}
+
+impl<'info> AccountList<'info> {
+    fn new() -> Self {
+        Self {
+            metas: vec![],
+            infos: vec![],
+        }
+    }
+
+    fn push(&mut self, info: &AccountInfo<'info>, is_writable: bool, is_signer: bool) {
+        self.metas.push(AccountMeta {
+            pubkey: *info.key,
+            is_signer,
+            is_writable,
+        });
+        self.infos.push(info.clone());
+    }
+
+    fn readonly(&mut self, info: &AccountInfo<'info>) {
+        self.push(info, false, false);
+    }
+
+    fn writable(&mut self, info: &AccountInfo<'info>) {
+        self.push(info, true, false);
+    }
+
+    fn signer(&mut self, info: &AccountInfo<'info>) {
+        self.push(info, false, true);
+    }
+
+    fn writable_signer(&mut self, info: &AccountInfo<'info>) {
+        self.push(info, true, true);
+    }
+
+    fn optional_readonly(&mut self, info: Option<&AccountInfo<'info>>) {
+        if let Some(info) = info {
+            self.readonly(info);
+        }
+    }
+
+    /// Adds a program account, which must be `program_id`.
+    fn program(&mut self, info: &AccountInfo<'info>, program_id: &Pubkey) -> ProgramResult {
+        if info.key != program_id {
+            msg!("Program account {} is not {}", info.key, program_id);
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        self.readonly(info);
+        Ok(())
+    }
+
+    /// Adds a sysvar account, which must be `sysvar_id`.
+    fn sysvar(&mut self, info: &AccountInfo<'info>, sysvar_id: &Pubkey) -> ProgramResult {
+        if info.key != sysvar_id {
+            msg!("Sysvar account {} is not {}", info.key, sysvar_id);
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.readonly(info);
+        Ok(())
+    }
+
+    /// Adds the payout hook program and hook authority, which must be given
+    /// exactly when the distribution has a payout hook.
+    fn payout_hook(
+        &mut self,
+        dist: &Distribution,
+        hook: Option<&PayoutHookAccounts<'info>>,
+    ) -> ProgramResult {
+        match (dist.hook_program(), hook) {
+            (Some(hook_program_id), Some(hook)) => {
+                self.program(&hook.program, hook_program_id)?;
+                self.readonly(&hook.authority);
+                Ok(())
+            }
+            (None, None) => Ok(()),
+            _ => {
+                msg!("Hook accounts must be given exactly when the distribution has a hook");
+                Err(ProgramError::InvalidArgument)
+            }
+        }
+    }
+
+    /// Adds accounts passed through with the privileges they were given,
+    /// such as the extra accounts of a payout hook.
+    fn pass_through(&mut self, infos: &[AccountInfo<'info>]) {
+        for info in infos {
+            self.push(info, info.is_writable, info.is_signer);
+        }
+    }
+
+    fn invoke(
+        mut self,
+        program: &AccountInfo<'info>,
+        instruction: DistInstruction,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        for (index, (meta, info)) in self.metas.iter().zip(&self.infos).enumerate() {
+            if meta.is_writable && !info.is_writable {
+                msg!("Account {} ({}) must be writable", index, info.key);
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        let instruction = Instruction {
+            program_id: *program.key,
+            accounts: self.metas,
+            data: instruction.pack(),
+        };
+        self.infos.push(program.clone());
+
+        invoke_signed(&instruction, &self.infos, signer_seeds)
+    }
+}
+
+/// Loads a distribution, to check the accounts given for it.
+fn load_distribution(
+    program: &AccountInfo,
+    distribution: &AccountInfo,
+) -> Result<Distribution, ProgramError> {
+    if distribution.owner != program.key {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Distribution::unpack(&distribution.data.borrow())
+}
+
+/// Fails unless a gate token account is given exactly when the distribution
+/// is gated.
+fn check_gate_account(
+    dist: &Distribution,
+    gate_token_account: Option<&AccountInfo>,
+) -> ProgramResult {
+    match (dist.gate(), gate_token_account) {
+        (Some(_), None) => {
+            msg!("Distribution is gated, gate token account missing");
+            Err(ProgramError::NotEnoughAccountKeys)
+        }
+        (None, Some(gate_token_account)) => {
+            msg!("Distribution isn't gated, got {}", gate_token_account.key);
+            Err(ProgramError::InvalidArgument)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fails unless a recipient is given as many extra accounts as the payout
+/// hook takes.
+fn check_hook_accounts(dist: &Distribution, hook_accounts: &[AccountInfo]) -> ProgramResult {
+    if hook_accounts.len() != dist.hook_accounts_len() {
+        msg!(
+            "Payout hook takes {} accounts, got {}",
+            dist.hook_accounts_len(),
+            hook_accounts.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Reads the answer of a query instruction out of the return data.
+fn return_data_of(program: &AccountInfo) -> Result<Vec<u8>, ProgramError> {
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => Ok(data),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Accounts of the instructions that only take the distribution and its
+/// dist authority, such as `SetGate`. For `AcceptDistAuthority` the
+/// dist authority is the proposed one.
+pub struct DistAuthorityAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+}
+
+impl<'info> DistAuthorityAccounts<'info> {
+    fn invoke(
+        &self,
+        program: &AccountInfo<'info>,
+        instruction: DistInstruction,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let mut account_list = AccountList::new();
+        account_list.writable(&self.distribution);
+        account_list.signer(&self.dist_authority);
+        account_list.invoke(program, instruction, signer_seeds)
+    }
+}
+
+/// Accounts of the instructions that only take the program config and its
+/// admin.
+pub struct ConfigAdminAccounts<'info> {
+    pub config: AccountInfo<'info>,
+    pub admin: AccountInfo<'info>,
+}
+
+impl<'info> ConfigAdminAccounts<'info> {
+    fn invoke(
+        &self,
+        program: &AccountInfo<'info>,
+        instruction: DistInstruction,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let mut account_list = AccountList::new();
+        account_list.writable(&self.config);
+        account_list.signer(&self.admin);
+        account_list.invoke(program, instruction, signer_seeds)
+    }
+}
+
+pub struct InitializeDistributionAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token: AccountInfo<'info>,
+    pub distribution: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+}
+
+/// Invokes `InitializeDistribution`.
+pub fn initialize_distribution<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &InitializeDistributionAccounts<'info>,
+    seed: Pubkey,
+    project_name: &str,
+    seed_bump: u8,
+    max_recipients: u16,
+    dist_authority: Pubkey,
+) -> ProgramResult {
+    initialize_distribution_signed(
+        program,
+        accounts,
+        seed,
+        project_name,
+        seed_bump,
+        max_recipients,
+        dist_authority,
+        &[],
+    )
+}
+
+/// Invokes `InitializeDistribution`, signing with PDAs of the caller.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_distribution_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &InitializeDistributionAccounts<'info>,
+    seed: Pubkey,
+    project_name: &str,
+    seed_bump: u8,
+    max_recipients: u16,
+    dist_authority: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.readonly(&accounts.token);
+    account_list.writable(&accounts.distribution);
+    account_list.readonly(&accounts.config);
+    account_list.invoke(
+        program,
+        DistInstruction::InitializeDistribution {
+            seed,
+            project_name: project_name.to_owned(),
+            seed_bump,
+            max_recipients,
+            dist_authority,
+        },
+        signer_seeds,
+    )
+}
+
+pub struct FundDistributionAccounts<'info> {
+    pub source: AccountInfo<'info>,
+    pub source_token_account: AccountInfo<'info>,
+    pub distribution: AccountInfo<'info>,
+    pub dist_token_account: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    /// Only when the program config charges a protocol fee.
+    pub treasury_token_account: Option<AccountInfo<'info>>,
+}
+
+/// Invokes `FundDistribution`.
+pub fn fund_distribution<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &FundDistributionAccounts<'info>,
+    amount: u64,
+    delegated: bool,
+) -> ProgramResult {
+    fund_distribution_signed(program, accounts, amount, delegated, &[])
+}
+
+/// Invokes `FundDistribution`, signing with PDAs of the caller.
+pub fn fund_distribution_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &FundDistributionAccounts<'info>,
+    amount: u64,
+    delegated: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.signer(&accounts.source);
+    account_list.writable(&accounts.source_token_account);
+    account_list.writable(&accounts.distribution);
+    account_list.writable(&accounts.dist_token_account);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.readonly(&accounts.config);
+    if let Some(treasury_token_account) = &accounts.treasury_token_account {
+        account_list.writable(treasury_token_account);
+    }
+    account_list.invoke(
+        program,
+        DistInstruction::FundDistribution { amount, delegated },
+        signer_seeds,
+    )
+}
+
+/// Invokes `ProposeDistAuthority`.
+pub fn propose_dist_authority<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    new_dist_authority: Pubkey,
+) -> ProgramResult {
+    propose_dist_authority_signed(program, accounts, new_dist_authority, &[])
+}
+
+/// Invokes `ProposeDistAuthority`, signing with PDAs of the caller.
+pub fn propose_dist_authority_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    new_dist_authority: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::ProposeDistAuthority { new_dist_authority },
+        signer_seeds,
+    )
+}
+
+pub struct BeginDistributionAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    /// Only when the distribution is funded by delegation.
+    pub funding_source: Option<AccountInfo<'info>>,
+}
+
+/// Invokes `BeginDistribution`.
+pub fn begin_distribution<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &BeginDistributionAccounts<'info>,
+    num_recipients: u16,
+    raffle: Option<RaffleCommit>,
+) -> ProgramResult {
+    begin_distribution_signed(program, accounts, num_recipients, raffle, &[])
+}
+
+/// Invokes `BeginDistribution`, signing with PDAs of the caller.
+pub fn begin_distribution_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &BeginDistributionAccounts<'info>,
+    num_recipients: u16,
+    raffle: Option<RaffleCommit>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.optional_readonly(accounts.funding_source.as_ref());
+    account_list.invoke(
+        program,
+        DistInstruction::BeginDistribution {
+            num_recipients,
+            raffle,
+        },
+        signer_seeds,
+    )
+}
+
+pub struct DistributeAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    /// The distribution token account, the funding source token account, the
+    /// token mint of a mint distribution, or the distribution account itself
+    /// for an NFT batch distribution.
+    pub dist_token_account: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    /// Only when the distribution has a payout hook.
+    pub hook: Option<PayoutHookAccounts<'info>>,
+    pub recipients: Vec<DistributeRecipient<'info>>,
+}
+
+pub struct PayoutHookAccounts<'info> {
+    pub program: AccountInfo<'info>,
+    /// The hook authority of the distribution.
+    pub authority: AccountInfo<'info>,
+}
+
+pub struct DistributeRecipient<'info> {
+    pub token_account: AccountInfo<'info>,
+    pub receipt: AccountInfo<'info>,
+    pub denylist_entry: AccountInfo<'info>,
+    /// The forward address account of the recipient wallet, or the pool item
+    /// it gets for an NFT batch distribution.
+    pub forward_address: AccountInfo<'info>,
+    /// Only when the distribution is gated.
+    pub gate_token_account: Option<AccountInfo<'info>>,
+    /// Passed on to the payout hook, as many as it takes.
+    pub hook_accounts: Vec<AccountInfo<'info>>,
+}
+
+/// Invokes `Distribute`.
+pub fn distribute<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistributeAccounts<'info>,
+    best_effort: bool,
+) -> ProgramResult {
+    distribute_signed(program, accounts, best_effort, &[])
+}
+
+/// Invokes `Distribute`, signing with PDAs of the caller.
+pub fn distribute_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistributeAccounts<'info>,
+    best_effort: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let dist = load_distribution(program, &accounts.distribution)?;
+
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.writable(&accounts.dist_token_account);
+    account_list.readonly(&accounts.config);
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.payout_hook(&dist, accounts.hook.as_ref())?;
+    for recipient in &accounts.recipients {
+        check_gate_account(&dist, recipient.gate_token_account.as_ref())?;
+        check_hook_accounts(&dist, &recipient.hook_accounts)?;
+
+        account_list.writable(&recipient.token_account);
+        account_list.writable(&recipient.receipt);
+        account_list.readonly(&recipient.denylist_entry);
+        if dist.kind() == DistributionKind::NftBatch {
+            account_list.writable(&recipient.forward_address);
+        } else {
+            account_list.readonly(&recipient.forward_address);
+        }
+        account_list.optional_readonly(recipient.gate_token_account.as_ref());
+        account_list.pass_through(&recipient.hook_accounts);
+    }
+    account_list.invoke(
+        program,
+        DistInstruction::Distribute { best_effort },
+        signer_seeds,
+    )
+}
+
+pub struct SetMetadataAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Invokes `SetMetadata`.
+pub fn set_metadata<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SetMetadataAccounts<'info>,
+    description: &str,
+    uri: &str,
+    creator_label: &str,
+    category: &str,
+) -> ProgramResult {
+    set_metadata_signed(
+        program,
+        accounts,
+        description,
+        uri,
+        creator_label,
+        category,
+        &[],
+    )
+}
+
+/// Invokes `SetMetadata`, signing with PDAs of the caller.
+pub fn set_metadata_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SetMetadataAccounts<'info>,
+    description: &str,
+    uri: &str,
+    creator_label: &str,
+    category: &str,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.readonly(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.writable_signer(&accounts.payer);
+    account_list.writable(&accounts.metadata);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.invoke(
+        program,
+        DistInstruction::SetMetadata {
+            description: description.to_owned(),
+            uri: uri.to_owned(),
+            creator_label: creator_label.to_owned(),
+            category: category.to_owned(),
+        },
+        signer_seeds,
+    )
+}
+
+pub struct InitializeConfigAccounts<'info> {
+    pub upgrade_authority: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub program_data: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Invokes `InitializeConfig`.
+pub fn initialize_config<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &InitializeConfigAccounts<'info>,
+    admin: Pubkey,
+) -> ProgramResult {
+    initialize_config_signed(program, accounts, admin, &[])
+}
+
+/// Invokes `InitializeConfig`, signing with PDAs of the caller.
+pub fn initialize_config_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &InitializeConfigAccounts<'info>,
+    admin: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable_signer(&accounts.upgrade_authority);
+    account_list.writable(&accounts.config);
+    account_list.readonly(&accounts.program_data);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.invoke(
+        program,
+        DistInstruction::InitializeConfig { admin },
+        signer_seeds,
+    )
+}
+
+/// Invokes `UpdateConfig`.
+pub fn update_config<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ConfigAdminAccounts<'info>,
+    paused: bool,
+    protocol_fee_bps: u16,
+    treasury: Pubkey,
+) -> ProgramResult {
+    update_config_signed(program, accounts, paused, protocol_fee_bps, treasury, &[])
+}
+
+/// Invokes `UpdateConfig`, signing with PDAs of the caller.
+pub fn update_config_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ConfigAdminAccounts<'info>,
+    paused: bool,
+    protocol_fee_bps: u16,
+    treasury: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::UpdateConfig {
+            paused,
+            protocol_fee_bps,
+            treasury,
+        },
+        signer_seeds,
+    )
+}
+
+/// Invokes `SetAllowedMints`.
+pub fn set_allowed_mints<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ConfigAdminAccounts<'info>,
+    mints: &[Pubkey],
+) -> ProgramResult {
+    set_allowed_mints_signed(program, accounts, mints, &[])
+}
+
+/// Invokes `SetAllowedMints`, signing with PDAs of the caller.
+pub fn set_allowed_mints_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ConfigAdminAccounts<'info>,
+    mints: &[Pubkey],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::SetAllowedMints {
+            mints: mints.to_vec(),
+        },
+        signer_seeds,
+    )
+}
+
+/// Invokes `AcceptDistAuthority`.
+pub fn accept_dist_authority<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+) -> ProgramResult {
+    accept_dist_authority_signed(program, accounts, &[])
+}
+
+/// Invokes `AcceptDistAuthority`, signing with PDAs of the caller.
+pub fn accept_dist_authority_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(program, DistInstruction::AcceptDistAuthority, signer_seeds)
+}
+
+/// Invokes `CancelDistAuthority`.
+pub fn cancel_dist_authority<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+) -> ProgramResult {
+    cancel_dist_authority_signed(program, accounts, &[])
+}
+
+/// Invokes `CancelDistAuthority`, signing with PDAs of the caller.
+pub fn cancel_dist_authority_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(program, DistInstruction::CancelDistAuthority, signer_seeds)
+}
+
+pub struct DistributeToWalletsAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    /// The distribution token account, the funding source token account or
+    /// the token mint of a mint distribution.
+    pub dist_token_account: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub token: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    /// Only when the distribution has a payout hook.
+    pub hook: Option<PayoutHookAccounts<'info>>,
+    pub recipients: Vec<WalletRecipient<'info>>,
+}
+
+pub struct WalletRecipient<'info> {
+    pub wallet: AccountInfo<'info>,
+    /// The wallet's associated token account, or its forward address.
+    pub token_account: AccountInfo<'info>,
+    pub receipt: AccountInfo<'info>,
+    pub denylist_entry: AccountInfo<'info>,
+    pub forward_address: AccountInfo<'info>,
+    /// Only when the distribution is gated.
+    pub gate_token_account: Option<AccountInfo<'info>>,
+    /// Passed on to the payout hook, as many as it takes.
+    pub hook_accounts: Vec<AccountInfo<'info>>,
+}
+
+/// Invokes `DistributeToWallets`.
+pub fn distribute_to_wallets<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistributeToWalletsAccounts<'info>,
+    best_effort: bool,
+) -> ProgramResult {
+    distribute_to_wallets_signed(program, accounts, best_effort, &[])
+}
+
+/// Invokes `DistributeToWallets`, signing with PDAs of the caller.
+pub fn distribute_to_wallets_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistributeToWalletsAccounts<'info>,
+    best_effort: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let dist = load_distribution(program, &accounts.distribution)?;
+
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.writable(&accounts.dist_token_account);
+    account_list.readonly(&accounts.config);
+    account_list.writable_signer(&accounts.payer);
+    account_list.readonly(&accounts.token);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.program(
+        &accounts.associated_token_program,
+        &spl_associated_token_account::id(),
+    )?;
+    account_list.payout_hook(&dist, accounts.hook.as_ref())?;
+    for recipient in &accounts.recipients {
+        check_gate_account(&dist, recipient.gate_token_account.as_ref())?;
+        check_hook_accounts(&dist, &recipient.hook_accounts)?;
+
+        account_list.readonly(&recipient.wallet);
+        account_list.writable(&recipient.token_account);
+        account_list.writable(&recipient.receipt);
+        account_list.readonly(&recipient.denylist_entry);
+        account_list.readonly(&recipient.forward_address);
+        account_list.optional_readonly(recipient.gate_token_account.as_ref());
+        account_list.pass_through(&recipient.hook_accounts);
+    }
+    account_list.invoke(
+        program,
+        DistInstruction::DistributeToWallets { best_effort },
+        signer_seeds,
+    )
+}
+
+/// Invokes `SetReleaseRate`.
+pub fn set_release_rate<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    release_rate: u64,
+    recipients_root: Hash,
+) -> ProgramResult {
+    set_release_rate_signed(program, accounts, release_rate, recipients_root, &[])
+}
+
+/// Invokes `SetReleaseRate`, signing with PDAs of the caller.
+pub fn set_release_rate_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    release_rate: u64,
+    recipients_root: Hash,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::SetReleaseRate {
+            release_rate,
+            recipients_root,
+        },
+        signer_seeds,
+    )
+}
+
+pub struct DripAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    /// The distribution token account, the funding source token account or
+    /// the token mint of a mint distribution.
+    pub dist_token_account: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub recipients: Vec<DripRecipient<'info>>,
+}
+
+pub struct DripRecipient<'info> {
+    /// The recipient token account, or the wallet's forward address.
+    pub token_account: AccountInfo<'info>,
+    pub receipt: AccountInfo<'info>,
+    pub denylist_entry: AccountInfo<'info>,
+    pub forward_address: AccountInfo<'info>,
+    /// Only when the distribution is gated.
+    pub gate_token_account: Option<AccountInfo<'info>>,
+    /// Merkle proof of the recipient wallet against the recipients root.
+    pub proof: Vec<Hash>,
+}
+
+/// Invokes `Drip`.
+pub fn drip<'info>(program: &AccountInfo<'info>, accounts: &DripAccounts<'info>) -> ProgramResult {
+    drip_signed(program, accounts, &[])
+}
+
+/// Invokes `Drip`, signing with PDAs of the caller.
+pub fn drip_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DripAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let dist = load_distribution(program, &accounts.distribution)?;
+
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.writable(&accounts.dist_token_account);
+    account_list.readonly(&accounts.config);
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    for recipient in &accounts.recipients {
+        check_gate_account(&dist, recipient.gate_token_account.as_ref())?;
+
+        account_list.writable(&recipient.token_account);
+        account_list.writable(&recipient.receipt);
+        account_list.readonly(&recipient.denylist_entry);
+        account_list.readonly(&recipient.forward_address);
+        account_list.optional_readonly(recipient.gate_token_account.as_ref());
+    }
+    let proofs = accounts
+        .recipients
+        .iter()
+        .map(|recipient| recipient.proof.clone())
+        .collect();
+    account_list.invoke(program, DistInstruction::Drip { proofs }, signer_seeds)
+}
+
+pub struct StartRoundAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub round_summary: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Invokes `StartRound`.
+pub fn start_round<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &StartRoundAccounts<'info>,
+) -> ProgramResult {
+    start_round_signed(program, accounts, &[])
+}
+
+/// Invokes `StartRound`, signing with PDAs of the caller.
+pub fn start_round_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &StartRoundAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.writable_signer(&accounts.payer);
+    account_list.writable(&accounts.round_summary);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.invoke(program, DistInstruction::StartRound, signer_seeds)
+}
+
+pub struct AddToDenylistAccounts<'info> {
+    pub config: AccountInfo<'info>,
+    pub admin: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    /// The denylist entry of each wallet, in the same order.
+    pub denylist_entries: Vec<AccountInfo<'info>>,
+}
+
+/// Invokes `AddToDenylist`.
+pub fn add_to_denylist<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &AddToDenylistAccounts<'info>,
+    wallets: &[Pubkey],
+) -> ProgramResult {
+    add_to_denylist_signed(program, accounts, wallets, &[])
+}
+
+/// Invokes `AddToDenylist`, signing with PDAs of the caller.
+pub fn add_to_denylist_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &AddToDenylistAccounts<'info>,
+    wallets: &[Pubkey],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.denylist_entries.len() != wallets.len() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut account_list = AccountList::new();
+    account_list.readonly(&accounts.config);
+    account_list.signer(&accounts.admin);
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    for denylist_entry in &accounts.denylist_entries {
+        account_list.writable(denylist_entry);
+    }
+    account_list.invoke(
+        program,
+        DistInstruction::AddToDenylist {
+            wallets: wallets.to_vec(),
+        },
+        signer_seeds,
+    )
+}
+
+pub struct RemoveFromDenylistAccounts<'info> {
+    pub config: AccountInfo<'info>,
+    pub admin: AccountInfo<'info>,
+    pub rent_receiver: AccountInfo<'info>,
+    /// The denylist entry of each wallet, in the same order.
+    pub denylist_entries: Vec<AccountInfo<'info>>,
+}
+
+/// Invokes `RemoveFromDenylist`.
+pub fn remove_from_denylist<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &RemoveFromDenylistAccounts<'info>,
+    wallets: &[Pubkey],
+) -> ProgramResult {
+    remove_from_denylist_signed(program, accounts, wallets, &[])
+}
+
+/// Invokes `RemoveFromDenylist`, signing with PDAs of the caller.
+pub fn remove_from_denylist_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &RemoveFromDenylistAccounts<'info>,
+    wallets: &[Pubkey],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.denylist_entries.len() != wallets.len() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut account_list = AccountList::new();
+    account_list.readonly(&accounts.config);
+    account_list.signer(&accounts.admin);
+    account_list.writable(&accounts.rent_receiver);
+    for denylist_entry in &accounts.denylist_entries {
+        account_list.writable(denylist_entry);
+    }
+    account_list.invoke(
+        program,
+        DistInstruction::RemoveFromDenylist {
+            wallets: wallets.to_vec(),
+        },
+        signer_seeds,
+    )
+}
+
+pub struct SetMintDistributionAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub token: AccountInfo<'info>,
+}
+
+/// Invokes `SetMintDistribution`.
+pub fn set_mint_distribution<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SetMintDistributionAccounts<'info>,
+    supply_cap: u64,
+) -> ProgramResult {
+    set_mint_distribution_signed(program, accounts, supply_cap, &[])
+}
+
+/// Invokes `SetMintDistribution`, signing with PDAs of the caller.
+pub fn set_mint_distribution_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SetMintDistributionAccounts<'info>,
+    supply_cap: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.readonly(&accounts.token);
+    account_list.invoke(
+        program,
+        DistInstruction::SetMintDistribution { supply_cap },
+        signer_seeds,
+    )
+}
+
+pub struct DepositNftsAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub owner: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+    pub nfts: Vec<DepositedNft<'info>>,
+}
+
+pub struct DepositedNft<'info> {
+    pub mint: AccountInfo<'info>,
+    /// The owner's token account holding the NFT.
+    pub token_account: AccountInfo<'info>,
+    pub pool_item: AccountInfo<'info>,
+}
+
+/// Invokes `DepositNfts`.
+pub fn deposit_nfts<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DepositNftsAccounts<'info>,
+) -> ProgramResult {
+    deposit_nfts_signed(program, accounts, &[])
+}
+
+/// Invokes `DepositNfts`, signing with PDAs of the caller.
+pub fn deposit_nfts_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DepositNftsAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.signer(&accounts.owner);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.sysvar(&accounts.rent, &sysvar::rent::id())?;
+    for nft in &accounts.nfts {
+        account_list.readonly(&nft.mint);
+        account_list.writable(&nft.token_account);
+        account_list.writable(&nft.pool_item);
+    }
+    account_list.invoke(program, DistInstruction::DepositNfts, signer_seeds)
+}
+
+pub struct SetRedemptionAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub input_mint: AccountInfo<'info>,
+    /// The distribution's associated token account of the input mint.
+    pub input_vault: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+}
+
+/// Invokes `SetRedemption`.
+pub fn set_redemption<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SetRedemptionAccounts<'info>,
+    input_mint: Pubkey,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    cap: u64,
+    burn_input: bool,
+) -> ProgramResult {
+    set_redemption_signed(
+        program,
+        accounts,
+        input_mint,
+        rate_numerator,
+        rate_denominator,
+        cap,
+        burn_input,
+        &[],
+    )
+}
+
+/// Invokes `SetRedemption`, signing with PDAs of the caller.
+#[allow(clippy::too_many_arguments)]
+pub fn set_redemption_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SetRedemptionAccounts<'info>,
+    input_mint: Pubkey,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    cap: u64,
+    burn_input: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.readonly(&accounts.input_mint);
+    account_list.writable(&accounts.input_vault);
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.program(
+        &accounts.associated_token_program,
+        &spl_associated_token_account::id(),
+    )?;
+    account_list.invoke(
+        program,
+        DistInstruction::SetRedemption {
+            input_mint,
+            rate_numerator,
+            rate_denominator,
+            cap,
+            burn_input,
+        },
+        signer_seeds,
+    )
+}
+
+pub struct RedeemAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub redeemer: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    /// The distribution token account or the funding source token account.
+    pub dist_token_account: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub input_mint: AccountInfo<'info>,
+    pub input_token_account: AccountInfo<'info>,
+    pub input_vault: AccountInfo<'info>,
+    pub output_token_account: AccountInfo<'info>,
+    /// Denylist entry of the owner of the output token account.
+    pub denylist_entry: AccountInfo<'info>,
+}
+
+/// Invokes `Redeem`.
+pub fn redeem<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &RedeemAccounts<'info>,
+    amount: u64,
+) -> ProgramResult {
+    redeem_signed(program, accounts, amount, &[])
+}
+
+/// Invokes `Redeem`, signing with PDAs of the caller.
+pub fn redeem_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &RedeemAccounts<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.redeemer);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.writable(&accounts.dist_token_account);
+    account_list.readonly(&accounts.config);
+    account_list.writable(&accounts.input_mint);
+    account_list.writable(&accounts.input_token_account);
+    account_list.writable(&accounts.input_vault);
+    account_list.writable(&accounts.output_token_account);
+    account_list.readonly(&accounts.denylist_entry);
+    account_list.invoke(program, DistInstruction::Redeem { amount }, signer_seeds)
+}
+
+pub struct DrawAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+/// Invokes `Draw`.
+pub fn draw<'info>(program: &AccountInfo<'info>, accounts: &DrawAccounts<'info>) -> ProgramResult {
+    draw_signed(program, accounts, &[])
+}
+
+/// Invokes `Draw`, signing with PDAs of the caller.
+pub fn draw_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DrawAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.sysvar(&accounts.slot_hashes, &sysvar::slot_hashes::id())?;
+    account_list.invoke(program, DistInstruction::Draw, signer_seeds)
+}
+
+pub struct ClaimRafflePrizeAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    /// The distribution token account or the funding source token account.
+    pub dist_token_account: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub winner_token_account: AccountInfo<'info>,
+    pub denylist_entry: AccountInfo<'info>,
+    pub forward_address: AccountInfo<'info>,
+    pub receipt: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    /// Only when the distribution is gated.
+    pub gate_token_account: Option<AccountInfo<'info>>,
+}
+
+/// Invokes `ClaimRafflePrize`.
+pub fn claim_raffle_prize<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ClaimRafflePrizeAccounts<'info>,
+    index: u32,
+    proof: Vec<Hash>,
+) -> ProgramResult {
+    claim_raffle_prize_signed(program, accounts, index, proof, &[])
+}
+
+/// Invokes `ClaimRafflePrize`, signing with PDAs of the caller.
+pub fn claim_raffle_prize_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ClaimRafflePrizeAccounts<'info>,
+    index: u32,
+    proof: Vec<Hash>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let dist = load_distribution(program, &accounts.distribution)?;
+    check_gate_account(&dist, accounts.gate_token_account.as_ref())?;
+
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.writable(&accounts.dist_token_account);
+    account_list.readonly(&accounts.config);
+    account_list.writable(&accounts.winner_token_account);
+    account_list.readonly(&accounts.denylist_entry);
+    account_list.readonly(&accounts.forward_address);
+    account_list.writable(&accounts.receipt);
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.optional_readonly(accounts.gate_token_account.as_ref());
+    account_list.invoke(
+        program,
+        DistInstruction::ClaimRafflePrize { index, proof },
+        signer_seeds,
+    )
+}
+
+pub struct CreateGrantAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub source_token_account: AccountInfo<'info>,
+    pub grant: AccountInfo<'info>,
+    pub grant_vault: AccountInfo<'info>,
+    pub token: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+}
+
+/// Invokes `CreateGrant`.
+pub fn create_grant<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &CreateGrantAccounts<'info>,
+    recipient: Pubkey,
+    amount: u64,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> ProgramResult {
+    create_grant_signed(
+        program,
+        accounts,
+        recipient,
+        amount,
+        start_epoch,
+        end_epoch,
+        &[],
+    )
+}
+
+/// Invokes `CreateGrant`, signing with PDAs of the caller.
+pub fn create_grant_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &CreateGrantAccounts<'info>,
+    recipient: Pubkey,
+    amount: u64,
+    start_epoch: u64,
+    end_epoch: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.readonly(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.writable(&accounts.source_token_account);
+    account_list.writable(&accounts.grant);
+    account_list.writable(&accounts.grant_vault);
+    account_list.readonly(&accounts.token);
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.sysvar(&accounts.rent, &sysvar::rent::id())?;
+    account_list.invoke(
+        program,
+        DistInstruction::CreateGrant {
+            recipient,
+            amount,
+            start_epoch,
+            end_epoch,
+        },
+        signer_seeds,
+    )
+}
+
+pub struct ReleaseGrantAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub grant: AccountInfo<'info>,
+    pub grant_vault: AccountInfo<'info>,
+    pub recipient_token_account: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub denylist_entry: AccountInfo<'info>,
+}
+
+/// Invokes `ReleaseGrant`.
+pub fn release_grant<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ReleaseGrantAccounts<'info>,
+) -> ProgramResult {
+    release_grant_signed(program, accounts, &[])
+}
+
+/// Invokes `ReleaseGrant`, signing with PDAs of the caller.
+pub fn release_grant_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ReleaseGrantAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.readonly(&accounts.distribution);
+    account_list.writable(&accounts.grant);
+    account_list.writable(&accounts.grant_vault);
+    account_list.writable(&accounts.recipient_token_account);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.readonly(&accounts.config);
+    account_list.readonly(&accounts.denylist_entry);
+    account_list.invoke(program, DistInstruction::ReleaseGrant, signer_seeds)
+}
+
+pub struct RevokeGrantAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub grant: AccountInfo<'info>,
+    pub grant_vault: AccountInfo<'info>,
+    pub destination_token_account: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+/// Invokes `RevokeGrant`.
+pub fn revoke_grant<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &RevokeGrantAccounts<'info>,
+) -> ProgramResult {
+    revoke_grant_signed(program, accounts, &[])
+}
+
+/// Invokes `RevokeGrant`, signing with PDAs of the caller.
+pub fn revoke_grant_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &RevokeGrantAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.readonly(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.writable(&accounts.grant);
+    account_list.writable(&accounts.grant_vault);
+    account_list.writable(&accounts.destination_token_account);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.invoke(program, DistInstruction::RevokeGrant, signer_seeds)
+}
+
+pub struct SetForwardAddressAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub wallet: AccountInfo<'info>,
+    pub forward_address: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Invokes `SetForwardAddress`.
+pub fn set_forward_address<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SetForwardAddressAccounts<'info>,
+) -> ProgramResult {
+    set_forward_address_signed(program, accounts, &[])
+}
+
+/// Invokes `SetForwardAddress`, signing with PDAs of the caller.
+pub fn set_forward_address_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SetForwardAddressAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.readonly(&accounts.distribution);
+    account_list.signer(&accounts.wallet);
+    account_list.writable(&accounts.forward_address);
+    account_list.readonly(&accounts.destination);
+    account_list.writable_signer(&accounts.payer);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.invoke(program, DistInstruction::SetForwardAddress, signer_seeds)
+}
+
+/// Invokes `SetGate`.
+pub fn set_gate<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    gate_mint: Pubkey,
+    min_balance: u64,
+) -> ProgramResult {
+    set_gate_signed(program, accounts, gate_mint, min_balance, &[])
+}
+
+/// Invokes `SetGate`, signing with PDAs of the caller.
+pub fn set_gate_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    gate_mint: Pubkey,
+    min_balance: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::SetGate {
+            gate_mint,
+            min_balance,
+        },
+        signer_seeds,
+    )
+}
+
+/// Invokes `SetAttester`.
+pub fn set_attester<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    attester: Pubkey,
+) -> ProgramResult {
+    set_attester_signed(program, accounts, attester, &[])
+}
+
+/// Invokes `SetAttester`, signing with PDAs of the caller.
+pub fn set_attester_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    attester: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::SetAttester { attester },
+        signer_seeds,
+    )
+}
+
+pub struct ClaimWithVoucherAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    /// The distribution token account, the funding source token account or
+    /// the token mint of a mint distribution.
+    pub dist_token_account: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub recipient_token_account: AccountInfo<'info>,
+    pub denylist_entry: AccountInfo<'info>,
+    pub forward_address: AccountInfo<'info>,
+    pub used_voucher: AccountInfo<'info>,
+    /// The account paying for the used voucher account, which is the
+    /// relayer for `RelayClaim`.
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub instructions: AccountInfo<'info>,
+    /// Only when the distribution is gated.
+    pub gate_token_account: Option<AccountInfo<'info>>,
+}
+
+impl<'info> ClaimWithVoucherAccounts<'info> {
+    fn invoke(
+        &self,
+        program: &AccountInfo<'info>,
+        relayer_token_account: Option<&AccountInfo<'info>>,
+        instruction: DistInstruction,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let dist = load_distribution(program, &self.distribution)?;
+        check_gate_account(&dist, self.gate_token_account.as_ref())?;
+
+        let mut account_list = AccountList::new();
+        account_list.writable(&self.distribution);
+        account_list.program(&self.token_program, &spl_token::id())?;
+        account_list.writable(&self.dist_token_account);
+        account_list.readonly(&self.config);
+        account_list.writable(&self.recipient_token_account);
+        account_list.readonly(&self.denylist_entry);
+        account_list.readonly(&self.forward_address);
+        account_list.writable(&self.used_voucher);
+        account_list.writable_signer(&self.payer);
+        account_list.program(&self.system_program, &system_program::id())?;
+        account_list.sysvar(&self.instructions, &sysvar::instructions::id())?;
+        if let Some(relayer_token_account) = relayer_token_account {
+            account_list.writable(relayer_token_account);
+        }
+        account_list.optional_readonly(self.gate_token_account.as_ref());
+        account_list.invoke(program, instruction, signer_seeds)
+    }
+}
+
+/// Invokes `ClaimWithVoucher`.
+pub fn claim_with_voucher<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ClaimWithVoucherAccounts<'info>,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+) -> ProgramResult {
+    claim_with_voucher_signed(program, accounts, amount, nonce, expiry, &[])
+}
+
+/// Invokes `ClaimWithVoucher`, signing with PDAs of the caller.
+pub fn claim_with_voucher_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ClaimWithVoucherAccounts<'info>,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        None,
+        DistInstruction::ClaimWithVoucher {
+            amount,
+            nonce,
+            expiry,
+        },
+        signer_seeds,
+    )
+}
+
+/// Invokes `SetRelayFeeCap`.
+pub fn set_relay_fee_cap<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    relay_fee_cap: u64,
+) -> ProgramResult {
+    set_relay_fee_cap_signed(program, accounts, relay_fee_cap, &[])
+}
+
+/// Invokes `SetRelayFeeCap`, signing with PDAs of the caller.
+pub fn set_relay_fee_cap_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    relay_fee_cap: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::SetRelayFeeCap { relay_fee_cap },
+        signer_seeds,
+    )
+}
+
+/// Invokes `RelayClaim`, with the relayer as payer of the voucher accounts.
+pub fn relay_claim<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ClaimWithVoucherAccounts<'info>,
+    relayer_token_account: &AccountInfo<'info>,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+    relayer_fee: u64,
+) -> ProgramResult {
+    relay_claim_signed(
+        program,
+        accounts,
+        relayer_token_account,
+        amount,
+        nonce,
+        expiry,
+        relayer_fee,
+        &[],
+    )
+}
+
+/// Invokes `RelayClaim`, with the relayer as payer of the voucher accounts,
+/// signing with PDAs of the caller.
+#[allow(clippy::too_many_arguments)]
+pub fn relay_claim_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &ClaimWithVoucherAccounts<'info>,
+    relayer_token_account: &AccountInfo<'info>,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+    relayer_fee: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        Some(relayer_token_account),
+        DistInstruction::RelayClaim {
+            amount,
+            nonce,
+            expiry,
+            relayer_fee,
+        },
+        signer_seeds,
+    )
+}
+
+/// Invokes `SetDecay`.
+pub fn set_decay<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    grace_epochs: u64,
+    bps_per_epoch: u16,
+) -> ProgramResult {
+    set_decay_signed(program, accounts, grace_epochs, bps_per_epoch, &[])
+}
+
+/// Invokes `SetDecay`, signing with PDAs of the caller.
+pub fn set_decay_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    grace_epochs: u64,
+    bps_per_epoch: u16,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::SetDecay {
+            grace_epochs,
+            bps_per_epoch,
+        },
+        signer_seeds,
+    )
+}
+
+pub struct SettleAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub dist_authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    /// The distribution token account, the funding source token account or
+    /// the token mint of a mint distribution.
+    pub dist_token_account: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub recipients: Vec<SettleRecipient<'info>>,
+}
+
+pub struct SettleRecipient<'info> {
+    pub token_account: AccountInfo<'info>,
+    pub receipt: AccountInfo<'info>,
+    pub denylist_entry: AccountInfo<'info>,
+    pub forward_address: AccountInfo<'info>,
+}
+
+/// Invokes `Settle`.
+pub fn settle<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SettleAccounts<'info>,
+) -> ProgramResult {
+    settle_signed(program, accounts, &[])
+}
+
+/// Invokes `Settle`, signing with PDAs of the caller.
+pub fn settle_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &SettleAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.signer(&accounts.dist_authority);
+    account_list.program(&accounts.token_program, &spl_token::id())?;
+    account_list.writable(&accounts.dist_token_account);
+    account_list.readonly(&accounts.config);
+    for recipient in &accounts.recipients {
+        account_list.writable(&recipient.token_account);
+        account_list.writable(&recipient.receipt);
+        account_list.readonly(&recipient.denylist_entry);
+        account_list.readonly(&recipient.forward_address);
+    }
+    account_list.invoke(program, DistInstruction::Settle, signer_seeds)
+}
+
+/// Invokes `SetHook`.
+pub fn set_hook<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    hook_program: Pubkey,
+    hook_accounts_len: u8,
+    failure_policy: HookFailurePolicy,
+) -> ProgramResult {
+    set_hook_signed(
+        program,
+        accounts,
+        hook_program,
+        hook_accounts_len,
+        failure_policy,
+        &[],
+    )
+}
+
+/// Invokes `SetHook`, signing with PDAs of the caller.
+pub fn set_hook_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &DistAuthorityAccounts<'info>,
+    hook_program: Pubkey,
+    hook_accounts_len: u8,
+    failure_policy: HookFailurePolicy,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    accounts.invoke(
+        program,
+        DistInstruction::SetHook {
+            hook_program,
+            hook_accounts_len,
+            failure_policy,
+        },
+        signer_seeds,
+    )
+}
+
+pub struct MigrateDistributionAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    /// Pays the rent for the larger account.
+    pub dist_authority: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Invokes `MigrateDistribution`.
+pub fn migrate_distribution<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &MigrateDistributionAccounts<'info>,
+) -> ProgramResult {
+    migrate_distribution_signed(program, accounts, &[])
+}
+
+/// Invokes `MigrateDistribution`, signing with PDAs of the caller.
+pub fn migrate_distribution_signed<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &MigrateDistributionAccounts<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_list = AccountList::new();
+    account_list.writable(&accounts.distribution);
+    account_list.writable_signer(&accounts.dist_authority);
+    account_list.program(&accounts.system_program, &system_program::id())?;
+    account_list.invoke(program, DistInstruction::MigrateDistribution, signer_seeds)
+}
+
+/// Invokes `GetDistributionInfo` and returns its answer. Queries take no
+/// signers, so there is no `_signed` variant.
+pub fn get_distribution_info<'info>(
+    program: &AccountInfo<'info>,
+    distribution: &AccountInfo<'info>,
+) -> Result<DistributionInfo, ProgramError> {
+    let mut account_list = AccountList::new();
+    account_list.readonly(distribution);
+    account_list.invoke(program, DistInstruction::GetDistributionInfo, &[])?;
+
+    DistributionInfo::unpack(&return_data_of(program)?)
+}
+
+pub struct IsRecipientPaidAccounts<'info> {
+    pub distribution: AccountInfo<'info>,
+    pub receipt: AccountInfo<'info>,
+}
+
+/// Invokes `IsRecipientPaid` and returns its answer.
+pub fn is_recipient_paid<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &IsRecipientPaidAccounts<'info>,
+    recipient: Pubkey,
+) -> Result<RecipientPaid, ProgramError> {
+    let mut account_list = AccountList::new();
+    account_list.readonly(&accounts.distribution);
+    account_list.readonly(&accounts.receipt);
+    account_list.invoke(program, DistInstruction::IsRecipientPaid { recipient }, &[])?;
+
+    RecipientPaid::unpack(&return_data_of(program)?)
+}
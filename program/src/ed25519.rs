@@ -0,0 +1,218 @@
+//! Ed25519 signatures verified by the Ed25519 program
+//!
+//! The program can't check Ed25519 signatures itself. Instead the signatures
+//! are verified by an Ed25519 program instruction placed right before the
+//! instruction relying on them, which then reads that instruction back from
+//! the instructions sysvar and checks it covers the expected signers and
+//! messages.
+
+use solana_program::{
+    account_info::AccountInfo,
+    ed25519_program,
+    instruction::Instruction,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+use crate::error::DistError;
+
+/// Length of an Ed25519 signature.
+pub const SIGNATURE_BYTES: usize = 64;
+
+// Ed25519 program instruction data: a signature count, a padding byte, the
+// offsets of each signature and then the data they point to.
+const HEADER_SIZE: usize = 2;
+const OFFSETS_SIZE: usize = 14;
+
+// Instruction index meaning the data is in the Ed25519 instruction itself.
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+const VOUCHER_DOMAIN: &[u8] = b"wave_dist voucher";
+const RELAY_DOMAIN: &[u8] = b"wave_dist relay";
+
+/// The message an attester signs to let `recipient` claim `amount` from a
+/// distribution with `ClaimWithVoucher`, until the `expiry` unix timestamp.
+pub fn voucher_message(
+    dist_account: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    let mut message = VOUCHER_DOMAIN.to_vec();
+    message.extend_from_slice(dist_account.as_ref());
+    message.extend_from_slice(recipient.as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+/// The message a recipient signs to have `relayer` claim its voucher for
+/// it with `RelayClaim`, reimbursing the relayer `relayer_fee` out of the
+/// distribution. No other relayer can submit the claim.
+pub fn relay_message(
+    dist_account: &Pubkey,
+    recipient: &Pubkey,
+    relayer: &Pubkey,
+    nonce: u64,
+    relayer_fee: u64,
+) -> Vec<u8> {
+    let mut message = RELAY_DOMAIN.to_vec();
+    message.extend_from_slice(dist_account.as_ref());
+    message.extend_from_slice(recipient.as_ref());
+    message.extend_from_slice(relayer.as_ref());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&relayer_fee.to_le_bytes());
+    message
+}
+
+/// Creates an Ed25519 program instruction verifying each `(signer,
+/// signature, message)`.
+pub fn new_ed25519_instruction(
+    signed_messages: &[(&Pubkey, &[u8; SIGNATURE_BYTES], &[u8])],
+) -> Instruction {
+    let mut data = vec![signed_messages.len() as u8, 0];
+    let mut data_offset = HEADER_SIZE + OFFSETS_SIZE * signed_messages.len();
+    for (_, _, message) in signed_messages {
+        let pubkey_offset = data_offset;
+        let signature_offset = pubkey_offset + PUBKEY_BYTES;
+        let message_offset = signature_offset + SIGNATURE_BYTES;
+        data_offset = message_offset + message.len();
+
+        for value in &[
+            signature_offset as u16,
+            CURRENT_INSTRUCTION,
+            pubkey_offset as u16,
+            CURRENT_INSTRUCTION,
+            message_offset as u16,
+            message.len() as u16,
+            CURRENT_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    for (signer, signature, message) in signed_messages {
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(*signature);
+        data.extend_from_slice(message);
+    }
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Checks that the instruction before the current one is an Ed25519
+/// program instruction verifying, among others, a signature of `message` by
+/// `signer`.
+pub fn check_signed_message(
+    instructions_sysvar_info: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<(), DistError> {
+    let current_index = load_current_index_checked(instructions_sysvar_info)
+        .map_err(|_| DistError::InvalidSignature)?;
+    if current_index == 0 {
+        return Err(DistError::InvalidSignature);
+    }
+
+    let instruction =
+        load_instruction_at_checked(current_index as usize - 1, instructions_sysvar_info)
+            .map_err(|_| DistError::InvalidSignature)?;
+    if instruction.program_id != ed25519_program::id() {
+        return Err(DistError::InvalidSignature);
+    }
+
+    let num_signatures = instruction.data.first().copied().unwrap_or_default();
+    let verified = (0..num_signatures as usize)
+        .filter_map(|i| parse_signature_offsets(&instruction.data, i))
+        .any(|(pubkey, signed_message)| pubkey == signer.as_ref() && signed_message == message);
+    if !verified {
+        return Err(DistError::InvalidSignature);
+    }
+
+    Ok(())
+}
+
+// Returns the public key and message of the `i`th signature an Ed25519
+// program instruction verifies, if they're held in the instruction itself.
+fn parse_signature_offsets(data: &[u8], i: usize) -> Option<(&[u8], &[u8])> {
+    let start = HEADER_SIZE + OFFSETS_SIZE * i;
+    let offsets: Vec<usize> = data
+        .get(start..start + OFFSETS_SIZE)?
+        .chunks(2)
+        .map(|value| u16::from_le_bytes([value[0], value[1]]) as usize)
+        .collect();
+    let (signature_index, pubkey_offset, pubkey_index) = (offsets[1], offsets[2], offsets[3]);
+    let (message_offset, message_size, message_index) = (offsets[4], offsets[5], offsets[6]);
+
+    // Data taken from other instructions could be anything
+    let current_instruction = CURRENT_INSTRUCTION as usize;
+    if signature_index != current_instruction
+        || pubkey_index != current_instruction
+        || message_index != current_instruction
+    {
+        return None;
+    }
+
+    let pubkey = data.get(pubkey_offset..pubkey_offset + PUBKEY_BYTES)?;
+    let message = data.get(message_offset..message_offset + message_size)?;
+    Some((pubkey, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_signature_offsets() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let instruction = new_ed25519_instruction(&[
+            (&alice, &[1; SIGNATURE_BYTES], b"first"),
+            (&bob, &[2; SIGNATURE_BYTES], b"second message"),
+        ]);
+
+        assert_eq!(
+            parse_signature_offsets(&instruction.data, 0),
+            Some((alice.as_ref(), &b"first"[..]))
+        );
+        assert_eq!(
+            parse_signature_offsets(&instruction.data, 1),
+            Some((bob.as_ref(), &b"second message"[..]))
+        );
+        assert_eq!(parse_signature_offsets(&instruction.data, 2), None);
+    }
+
+    #[test]
+    fn rejects_data_of_other_instructions() {
+        let instruction =
+            new_ed25519_instruction(&[(&Pubkey::new_unique(), &[1; SIGNATURE_BYTES], b"message")]);
+
+        // The signature, public key and message instruction indices
+        for index_offset in [2, 6, 12] {
+            let mut data = instruction.data.clone();
+            let start = HEADER_SIZE + index_offset;
+            data[start..start + 2].copy_from_slice(&0u16.to_le_bytes());
+            assert_eq!(parse_signature_offsets(&data, 0), None);
+        }
+    }
+
+    #[test]
+    fn rejects_offsets_out_of_bounds() {
+        let instruction =
+            new_ed25519_instruction(&[(&Pubkey::new_unique(), &[1; SIGNATURE_BYTES], b"message")]);
+
+        // Cut off the end of the message
+        let data = &instruction.data[..instruction.data.len() - 1];
+        assert_eq!(parse_signature_offsets(data, 0), None);
+
+        // Cut off the offsets themselves
+        assert_eq!(
+            parse_signature_offsets(&instruction.data[..HEADER_SIZE + OFFSETS_SIZE - 1], 0),
+            None
+        );
+    }
+}
@@ -40,6 +40,233 @@ pub enum DistError {
     /// Too many recipients
     #[error("Too many recipients")]
     TooManyRecipients,
+
+    /// Project name is empty
+    #[error("Invalid project name")]
+    InvalidProjectName,
+
+    /// A metadata field is too long
+    #[error("Invalid metadata")]
+    InvalidMetadata,
+
+    /// Config admin doesn't match
+    #[error("Unauthorized config admin")]
+    UnauthorizedConfigAdmin,
+
+    /// The program is paused by the config admin
+    #[error("Program is paused")]
+    ProgramPaused,
+
+    /// The token mint is not on the config allowlist
+    #[error("Token mint is not allowed")]
+    MintNotAllowed,
+
+    /// Protocol fee is above 100%
+    #[error("Invalid protocol fee")]
+    InvalidProtocolFee,
+
+    /// More mints than the allowlist can hold
+    #[error("Too many allowed mints")]
+    TooManyAllowedMints,
+
+    /// Treasury token account doesn't belong to the config treasury
+    #[error("Invalid treasury account")]
+    InvalidTreasuryAccount,
+
+    /// No dist authority has been proposed
+    #[error("No pending dist authority")]
+    NoPendingDistAuthority,
+
+    /// Signer is not the proposed dist authority
+    #[error("Pending dist authority mismatch")]
+    PendingDistAuthorityMismatch,
+
+    /// Recipient token account holds a different mint than the distribution
+    #[error("Recipient token account mint mismatch")]
+    RecipientMintMismatch,
+
+    /// Recipient token account is frozen
+    #[error("Recipient token account is frozen")]
+    RecipientAccountFrozen,
+
+    /// Recipient token account doesn't exist or isn't a token account
+    #[error("Invalid recipient token account")]
+    InvalidRecipientAccount,
+
+    /// Receipt account isn't the one derived for the recipient
+    #[error("Invalid receipt account")]
+    InvalidReceiptAccount,
+
+    /// Recipient wallet has already been paid by the distribution
+    #[error("Recipient has already been paid")]
+    RecipientAlreadyPaid,
+
+    /// Streaming distributions pay out with Drip only
+    #[error("Distribution is streaming")]
+    DistributionIsStreaming,
+
+    /// Drip needs a release rate
+    #[error("Distribution is not streaming")]
+    DistributionNotStreaming,
+
+    /// Distribution hasn't begun yet
+    #[error("Distribution has not started")]
+    DistributionNotStarted,
+
+    /// Recipient wallet is on the denylist
+    #[error("Recipient is denylisted")]
+    RecipientDenylisted,
+
+    /// Denylist entry isn't the one derived for the wallet
+    #[error("Invalid denylist account")]
+    InvalidDenylistAccount,
+
+    /// Funding doesn't match how the distribution was funded before
+    #[error("Funding mode mismatch")]
+    FundingModeMismatch,
+
+    /// Token account isn't the one delegated to the distribution
+    #[error("Funding source mismatch")]
+    FundingSourceMismatch,
+
+    /// Funding source doesn't delegate enough tokens to the distribution
+    #[error("Insufficient delegation")]
+    InsufficientDelegation,
+
+    /// Distribution already holds funds from another funding mode
+    #[error("Distribution is already funded")]
+    DistributionAlreadyFunded,
+
+    /// Mint distributions mint their payouts rather than being funded
+    #[error("Distribution mints its payouts")]
+    DistributionIsMinting,
+
+    /// Distribution account isn't the mint authority of its token
+    #[error("Invalid mint authority")]
+    InvalidMintAuthority,
+
+    /// Account isn't the distribution token mint
+    #[error("Distribution mint mismatch")]
+    DistributionMintMismatch,
+
+    /// Instruction doesn't apply to this kind of distribution
+    #[error("Unsupported distribution kind")]
+    UnsupportedDistributionKind,
+
+    /// Mint isn't an NFT: it must have no decimals, a supply of one and no
+    /// mint or freeze authority
+    #[error("Invalid NFT mint")]
+    InvalidNftMint,
+
+    /// Pool item isn't an NFT held by the distribution
+    #[error("Invalid pool item account")]
+    InvalidPoolItemAccount,
+
+    /// Redemption would pay out more than the cap or the funded amount
+    #[error("Redemption cap reached")]
+    RedemptionCapReached,
+
+    /// Account isn't the distribution's vault for redeemed input tokens
+    #[error("Invalid redemption vault")]
+    InvalidRedemptionVault,
+
+    /// Raffle has more winners than can be drawn, or than entrants
+    #[error("Invalid raffle")]
+    InvalidRaffle,
+
+    /// Raffle has already been drawn
+    #[error("Raffle already drawn")]
+    RaffleAlreadyDrawn,
+
+    /// Raffle has not been drawn yet
+    #[error("Raffle not drawn")]
+    RaffleNotDrawn,
+
+    /// The raffle's draw slot hasn't passed yet
+    #[error("Raffle draw too early")]
+    RaffleDrawTooEarly,
+
+    /// Entrant index isn't among the drawn winners
+    #[error("Entrant is not a raffle winner")]
+    NotRaffleWinner,
+
+    /// Merkle proof doesn't lead to the committed root
+    #[error("Invalid Merkle proof")]
+    InvalidMerkleProof,
+
+    /// Grant account doesn't belong to the distribution and recipient
+    #[error("Invalid grant account")]
+    InvalidGrantAccount,
+
+    /// Grant has already been revoked
+    #[error("Grant revoked")]
+    GrantRevoked,
+
+    /// Forward address account doesn't belong to the distribution and wallet
+    #[error("Invalid forward address account")]
+    InvalidForwardAccount,
+
+    /// Recipient forwards its payouts to a different token account
+    #[error("Recipient forwards to a different token account")]
+    ForwardAddressMismatch,
+
+    /// Gate account isn't a token account of the gate mint owned by the
+    /// recipient
+    #[error("Invalid gate account")]
+    InvalidGateAccount,
+
+    /// Recipient holds less of the gate mint than the distribution requires
+    #[error("Recipient holds too little of the gate token")]
+    InsufficientGateBalance,
+
+    /// Distribution doesn't accept vouchers
+    #[error("Attester not set")]
+    AttesterNotSet,
+
+    /// No Ed25519 program instruction verifying the expected signer and
+    /// message precedes the instruction
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    /// Voucher is past its expiry
+    #[error("Voucher expired")]
+    VoucherExpired,
+
+    /// Voucher nonce has already been claimed by the recipient
+    #[error("Voucher already used")]
+    VoucherAlreadyUsed,
+
+    /// Relayer fee is over the distribution's relay fee cap
+    #[error("Relayer fee too high")]
+    RelayerFeeTooHigh,
+
+    /// Current round has been settled and pays out no more
+    #[error("Distribution settled")]
+    DistributionSettled,
+
+    /// Recipient hasn't been paid in the current round
+    #[error("Recipient not paid")]
+    RecipientNotPaid,
+
+    /// Hook program account doesn't match the distribution's payout hook
+    #[error("Invalid hook program")]
+    InvalidHookProgram,
+
+    /// Payout hook program can't be called, or the payout instruction
+    /// doesn't call it
+    #[error("Hook unavailable")]
+    HookUnavailable,
+
+    /// Token account isn't the distribution's associated token account
+    #[error("Distribution token account mismatch")]
+    DistTokenAccountMismatch,
+
+    /// Recipient has already been paid the settle bonus of the round
+    #[error("Settle bonus already paid")]
+    SettleBonusAlreadyPaid,
+    /// Distribution would pay out both equal shares and vouchers
+    #[error("Shares and vouchers can't be mixed")]
+    SharesAndVouchersMixed,
 }
 
 impl From<DistError> for ProgramError {
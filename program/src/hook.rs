@@ -0,0 +1,93 @@
+//! Payout hooks
+//!
+//! A distribution may name a hook program, set with `SetHook`, that
+//! `Distribute` and `DistributeToWallets` call after paying each recipient,
+//! so that another program can act on the payout, for instance by crediting
+//! loyalty points. Other payout instructions fail while a hook is set.
+//!
+//! The hook is called with a fixed instruction layout. Its data is
+//! [`PAYOUT_HOOK_TAG`] followed by the distribution account, the recipient
+//! wallet and the amount paid, as a little-endian `u64`. Its accounts are:
+//!
+//!   0. `[signer]` Hook authority of the distribution, signing so the hook
+//!      can tell the call comes from this program. It is derived from
+//!      [`HOOK_AUTHORITY_SEED`] and the distribution account, and owns
+//!      nothing, so the hook gains no say over the distribution's tokens.
+//!   1. `[]` Distribution account.
+//!   2. `[]` Recipient token account that was paid.
+//!   3. ..3+N The N extra accounts passed with the recipient to the payout
+//!      instruction, with the same privileges.
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+/// Seed of the hook authority address of a distribution.
+pub const HOOK_AUTHORITY_SEED: &[u8] = b"hook";
+
+/// Leads the data of every hook call, so that the hook program can tell it
+/// apart from its own instructions.
+pub const PAYOUT_HOOK_TAG: [u8; 8] = *b"wdpayout";
+
+/// Length of the data of a hook call.
+pub const PAYOUT_HOOK_LEN: usize = PAYOUT_HOOK_TAG.len() + PUBKEY_BYTES + PUBKEY_BYTES + 8;
+
+/// The payout a hook is called about.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PayoutHook {
+    pub distribution: Pubkey,
+    /// The recipient wallet, which is the owner of the paid token account
+    /// unless that is its forward address.
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+impl PayoutHook {
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(PAYOUT_HOOK_LEN);
+        buf.extend_from_slice(&PAYOUT_HOOK_TAG);
+        buf.extend_from_slice(self.distribution.as_ref());
+        buf.extend_from_slice(self.recipient.as_ref());
+        buf.extend_from_slice(&self.amount.to_le_bytes());
+        buf
+    }
+
+    /// Unpacks the data of a hook call, for use by hook programs.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != PAYOUT_HOOK_LEN || input[..PAYOUT_HOOK_TAG.len()] != PAYOUT_HOOK_TAG {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let (distribution, rest) = input[PAYOUT_HOOK_TAG.len()..].split_at(PUBKEY_BYTES);
+        let (recipient, amount) = rest.split_at(PUBKEY_BYTES);
+
+        Ok(Self {
+            distribution: Pubkey::new(distribution),
+            recipient: Pubkey::new(recipient),
+            amount: u64::from_le_bytes(amount.try_into().unwrap()),
+        })
+    }
+}
+
+/// Creates the instruction calling a hook program about a payout.
+pub fn payout_hook(
+    hook_program: &Pubkey,
+    hook_authority: &Pubkey,
+    recipient_token_account: &Pubkey,
+    payout: &PayoutHook,
+    extra_accounts: &[AccountMeta],
+) -> Instruction {
+    let mut accounts = Vec::with_capacity(3 + extra_accounts.len());
+    accounts.push(AccountMeta::new_readonly(*hook_authority, true));
+    accounts.push(AccountMeta::new_readonly(payout.distribution, false));
+    accounts.push(AccountMeta::new_readonly(*recipient_token_account, false));
+    accounts.extend_from_slice(extra_accounts);
+
+    Instruction {
+        program_id: *hook_program,
+        accounts,
+        data: payout.pack(),
+    }
+}
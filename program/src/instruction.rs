@@ -3,14 +3,24 @@
 use std::mem::size_of;
 
 use solana_program::{
+    bpf_loader_upgradeable,
+    hash::{Hash, HASH_BYTES},
     instruction::{AccountMeta, Instruction},
     msg,
     program_error::ProgramError,
     pubkey::{Pubkey, PUBKEY_BYTES},
-    system_program,
+    system_program, sysvar,
 };
 
-use crate::error::DistError;
+use crate::{error::DistError, state::HookFailurePolicy};
+
+/// Commitment to the entrants of a raffle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaffleCommit {
+    /// Merkle root of the entrants, see [merkle](../merkle/index.html)
+    pub entrants_root: Hash,
+    pub num_entrants: u32,
+}
 
 /// Instructions supported by the token program.
 #[repr(C)]
@@ -20,18 +30,24 @@ pub enum DistInstruction {
     ///
     /// Initializes a new distribution.
     ///
+    /// The distribution address is derived from the seed, the project name
+    /// and the funding account, which is recorded as the creator.
+    ///
     /// Accounts expected by this instruction:
     ///
-    ///   0. `[signer]` The funding account.
+    ///   0. `[signer]` The funding account and distribution creator.
     ///   1. `[]` The system program ID.
     ///   2. `[]` Token program ID.
     ///   3. `[]` Token address.
     ///   4. `[writable]` The distribution account.
-    ///   4. `[writable]` The project name.
+    ///   5. `[]` The program config account.
     ///
     InitializeDistribution {
         seed: Pubkey,
-        project_name:Pubkey,
+        // The UTF-8 project name. Its SHA-256 hash is part of the address
+        // seeds and the name itself is kept in the distribution state, cut
+        // to its first `MAX_PROJECT_NAME_LEN` bytes.
+        project_name: String,
         seed_bump: u8,
         // The maximum number of recipients for the distribution.
         // Affects space allocation which is necessary to avoid
@@ -43,28 +59,46 @@ pub enum DistInstruction {
 
     /// Index: 1
     ///
-    /// Initializes a new distribution.
+    /// Funds a distribution with `amount` tokens. The protocol fee set in the
+    /// program config is taken out of `amount` and sent to the treasury; the
+    /// rest is added to the funded amount. Fails while the program is paused.
     ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[signer]` The token source account.
     ///   1. `[writable]` The token source account's token account.
     ///   2. `[writable]` The distribution account.
-    ///   3. `[writable]` The distribution account's token account.
+    ///   3. `[writable]` The distribution's associated token account.
     ///   4. `[]` Token program ID.
+    ///   5. `[]` The program config account, which holds the pause flag, the
+    ///      protocol fee and the treasury wallet.
+    ///   6. `[writable]` The treasury token account, owned by the config
+    ///      treasury and holding the distribution token. Only required when
+    ///      the protocol fee on `amount` is not zero.
+    ///
+    /// When `delegated` is set the tokens stay in the source token account,
+    /// which must approve the distribution account as delegate for the funded
+    /// amount. The distribution records the source token account and pays
+    /// recipients straight out of it; the distribution token account is
+    /// ignored. A distribution can't mix both funding modes.
     ///
-    FundDistribution { amount: u64 },
+    FundDistribution {
+        amount: u64,
+        /// Fund by delegation rather than transfer
+        delegated: bool,
+    },
 
     /// Index: 2
     ///
-    /// Sets a new owner for the distribution.
+    /// Proposes a new owner for the distribution. The current dist authority
+    /// stays in charge until the proposed one accepts.
     ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[writable]` Distribution account.
     ///   1. `[signer]` The current dist authority.
     ///
-    SetDistAuthority { new_dist_authority: Pubkey },
+    ProposeDistAuthority { new_dist_authority: Pubkey },
 
     /// Index: 3
     ///
@@ -75,8 +109,19 @@ pub enum DistInstruction {
     ///
     ///   0. `[writable]` Distribution account.
     ///   1. `[signer]` The dist authority.
+    ///   2. `[]` The funding source token account. Only required when the
+    ///      distribution is funded by delegation, in which case it must still
+    ///      delegate the unpaid funded amount.
+    ///
+    /// With a raffle commitment the distribution becomes a raffle instead,
+    /// where `num_recipients` is the number of winners to draw from the
+    /// committed entrants. Winners are drawn with `Draw` and paid with
+    /// `ClaimRafflePrize`.
     ///
-    BeginDistribution { num_recipients: u16 },
+    BeginDistribution {
+        num_recipients: u16,
+        raffle: Option<RaffleCommit>,
+    },
 
     /// Index: 4
     ///
@@ -84,200 +129,2418 @@ pub enum DistInstruction {
     ///
     /// This instruction is called as many times as necessary to reach the
     /// total number of recipients. Only one distribution per recipient is
-    /// allowed.
+    /// allowed, which is tracked by a receipt account per recipient wallet.
+    /// Every recipient must be an unfrozen token account of the distribution
+    /// mint that hasn't been paid yet and whose owner isn't denylisted;
+    /// otherwise nothing is transferred.
+    ///
+    /// In best-effort mode ineligible recipients are skipped instead, and a
+    /// `Skip: index=<I> account=<ADDRESS> reason=<ERROR>` line is logged for
+    /// each of them.
+    ///
+    /// The recipient wallet is the token account's owner, unless the token
+    /// account is the forward address some wallet set with
+    /// `SetForwardAddress`. A wallet that has set a forward address is only
+    /// paid to that token account.
+    ///
+    /// For an NFT batch distribution each recipient names the pool item it
+    /// gets instead of a forward address, and must be a token account of
+    /// that item's mint. The emptied pool item is closed and its rent
+    /// returned to the payer.
+    ///
+    /// A distribution with a payout hook calls it after paying each
+    /// recipient, as described in [hook](../hook/index.html), passing it the
+    /// extra accounts given with the recipient.
     ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[writable]` Distribution account.
     ///   1. `[signer]` The dist authority.
     ///   2. `[]` Token program ID.
-    ///   3. `[writable]` Distribution token account.
-    ///   4. ..4+M `[writable]` M recipients.
+    ///   3. `[writable]` Distribution token account, the funding source
+    ///      token account when the distribution is funded by delegation, or
+    ///      the distribution token mint for a mint distribution. Unused for
+    ///      an NFT batch distribution.
+    ///   4. `[]` The program config account.
+    ///   5. `[writable, signer]` The account paying for receipt accounts.
+    ///   6. `[]` The system program ID.
+    ///   7. `[]` The payout hook program, only when the distribution has one.
+    ///   8. `[]` The hook authority of the distribution, only when it has a
+    ///      payout hook.
+    ///   9. ..9+4M M groups of:
+    ///      * `[writable]` Recipient token account.
+    ///      * `[writable]` Receipt account of the recipient wallet.
+    ///      * `[]` Denylist entry of the recipient wallet.
+    ///      * `[]` Forward address account of the recipient wallet, or
+    ///        `[writable]` the pool item token account for an NFT batch
+    ///        distribution.
+    ///      * `[]` Gate token account of the recipient wallet, only when the
+    ///        distribution is gated.
+    ///      * The extra accounts passed to the payout hook, as many as the
+    ///        hook takes, only when the distribution has one.
     ///
-    Distribute,
-}
-
-impl DistInstruction {
-    /// Unpacks a byte buffer into a [DistInstruction](enum.DistInstruction.html).
-    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        use DistError::InvalidInstruction;
+    /// Without a payout hook the recipient groups start at 7.
+    ///
+    Distribute {
+        /// Skip ineligible recipients rather than fail
+        best_effort: bool,
+    },
 
-        let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
+    /// Index: 5
+    ///
+    /// Creates or updates the metadata account of a distribution.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///   2. `[writable, signer]` The funding account.
+    ///   3. `[writable]` The metadata account.
+    ///   4. `[]` The system program ID.
+    ///
+    SetMetadata {
+        description: String,
+        uri: String,
+        creator_label: String,
+        category: String,
+    },
 
-        Ok(match tag {
-            0 => {
-                let (seed, rest) = Self::unpack_pubkey(rest)?;
-                // strip the project name pubkey from 
-                let (project_name, rest) = Self::unpack_pubkey(rest)?;
-                let (seed_bump, rest) = rest.split_at(1);
-                let seed_bump = seed_bump[0];
+    /// Index: 6
+    ///
+    /// Initializes the program config. Only the upgrade authority of the
+    /// program may do this.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The program upgrade authority.
+    ///   1. `[writable]` The program config account.
+    ///   2. `[]` The program data account of this program.
+    ///   3. `[]` The system program ID.
+    ///
+    InitializeConfig { admin: Pubkey },
 
-                let (max_recipients, rest) = rest.split_at(2);
+    /// Index: 7
+    ///
+    /// Updates the program config.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The program config account.
+    ///   1. `[signer]` The config admin.
+    ///
+    UpdateConfig {
+        // Stops new distributions, funding and payouts.
+        paused: bool,
+        protocol_fee_bps: u16,
+        treasury: Pubkey,
+    },
 
-                let max_recipients = max_recipients
-                    .try_into()
-                    .ok()
-                    .map(u16::from_le_bytes)
-                    .ok_or(InvalidInstruction)?;
+    /// Index: 8
+    ///
+    /// Replaces the mints that may be distributed. An empty list allows
+    /// every mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The program config account.
+    ///   1. `[signer]` The config admin.
+    ///
+    SetAllowedMints { mints: Vec<Pubkey> },
 
-                let (dist_authority, _rest) = Self::unpack_pubkey(rest)?;
+    /// Index: 9
+    ///
+    /// Makes the proposed dist authority the owner of the distribution.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The proposed dist authority.
+    ///
+    AcceptDistAuthority,
 
-                Self::InitializeDistribution {
-                    seed,
-                    project_name,
-                    seed_bump,
-                    max_recipients,
-                    dist_authority,
-                }
-            }
-            1 => {
-                let (amount, _rest) = rest.split_at(8);
+    /// Index: 10
+    ///
+    /// Withdraws a dist authority proposal.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The current dist authority.
+    ///
+    CancelDistAuthority,
 
-                let amount = amount
-                    .try_into()
-                    .ok()
-                    .map(u64::from_le_bytes)
-                    .ok_or(InvalidInstruction)?;
+    /// Index: 11
+    ///
+    /// Performs distribution to the provided recipient wallets, creating
+    /// their associated token accounts first where they don't exist yet.
+    /// Recipients are checked and skipped like in `Distribute`. A wallet
+    /// that has set a forward address is paid to that token account
+    /// instead, which must be given in place of its associated token
+    /// account. Not supported by NFT batch distributions.
+    ///
+    /// A distribution with a payout hook calls it after paying each
+    /// recipient, like `Distribute` does.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///   2. `[]` Token program ID.
+    ///   3. `[writable]` Distribution token account, the funding source
+    ///      token account when the distribution is funded by delegation, or
+    ///      the distribution token mint for a mint distribution.
+    ///   4. `[]` The program config account.
+    ///   5. `[writable, signer]` The account paying for new token and receipt
+    ///      accounts.
+    ///   6. `[]` Token address.
+    ///   7. `[]` The system program ID.
+    ///   8. `[]` The associated token account program ID.
+    ///   9. `[]` The payout hook program, only when the distribution has one.
+    ///   10. `[]` The hook authority of the distribution, only when it has a
+    ///       payout hook.
+    ///   11. ..11+5M M groups of:
+    ///       * `[]` Recipient wallet.
+    ///       * `[writable]` The recipient wallet's associated token account,
+    ///         or its forward address.
+    ///       * `[writable]` The recipient wallet's receipt account.
+    ///       * `[]` The recipient wallet's denylist entry.
+    ///       * `[]` The recipient wallet's forward address account.
+    ///       * `[]` Gate token account of the recipient wallet, only when the
+    ///         distribution is gated.
+    ///       * The extra accounts passed to the payout hook, as many as the
+    ///         hook takes, only when the distribution has one.
+    ///
+    DistributeToWallets {
+        /// Skip ineligible recipients rather than fail
+        best_effort: bool,
+    },
 
-                Self::FundDistribution { amount }
-            }
-            2 => {
-                let (new_dist_authority, _rest) = Self::unpack_pubkey(rest)?;
+    /// Index: 12
+    ///
+    /// Turns a distribution into a stream that releases a fixed amount per
+    /// epoch, counted from the epoch the distribution begins. Can only be
+    /// set before the distribution begins. A rate of zero makes it a
+    /// one-shot distribution again.
+    ///
+    /// The wallets the stream may drip to are committed as the merkle root
+    /// of their `merkle::recipient_leaf`, which is required with a non-zero
+    /// rate.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///
+    SetReleaseRate {
+        /// Tokens released to all recipients together per epoch
+        release_rate: u64,
+        recipients_root: Hash,
+    },
 
-                Self::SetDistAuthority { new_dist_authority }
-            }
-            3 => {
-                let (num_recipients, _rest) = rest.split_at(2);
+    /// Index: 13
+    ///
+    /// Pays the provided recipients of a streaming distribution what has
+    /// been released to them but not paid yet. The amount paid so far is
+    /// kept in each recipient's receipt account, which is created on the
+    /// first drip. May be repeated every epoch. Not supported by NFT batch
+    /// distributions.
+    ///
+    /// Each recipient wallet is proven against the recipients root set with
+    /// `SetReleaseRate`. A wallet with a forward address is paid to that
+    /// token account instead.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///   2. `[]` Token program ID.
+    ///   3. `[writable]` Distribution token account, the funding source
+    ///      token account when the distribution is funded by delegation, or
+    ///      the distribution token mint for a mint distribution.
+    ///   4. `[]` The program config account.
+    ///   5. `[writable, signer]` The account paying for receipt accounts.
+    ///   6. `[]` The system program ID.
+    ///   7. ..7+4M M groups of:
+    ///      * `[writable]` Recipient token account, or the forward address
+    ///        of the recipient wallet.
+    ///      * `[writable]` Receipt account of the recipient wallet.
+    ///      * `[]` Denylist entry of the recipient wallet.
+    ///      * `[]` Forward address account of the recipient wallet.
+    ///      * `[]` Gate token account of the recipient wallet, only when the
+    ///        distribution is gated.
+    ///
+    Drip {
+        /// Merkle proof of each recipient wallet, in the order of the
+        /// account groups
+        proofs: Vec<Vec<Hash>>,
+    },
 
-                let num_recipients = num_recipients
-                    .try_into()
-                    .ok()
-                    .map(u16::from_le_bytes)
-                    .ok_or(InvalidInstruction)?;
+    /// Index: 14
+    ///
+    /// Closes the current round of a distribution and opens the next one.
+    /// The totals of the closed round are written to a summary account.
+    /// The new round is begun, funded and paid out like a fresh
+    /// distribution; what wasn't paid out stays funded, and every recipient
+    /// can be paid again.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///   2. `[writable, signer]` The funding account.
+    ///   3. `[writable]` The summary account of the closing round.
+    ///   4. `[]` The system program ID.
+    ///
+    StartRound,
 
-                Self::BeginDistribution { num_recipients }
-            }
-            4 => Self::Distribute,
-            _ => {
-                return Err(InvalidInstruction.into());
-            }
-        })
-    }
+    /// Index: 15
+    ///
+    /// Puts wallets on the program-wide denylist, which no distribution may
+    /// pay. Wallets that are already denylisted are left as they are.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The program config account.
+    ///   1. `[signer]` The config admin.
+    ///   2. `[writable, signer]` The funding account.
+    ///   3. `[]` The system program ID.
+    ///   4. ..4+M `[writable]` The denylist entry of each of the M wallets.
+    ///
+    AddToDenylist {
+        /// The wallets to denylist
+        wallets: Vec<Pubkey>,
+    },
 
-    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
-        if input.len() >= PUBKEY_BYTES {
-            let (key, rest) = input.split_at(PUBKEY_BYTES);
-            let pk = Pubkey::new(key);
-            Ok((pk, rest))
-        } else {
-            Err(DistError::InvalidInstruction.into())
-        }
-    }
+    /// Index: 16
+    ///
+    /// Takes wallets off the program-wide denylist and closes their entries.
+    /// Wallets that aren't denylisted are left as they are.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The program config account.
+    ///   1. `[signer]` The config admin.
+    ///   2. `[writable]` The account receiving the entries' rent.
+    ///   3. ..3+M `[writable]` The denylist entry of each of the M wallets.
+    ///
+    RemoveFromDenylist {
+        /// The wallets to take off the denylist
+        wallets: Vec<Pubkey>,
+    },
 
-    /// Packs a [DistInstruction](enum.DistInstruction.html) into a byte buffer.
-    pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(size_of::<Self>());
-        match self {
-            &Self::InitializeDistribution {
-                ref seed,
-                ref project_name,
-                seed_bump,
-                max_recipients,
-                ref dist_authority,
-            } => {
-                buf.push(0);
-                buf.extend_from_slice(seed.as_ref());
-                buf.extend_from_slice(project_name.as_ref());
-                buf.push(seed_bump);
-                buf.extend_from_slice(&max_recipients.to_le_bytes());
-                buf.extend_from_slice(dist_authority.as_ref());
-            }
-            &Self::FundDistribution { amount } => {
-                buf.push(1);
-                buf.extend_from_slice(&amount.to_le_bytes());
-            }
-            &Self::SetDistAuthority {
-                ref new_dist_authority,
-            } => {
-                buf.push(2);
-                buf.extend_from_slice(new_dist_authority.as_ref());
-            }
-            &Self::BeginDistribution { num_recipients } => {
-                buf.push(3);
-                buf.extend_from_slice(&num_recipients.to_le_bytes());
-            }
-            &Self::Distribute => {
-                buf.push(4);
-            }
-        }
-        buf
-    }
-}
+    /// Index: 17
+    ///
+    /// Turns an unfunded distribution into a mint distribution, which mints
+    /// each recipient share instead of paying it out of funded tokens. The
+    /// distribution account must already be the mint authority of the
+    /// distribution token. Mint distributions aren't funded; the supply cap
+    /// is what gets shared between the recipients.
+    ///
+    /// Once set, the distribution token mint takes the place of the
+    /// distribution token account in `Distribute`, `DistributeToWallets` and
+    /// `Drip`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///   2. `[]` The distribution token mint.
+    ///
+    SetMintDistribution {
+        /// Most the distribution may ever mint, in base units
+        supply_cap: u64,
+    },
 
-pub fn init_distribution(
-    program_id: &Pubkey,
-    token: &Pubkey,
-    dist_account: &Pubkey,
-    fee_payer_account: &Pubkey,
-    seed: &Pubkey,
-    project_name: &Pubkey,
-    seed_bump: u8,
-    max_recipients: u16,
-    dist_authority_account: &Pubkey,
-) -> Instruction {
-    let data = DistInstruction::InitializeDistribution {
-        seed: *seed,
-        project_name: *project_name,
-        seed_bump,
-        max_recipients,
-        dist_authority: *dist_authority_account,
-    }
-    .pack();
+    /// Index: 18
+    ///
+    /// Deposits NFTs into the pool of an NFT batch distribution, moving each
+    /// one into a pool item token account owned by the distribution. The
+    /// first deposit turns an unfunded distribution into an NFT batch
+    /// distribution. Each deposited NFT counts as one funded unit, and the
+    /// distribution can't begin with more recipients than NFTs. Only the dist
+    /// authority may deposit, and only NFTs whose mint has no decimals, a
+    /// supply of one and neither a mint nor a freeze authority.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///   2. `[signer]` The owner of the deposited NFTs.
+    ///   3. `[]` Token program ID.
+    ///   4. `[writable, signer]` The account paying for pool item accounts.
+    ///   5. `[]` The system program ID.
+    ///   6. `[]` The rent sysvar.
+    ///   7. ..7+3M M triples of:
+    ///      * `[]` NFT mint.
+    ///      * `[writable]` The owner's token account holding the NFT.
+    ///      * `[writable]` Pool item token account of the mint.
+    ///
+    DepositNfts,
 
-    Instruction {
-        program_id: *program_id,
-        accounts: vec![
-            AccountMeta::new(*fee_payer_account, true),
-            AccountMeta::new_readonly(system_program::id(), false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-            AccountMeta::new_readonly(*token, false),
-            AccountMeta::new(*dist_account, false),
-        ],
-        data,
-    }
-}
+    /// Index: 19
+    ///
+    /// Turns a distribution into a redemption distribution, or updates its
+    /// terms before anything has been redeemed. Redeemers give up input
+    /// tokens for `rate_numerator / rate_denominator` funded tokens each,
+    /// until `cap` funded tokens have been paid out. Redemption
+    /// distributions are funded with `FundDistribution` like any other, but
+    /// pay out through `Redeem` only. The cap can't be zero.
+    ///
+    /// Unless input tokens are burned, the input vault, the distribution's
+    /// associated token account of the input mint, is created if missing.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///   2. `[]` Input mint.
+    ///   3. `[writable]` Input vault.
+    ///   4. `[writable, signer]` The account paying for the input vault.
+    ///   5. `[]` The system program ID.
+    ///   6. `[]` Token program ID.
+    ///   7. `[]` Associated token program ID.
+    ///
+    SetRedemption {
+        /// The token taken from redeemers
+        input_mint: Pubkey,
+        /// Funded token base units paid per `rate_denominator` input base units
+        rate_numerator: u64,
+        rate_denominator: u64,
+        /// Most funded tokens paid to redeemers, in base units
+        cap: u64,
+        /// Burn redeemed input tokens rather than keep them in the vault
+        burn_input: bool,
+    },
 
-pub fn fund_distribution(
-    program_id: &Pubkey,
-    source_account: &Pubkey,
-    source_token_account: &Pubkey,
-    dist_account: &Pubkey,
-    dist_token_account: &Pubkey,
-    amount: u64,
-) -> Instruction {
-    let data = DistInstruction::FundDistribution { amount }.pack();
+    /// Index: 20
+    ///
+    /// Redeems `amount` input tokens for funded tokens at the rate of a
+    /// redemption distribution. The input tokens are burned or moved to the
+    /// distribution's input vault, the associated token account of the
+    /// distribution account for the input mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The redeemer.
+    ///   2. `[]` Token program ID.
+    ///   3. `[writable]` Distribution token account, or the funding source
+    ///      token account when the distribution is funded by delegation.
+    ///   4. `[]` The program config account.
+    ///   5. `[writable]` The input mint.
+    ///   6. `[writable]` The redeemer's input token account.
+    ///   7. `[writable]` The distribution's input vault. Unused when input
+    ///      tokens are burned.
+    ///   8. `[writable]` The token account receiving the funded tokens.
+    ///   9. `[]` Denylist entry of the receiving token account's owner.
+    ///
+    Redeem { amount: u64 },
 
-    Instruction {
-        program_id: *program_id,
-        accounts: vec![
-            AccountMeta::new_readonly(*source_account, true),
-            AccountMeta::new(*source_token_account, false),
-            AccountMeta::new(*dist_account, false),
-            AccountMeta::new(*dist_token_account, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-        ],
-        data,
-    }
-}
+    /// Index: 21
+    ///
+    /// Draws the winners of a raffle from the hash of its draw slot,
+    /// `RAFFLE_DRAW_DELAY_SLOTS` after the raffle was committed. Anyone may
+    /// draw once that slot has passed. The winners are recorded in the
+    /// distribution as entrant indices.
+    ///
+    /// If the draw slot was skipped or its hash is no longer in the slot
+    /// hashes sysvar, nothing is drawn and the draw slot moves to
+    /// `RAFFLE_DRAW_DELAY_SLOTS` after the current slot instead.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[]` The slot hashes sysvar.
+    ///
+    Draw,
 
-pub fn set_dist_authority(
-    program_id: &Pubkey,
-    dist_account: &Pubkey,
+    /// Index: 22
+    ///
+    /// Pays a raffle winner its share of the funded amount. The winner's
+    /// entry is proven against the committed entrants root. Anyone may
+    /// claim for a winner, as the prize only goes to a token account owned
+    /// by the winning wallet, or to its forward address if it set one. The
+    /// prize is recorded in the winning wallet's receipt account, as for
+    /// `Distribute`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[]` Token program ID.
+    ///   2. `[writable]` Distribution token account, or the funding source
+    ///      token account when the distribution is funded by delegation.
+    ///   3. `[]` The program config account.
+    ///   4. `[writable]` Winner token account.
+    ///   5. `[]` Denylist entry of the winning wallet.
+    ///   6. `[]` Forward address account of the winning wallet.
+    ///   7. `[writable]` Receipt account of the winning wallet.
+    ///   8. `[writable, signer]` The account paying for the receipt account.
+    ///   9. `[]` System program.
+    ///   10. `[]` Gate token account of the winning wallet, only when the
+    ///       distribution is gated.
+    ///
+    ClaimRafflePrize {
+        /// The winner's entrant index
+        index: u32,
+        /// Merkle proof of the winner's entry
+        proof: Vec<Hash>,
+    },
+
+    /// Index: 23
+    ///
+    /// Grants tokens to a recipient, moving them from the dist authority's
+    /// token account into a grant vault owned by the distribution. The grant
+    /// vests linearly from `start_epoch` to `end_epoch`. A recipient can only
+    /// hold one grant per distribution.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Distribution account.
+    ///   1. `[signer]` Dist authority account.
+    ///   2. `[writable]` The dist authority's token account funding the grant.
+    ///   3. `[writable]` Grant account of the recipient.
+    ///   4. `[writable]` Grant vault token account of the recipient.
+    ///   5. `[]` The distribution token mint.
+    ///   6. `[writable, signer]` The account paying for the grant accounts.
+    ///   7. `[]` The system program ID.
+    ///   8. `[]` Token program ID.
+    ///   9. `[]` The rent sysvar.
+    ///
+    CreateGrant {
+        recipient: Pubkey,
+        amount: u64,
+        start_epoch: u64,
+        end_epoch: u64,
+    },
+
+    /// Index: 24
+    ///
+    /// Releases what has vested of a grant to the recipient. Anyone may
+    /// release, as the tokens only go to a token account owned by the
+    /// recipient.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Distribution account.
+    ///   1. `[writable]` Grant account.
+    ///   2. `[writable]` Grant vault token account.
+    ///   3. `[writable]` Recipient token account.
+    ///   4. `[]` Token program ID.
+    ///   5. `[]` The program config account.
+    ///   6. `[]` Denylist entry of the recipient.
+    ///
+    ReleaseGrant,
+
+    /// Index: 25
+    ///
+    /// Revokes a grant, returning its unvested part to the given token
+    /// account. What has vested stays in the vault for the recipient to
+    /// release.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Distribution account.
+    ///   1. `[signer]` Dist authority account.
+    ///   2. `[writable]` Grant account.
+    ///   3. `[writable]` Grant vault token account.
+    ///   4. `[writable]` Token account receiving the unvested tokens.
+    ///   5. `[]` Token program ID.
+    ///
+    RevokeGrant,
+
+    /// Index: 26
+    ///
+    /// Sets the token account a recipient wallet's payouts from a
+    /// distribution are sent to, replacing any earlier one. `Distribute`,
+    /// `DistributeToWallets`, `Drip` and `ClaimRafflePrize` only pay the
+    /// wallet to that token account from then on. Not supported by NFT batch
+    /// distributions.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Distribution account.
+    ///   1. `[signer]` The recipient wallet.
+    ///   2. `[writable]` Forward address account of the wallet.
+    ///   3. `[]` Destination token account, holding the distribution mint.
+    ///   4. `[writable, signer]` The account paying for the forward address
+    ///      account.
+    ///   5. `[]` The system program ID.
+    ///
+    SetForwardAddress,
+
+    /// Index: 27
+    ///
+    /// Gates a distribution, so that it only pays recipient wallets holding
+    /// at least `min_balance` of `gate_mint` at the time of payment. The
+    /// default pubkey as gate mint removes the gate.
+    ///
+    /// A gated distribution takes the gate token account of each recipient
+    /// wallet as an extra account in `Distribute`, `DistributeToWallets`,
+    /// `Drip` and `ClaimRafflePrize`. Recipients failing the gate are
+    /// skipped in best-effort mode.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///
+    SetGate {
+        gate_mint: Pubkey,
+        /// Least the recipients must hold, in base units of the gate mint
+        min_balance: u64,
+    },
+
+    /// Index: 28
+    ///
+    /// Sets the attester whose signed vouchers recipients may claim with
+    /// `ClaimWithVoucher`. The default pubkey stops vouchers from being
+    /// accepted, and is rejected once vouchers have paid out of the round.
+    ///
+    /// A distribution pays out either vouchers or equal shares, so the
+    /// attester can't be set once `BeginDistribution` has set recipients,
+    /// and `Distribute`, `DistributeToWallets` and `Drip` fail while an
+    /// attester is set.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///
+    SetAttester { attester: Pubkey },
+
+    /// Index: 29
+    ///
+    /// Pays a recipient the amount of a voucher signed by the distribution's
+    /// attester. The instruction right before this one must be an Ed25519
+    /// program instruction verifying the attester's signature of the
+    /// [voucher message](../ed25519/fn.voucher_message.html). Each nonce can
+    /// only be claimed once per recipient wallet, and not after the voucher's
+    /// expiry. Anyone may claim, as the voucher only pays out to a token
+    /// account owned by the recipient wallet, or to its forward address if it
+    /// set one.
+    ///
+    /// Only escrow and mint distributions accept vouchers, and the amounts
+    /// claimed count against the unpaid funded amount.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[]` Token program ID.
+    ///   2. `[writable]` Distribution token account, the funding source
+    ///      token account when the distribution is funded by delegation, or
+    ///      the distribution token mint for a mint distribution.
+    ///   3. `[]` The program config account.
+    ///   4. `[writable]` Recipient token account.
+    ///   5. `[]` Denylist entry of the recipient wallet.
+    ///   6. `[]` Forward address account of the recipient wallet.
+    ///   7. `[writable]` Used voucher account of the recipient wallet and
+    ///      nonce.
+    ///   8. `[writable, signer]` The account paying for the used voucher
+    ///      account.
+    ///   9. `[]` The system program ID.
+    ///   10. `[]` The instructions sysvar.
+    ///   11. `[]` Gate token account of the recipient wallet, only when the
+    ///       distribution is gated.
+    ///
+    ClaimWithVoucher {
+        amount: u64,
+        nonce: u64,
+        /// Unix timestamp after which the voucher can't be claimed
+        expiry: i64,
+    },
+
+    /// Index: 30
+    ///
+    /// Sets the most a relayer is reimbursed out of the distribution for
+    /// each claim it relays with `RelayClaim`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///
+    SetRelayFeeCap { relay_fee_cap: u64 },
+
+    /// Index: 31
+    ///
+    /// Claims a voucher on behalf of its recipient, so that a recipient
+    /// holding no SOL can be paid. The relayer pays the fees and rent, and
+    /// is reimbursed `relayer_fee` out of the distribution on top of the
+    /// voucher amount, up to the distribution's relay fee cap.
+    ///
+    /// The Ed25519 program instruction right before this one must verify
+    /// both the attester's signature of the voucher, as for
+    /// `ClaimWithVoucher`, and the recipient wallet's signature of the
+    /// [relay message](../ed25519/fn.relay_message.html) naming the relayer.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[]` Token program ID.
+    ///   2. `[writable]` Distribution token account, the funding source
+    ///      token account when the distribution is funded by delegation, or
+    ///      the distribution token mint for a mint distribution.
+    ///   3. `[]` The program config account.
+    ///   4. `[writable]` Recipient token account.
+    ///   5. `[]` Denylist entry of the recipient wallet.
+    ///   6. `[]` Forward address account of the recipient wallet.
+    ///   7. `[writable]` Used voucher account of the recipient wallet and
+    ///      nonce.
+    ///   8. `[writable, signer]` The relayer, paying for the used voucher
+    ///      account.
+    ///   9. `[]` The system program ID.
+    ///   10. `[]` The instructions sysvar.
+    ///   11. `[writable]` Token account reimbursing the relayer.
+    ///   12. `[]` Gate token account of the recipient wallet, only when the
+    ///       distribution is gated.
+    ///
+    RelayClaim {
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+        relayer_fee: u64,
+    },
+
+    /// Index: 32
+    ///
+    /// Makes recipient shares decay the longer they go unpaid. For every
+    /// epoch past `grace_epochs` epochs after the distribution began, a
+    /// share loses `bps_per_epoch` basis points of itself, down to nothing.
+    /// `Distribute` and `DistributeToWallets` pay the decayed share and
+    /// leave the forfeited rest to be split among paid recipients by
+    /// `Settle`. Zero basis points removes the decay.
+    ///
+    /// Not supported by streaming and NFT batch distributions, and must be
+    /// set before the distribution begins.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///
+    SetDecay {
+        grace_epochs: u64,
+        bps_per_epoch: u16,
+    },
+
+    /// Index: 33
+    ///
+    /// Settles the current round, splitting what was lost to decay evenly
+    /// among its paid recipients: what they forfeited, and what the shares
+    /// never claimed have decayed by then. The decayed value of the shares
+    /// never claimed, and whatever doesn't split evenly, stay funded for the
+    /// next round. The first call closes the round to further payouts and
+    /// fixes each recipient's bonus.
+    ///
+    /// This instruction is called as many times as necessary to pay the
+    /// bonus to every paid recipient. Each recipient's receipt records the
+    /// bonus paid, so it can't be paid twice.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///   2. `[]` Token program ID.
+    ///   3. `[writable]` Distribution token account, the funding source
+    ///      token account when the distribution is funded by delegation, or
+    ///      the distribution token mint for a mint distribution.
+    ///   4. `[]` The program config account.
+    ///   5. ..5+4M M groups of:
+    ///      * `[writable]` Recipient token account.
+    ///      * `[writable]` Receipt account of the recipient wallet.
+    ///      * `[]` Denylist entry of the recipient wallet.
+    ///      * `[]` Forward address account of the recipient wallet.
+    ///
+    Settle,
+
+    /// Index: 34
+    ///
+    /// Sets the payout hook program that `Distribute` and
+    /// `DistributeToWallets` call after paying each recipient, with
+    /// `hook_accounts_len` extra accounts given per recipient. The default
+    /// pubkey as hook program removes the hook.
+    ///
+    /// The other instructions paying recipients, `Drip`, `Redeem`,
+    /// `ClaimRafflePrize`, `ReleaseGrant`, `ClaimWithVoucher`, `RelayClaim`
+    /// and `Settle`, take no hook accounts and fail with `HookUnavailable`
+    /// while the distribution has a hook.
+    ///
+    /// `HookFailurePolicy::Skip` only covers a hook program that can't be
+    /// called at all, because it was closed or isn't a program: the payout
+    /// goes ahead without calling it. A hook that is called and fails always
+    /// fails the payout, whatever the policy.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[signer]` The dist authority.
+    ///
+    SetHook {
+        hook_program: Pubkey,
+        hook_accounts_len: u8,
+        failure_policy: HookFailurePolicy,
+    },
+
+    /// Index: 35
+    ///
+    /// Changes nothing, and returns a summary of the distribution as a
+    /// [DistributionInfo](../query/enum.DistributionInfo.html) in the
+    /// program return data.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Distribution account.
+    ///
+    GetDistributionInfo,
+
+    /// Index: 36
+    ///
+    /// Changes nothing, and returns whether the distribution paid
+    /// `recipient` in its current round as a
+    /// [RecipientPaid](../query/enum.RecipientPaid.html) in the program
+    /// return data.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Distribution account.
+    ///   1. `[]` Receipt account of the recipient wallet.
+    ///
+    IsRecipientPaid { recipient: Pubkey },
+
+    /// Index: 37
+    ///
+    /// Migrates a version 1 distribution account to the current layout,
+    /// growing it to the current size. Version 1 accounts can't be used by
+    /// any other instruction until migrated.
+    ///
+    /// The migrated distribution keeps its address and has no project name.
+    /// If it had paid some of its recipients, it moves on to a new round
+    /// holding what is left of its funds, as version 1 didn't record which
+    /// recipients were paid.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Distribution account.
+    ///   1. `[writable, signer]` The dist authority, paying the rent for the
+    ///      larger account.
+    ///   2. `[]` The system program ID.
+    ///
+    MigrateDistribution,
+}
+
+impl DistInstruction {
+    /// Unpacks a byte buffer into a [DistInstruction](enum.DistInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        use DistError::InvalidInstruction;
+
+        let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
+
+        Ok(match tag {
+            0 => {
+                let (seed, rest) = Self::unpack_pubkey(rest)?;
+                let (project_name, rest) = Self::unpack_string(rest)?;
+                let (&seed_bump, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let (max_recipients, rest) = Self::unpack_u16(rest)?;
+                let (dist_authority, _rest) = Self::unpack_pubkey(rest)?;
+
+                Self::InitializeDistribution {
+                    seed,
+                    project_name,
+                    seed_bump,
+                    max_recipients,
+                    dist_authority,
+                }
+            }
+            1 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (delegated, _rest) = Self::unpack_flag(rest)?;
+
+                Self::FundDistribution { amount, delegated }
+            }
+            2 => {
+                let (new_dist_authority, _rest) = Self::unpack_pubkey(rest)?;
+
+                Self::ProposeDistAuthority { new_dist_authority }
+            }
+            3 => {
+                let (num_recipients, rest) = Self::unpack_u16(rest)?;
+                let (is_raffle, rest) = Self::unpack_flag(rest)?;
+                let raffle = if is_raffle {
+                    let (entrants_root, rest) = Self::unpack_hash(rest)?;
+                    let (num_entrants, _rest) = Self::unpack_u32(rest)?;
+                    Some(RaffleCommit {
+                        entrants_root,
+                        num_entrants,
+                    })
+                } else {
+                    None
+                };
+
+                Self::BeginDistribution {
+                    num_recipients,
+                    raffle,
+                }
+            }
+            4 => {
+                let (best_effort, _rest) = Self::unpack_flag(rest)?;
+
+                Self::Distribute { best_effort }
+            }
+            5 => {
+                let (description, rest) = Self::unpack_string(rest)?;
+                let (uri, rest) = Self::unpack_string(rest)?;
+                let (creator_label, rest) = Self::unpack_string(rest)?;
+                let (category, _rest) = Self::unpack_string(rest)?;
+
+                Self::SetMetadata {
+                    description,
+                    uri,
+                    creator_label,
+                    category,
+                }
+            }
+            6 => {
+                let (admin, _rest) = Self::unpack_pubkey(rest)?;
+
+                Self::InitializeConfig { admin }
+            }
+            7 => {
+                let (paused, rest) = Self::unpack_bool(rest)?;
+                let (protocol_fee_bps, rest) = Self::unpack_u16(rest)?;
+                let (treasury, _rest) = Self::unpack_pubkey(rest)?;
+
+                Self::UpdateConfig {
+                    paused,
+                    protocol_fee_bps,
+                    treasury,
+                }
+            }
+            8 => {
+                let (mints, _rest) = Self::unpack_pubkeys(rest)?;
+
+                Self::SetAllowedMints { mints }
+            }
+            9 => Self::AcceptDistAuthority,
+            10 => Self::CancelDistAuthority,
+            11 => {
+                let (best_effort, _rest) = Self::unpack_flag(rest)?;
+
+                Self::DistributeToWallets { best_effort }
+            }
+            12 => {
+                let (release_rate, rest) = Self::unpack_u64(rest)?;
+                let (recipients_root, _rest) = Self::unpack_hash(rest)?;
+
+                Self::SetReleaseRate {
+                    release_rate,
+                    recipients_root,
+                }
+            }
+            13 => {
+                let (&count, mut rest) = rest.split_first().ok_or(DistError::InvalidInstruction)?;
+                let mut proofs = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (proof, next) = Self::unpack_hashes(rest)?;
+                    proofs.push(proof);
+                    rest = next;
+                }
+
+                Self::Drip { proofs }
+            }
+            14 => Self::StartRound,
+            15 => {
+                let (wallets, _rest) = Self::unpack_pubkeys(rest)?;
+
+                Self::AddToDenylist { wallets }
+            }
+            16 => {
+                let (wallets, _rest) = Self::unpack_pubkeys(rest)?;
+
+                Self::RemoveFromDenylist { wallets }
+            }
+            17 => {
+                let (supply_cap, _rest) = Self::unpack_u64(rest)?;
+
+                Self::SetMintDistribution { supply_cap }
+            }
+            18 => Self::DepositNfts,
+            19 => {
+                let (input_mint, rest) = Self::unpack_pubkey(rest)?;
+                let (rate_numerator, rest) = Self::unpack_u64(rest)?;
+                let (rate_denominator, rest) = Self::unpack_u64(rest)?;
+                let (cap, rest) = Self::unpack_u64(rest)?;
+                let (burn_input, _rest) = Self::unpack_bool(rest)?;
+
+                Self::SetRedemption {
+                    input_mint,
+                    rate_numerator,
+                    rate_denominator,
+                    cap,
+                    burn_input,
+                }
+            }
+            20 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+
+                Self::Redeem { amount }
+            }
+            21 => Self::Draw,
+            22 => {
+                let (index, rest) = Self::unpack_u32(rest)?;
+                let (proof, _rest) = Self::unpack_hashes(rest)?;
+
+                Self::ClaimRafflePrize { index, proof }
+            }
+            23 => {
+                let (recipient, rest) = Self::unpack_pubkey(rest)?;
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (start_epoch, rest) = Self::unpack_u64(rest)?;
+                let (end_epoch, _rest) = Self::unpack_u64(rest)?;
+
+                Self::CreateGrant {
+                    recipient,
+                    amount,
+                    start_epoch,
+                    end_epoch,
+                }
+            }
+            24 => Self::ReleaseGrant,
+            25 => Self::RevokeGrant,
+            26 => Self::SetForwardAddress,
+            27 => {
+                let (gate_mint, rest) = Self::unpack_pubkey(rest)?;
+                let (min_balance, _rest) = Self::unpack_u64(rest)?;
+
+                Self::SetGate {
+                    gate_mint,
+                    min_balance,
+                }
+            }
+            28 => {
+                let (attester, _rest) = Self::unpack_pubkey(rest)?;
+
+                Self::SetAttester { attester }
+            }
+            29 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (nonce, rest) = Self::unpack_u64(rest)?;
+                let (expiry, _rest) = Self::unpack_u64(rest)?;
+
+                Self::ClaimWithVoucher {
+                    amount,
+                    nonce,
+                    expiry: expiry as i64,
+                }
+            }
+            30 => {
+                let (relay_fee_cap, _rest) = Self::unpack_u64(rest)?;
+
+                Self::SetRelayFeeCap { relay_fee_cap }
+            }
+            31 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (nonce, rest) = Self::unpack_u64(rest)?;
+                let (expiry, rest) = Self::unpack_u64(rest)?;
+                let (relayer_fee, _rest) = Self::unpack_u64(rest)?;
+
+                Self::RelayClaim {
+                    amount,
+                    nonce,
+                    expiry: expiry as i64,
+                    relayer_fee,
+                }
+            }
+            32 => {
+                let (grace_epochs, rest) = Self::unpack_u64(rest)?;
+                let (bps_per_epoch, _rest) = Self::unpack_u16(rest)?;
+
+                Self::SetDecay {
+                    grace_epochs,
+                    bps_per_epoch,
+                }
+            }
+            33 => Self::Settle,
+            34 => {
+                let (hook_program, rest) = Self::unpack_pubkey(rest)?;
+                let (&hook_accounts_len, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let failure_policy = match rest.first() {
+                    Some(0) => HookFailurePolicy::Abort,
+                    Some(1) => HookFailurePolicy::Skip,
+                    _ => return Err(InvalidInstruction.into()),
+                };
+
+                Self::SetHook {
+                    hook_program,
+                    hook_accounts_len,
+                    failure_policy,
+                }
+            }
+            35 => Self::GetDistributionInfo,
+            36 => {
+                let (recipient, _rest) = Self::unpack_pubkey(rest)?;
+
+                Self::IsRecipientPaid { recipient }
+            }
+            37 => Self::MigrateDistribution,
+            _ => {
+                return Err(InvalidInstruction.into());
+            }
+        })
+    }
+
+    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+        if input.len() >= PUBKEY_BYTES {
+            let (key, rest) = input.split_at(PUBKEY_BYTES);
+            let pk = Pubkey::new(key);
+            Ok((pk, rest))
+        } else {
+            Err(DistError::InvalidInstruction.into())
+        }
+    }
+
+    /// Unpacks a list of pubkeys prefixed with a one byte count.
+    fn unpack_pubkeys(input: &[u8]) -> Result<(Vec<Pubkey>, &[u8]), ProgramError> {
+        let (&count, mut rest) = input.split_first().ok_or(DistError::InvalidInstruction)?;
+        let mut pubkeys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (pubkey, next) = Self::unpack_pubkey(rest)?;
+            pubkeys.push(pubkey);
+            rest = next;
+        }
+        Ok((pubkeys, rest))
+    }
+
+    fn pack_pubkeys(buf: &mut Vec<u8>, pubkeys: &[Pubkey]) {
+        buf.push(pubkeys.len() as u8);
+        for pubkey in pubkeys {
+            buf.extend_from_slice(pubkey.as_ref());
+        }
+    }
+
+    fn unpack_hash(input: &[u8]) -> Result<(Hash, &[u8]), ProgramError> {
+        if input.len() < HASH_BYTES {
+            return Err(DistError::InvalidInstruction.into());
+        }
+        let (hash, rest) = input.split_at(HASH_BYTES);
+        Ok((Hash::new(hash), rest))
+    }
+
+    /// Unpacks a list of hashes prefixed with a one byte count.
+    fn unpack_hashes(input: &[u8]) -> Result<(Vec<Hash>, &[u8]), ProgramError> {
+        let (&count, mut rest) = input.split_first().ok_or(DistError::InvalidInstruction)?;
+        let mut hashes = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (hash, next) = Self::unpack_hash(rest)?;
+            hashes.push(hash);
+            rest = next;
+        }
+        Ok((hashes, rest))
+    }
+
+    fn pack_hashes(buf: &mut Vec<u8>, hashes: &[Hash]) {
+        buf.push(hashes.len() as u8);
+        for hash in hashes {
+            buf.extend_from_slice(hash.as_ref());
+        }
+    }
+
+    fn unpack_bool(input: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
+        match input.split_first() {
+            Some((0, rest)) => Ok((false, rest)),
+            Some((1, rest)) => Ok((true, rest)),
+            _ => Err(DistError::InvalidInstruction.into()),
+        }
+    }
+
+    /// Flags added to an existing instruction are absent from instructions
+    /// packed before them, and default to false.
+    fn unpack_flag(input: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
+        if input.is_empty() {
+            return Ok((false, input));
+        }
+        Self::unpack_bool(input)
+    }
+
+    fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+        let (&low, rest) = input.split_first().ok_or(DistError::InvalidInstruction)?;
+        let (&high, rest) = rest.split_first().ok_or(DistError::InvalidInstruction)?;
+        Ok((u16::from_le_bytes([low, high]), rest))
+    }
+
+    fn unpack_u32(input: &[u8]) -> Result<(u32, &[u8]), ProgramError> {
+        if input.len() < 4 {
+            return Err(DistError::InvalidInstruction.into());
+        }
+        let (value, rest) = input.split_at(4);
+        Ok((
+            u32::from_le_bytes([value[0], value[1], value[2], value[3]]),
+            rest,
+        ))
+    }
+
+    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() < 8 {
+            return Err(DistError::InvalidInstruction.into());
+        }
+        let (value, rest) = input.split_at(8);
+        let value = value
+            .try_into()
+            .ok()
+            .map(u64::from_le_bytes)
+            .ok_or(DistError::InvalidInstruction)?;
+        Ok((value, rest))
+    }
+
+    fn unpack_string(input: &[u8]) -> Result<(String, &[u8]), ProgramError> {
+        let (len, rest) = Self::unpack_u16(input)?;
+        let len = len as usize;
+        if rest.len() < len {
+            return Err(DistError::InvalidInstruction.into());
+        }
+        let (bytes, rest) = rest.split_at(len);
+        let s = String::from_utf8(bytes.to_vec()).map_err(|_| DistError::InvalidInstruction)?;
+        Ok((s, rest))
+    }
+
+    fn pack_string(buf: &mut Vec<u8>, s: &str) {
+        buf.extend_from_slice(&(s.len() as u16).to_le_bytes());
+        buf.extend_from_slice(s.as_bytes());
+    }
+
+    /// Packs a [DistInstruction](enum.DistInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::InitializeDistribution {
+                seed,
+                project_name,
+                seed_bump,
+                max_recipients,
+                dist_authority,
+            } => {
+                buf.push(0);
+                buf.extend_from_slice(seed.as_ref());
+                Self::pack_string(&mut buf, project_name);
+                buf.push(*seed_bump);
+                buf.extend_from_slice(&max_recipients.to_le_bytes());
+                buf.extend_from_slice(dist_authority.as_ref());
+            }
+            Self::FundDistribution { amount, delegated } => {
+                buf.push(1);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.push(*delegated as u8);
+            }
+            Self::ProposeDistAuthority { new_dist_authority } => {
+                buf.push(2);
+                buf.extend_from_slice(new_dist_authority.as_ref());
+            }
+            Self::BeginDistribution {
+                num_recipients,
+                raffle,
+            } => {
+                buf.push(3);
+                buf.extend_from_slice(&num_recipients.to_le_bytes());
+                if let Some(raffle) = raffle {
+                    buf.push(1);
+                    buf.extend_from_slice(raffle.entrants_root.as_ref());
+                    buf.extend_from_slice(&raffle.num_entrants.to_le_bytes());
+                }
+            }
+            Self::Distribute { best_effort } => {
+                buf.push(4);
+                buf.push(*best_effort as u8);
+            }
+            Self::SetMetadata {
+                description,
+                uri,
+                creator_label,
+                category,
+            } => {
+                buf.push(5);
+                Self::pack_string(&mut buf, description);
+                Self::pack_string(&mut buf, uri);
+                Self::pack_string(&mut buf, creator_label);
+                Self::pack_string(&mut buf, category);
+            }
+            Self::InitializeConfig { admin } => {
+                buf.push(6);
+                buf.extend_from_slice(admin.as_ref());
+            }
+            Self::UpdateConfig {
+                paused,
+                protocol_fee_bps,
+                treasury,
+            } => {
+                buf.push(7);
+                buf.push(*paused as u8);
+                buf.extend_from_slice(&protocol_fee_bps.to_le_bytes());
+                buf.extend_from_slice(treasury.as_ref());
+            }
+            Self::SetAllowedMints { mints } => {
+                buf.push(8);
+                Self::pack_pubkeys(&mut buf, mints);
+            }
+            Self::AcceptDistAuthority => {
+                buf.push(9);
+            }
+            Self::CancelDistAuthority => {
+                buf.push(10);
+            }
+            Self::DistributeToWallets { best_effort } => {
+                buf.push(11);
+                buf.push(*best_effort as u8);
+            }
+            Self::SetReleaseRate {
+                release_rate,
+                recipients_root,
+            } => {
+                buf.push(12);
+                buf.extend_from_slice(&release_rate.to_le_bytes());
+                buf.extend_from_slice(recipients_root.as_ref());
+            }
+            Self::Drip { proofs } => {
+                buf.push(13);
+                buf.push(proofs.len() as u8);
+                for proof in proofs {
+                    Self::pack_hashes(&mut buf, proof);
+                }
+            }
+            Self::StartRound => {
+                buf.push(14);
+            }
+            Self::AddToDenylist { wallets } => {
+                buf.push(15);
+                Self::pack_pubkeys(&mut buf, wallets);
+            }
+            Self::RemoveFromDenylist { wallets } => {
+                buf.push(16);
+                Self::pack_pubkeys(&mut buf, wallets);
+            }
+            Self::SetMintDistribution { supply_cap } => {
+                buf.push(17);
+                buf.extend_from_slice(&supply_cap.to_le_bytes());
+            }
+            Self::DepositNfts => {
+                buf.push(18);
+            }
+            Self::SetRedemption {
+                input_mint,
+                rate_numerator,
+                rate_denominator,
+                cap,
+                burn_input,
+            } => {
+                buf.push(19);
+                buf.extend_from_slice(input_mint.as_ref());
+                buf.extend_from_slice(&rate_numerator.to_le_bytes());
+                buf.extend_from_slice(&rate_denominator.to_le_bytes());
+                buf.extend_from_slice(&cap.to_le_bytes());
+                buf.push(*burn_input as u8);
+            }
+            Self::Redeem { amount } => {
+                buf.push(20);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::Draw => {
+                buf.push(21);
+            }
+            Self::ClaimRafflePrize { index, proof } => {
+                buf.push(22);
+                buf.extend_from_slice(&index.to_le_bytes());
+                Self::pack_hashes(&mut buf, proof);
+            }
+            Self::CreateGrant {
+                recipient,
+                amount,
+                start_epoch,
+                end_epoch,
+            } => {
+                buf.push(23);
+                buf.extend_from_slice(recipient.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&start_epoch.to_le_bytes());
+                buf.extend_from_slice(&end_epoch.to_le_bytes());
+            }
+            Self::ReleaseGrant => {
+                buf.push(24);
+            }
+            Self::RevokeGrant => {
+                buf.push(25);
+            }
+            Self::SetForwardAddress => {
+                buf.push(26);
+            }
+            Self::SetGate {
+                gate_mint,
+                min_balance,
+            } => {
+                buf.push(27);
+                buf.extend_from_slice(gate_mint.as_ref());
+                buf.extend_from_slice(&min_balance.to_le_bytes());
+            }
+            Self::SetAttester { attester } => {
+                buf.push(28);
+                buf.extend_from_slice(attester.as_ref());
+            }
+            Self::ClaimWithVoucher {
+                amount,
+                nonce,
+                expiry,
+            } => {
+                buf.push(29);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&nonce.to_le_bytes());
+                buf.extend_from_slice(&expiry.to_le_bytes());
+            }
+            Self::SetRelayFeeCap { relay_fee_cap } => {
+                buf.push(30);
+                buf.extend_from_slice(&relay_fee_cap.to_le_bytes());
+            }
+            Self::RelayClaim {
+                amount,
+                nonce,
+                expiry,
+                relayer_fee,
+            } => {
+                buf.push(31);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&nonce.to_le_bytes());
+                buf.extend_from_slice(&expiry.to_le_bytes());
+                buf.extend_from_slice(&relayer_fee.to_le_bytes());
+            }
+            Self::SetDecay {
+                grace_epochs,
+                bps_per_epoch,
+            } => {
+                buf.push(32);
+                buf.extend_from_slice(&grace_epochs.to_le_bytes());
+                buf.extend_from_slice(&bps_per_epoch.to_le_bytes());
+            }
+            Self::Settle => {
+                buf.push(33);
+            }
+            Self::SetHook {
+                hook_program,
+                hook_accounts_len,
+                failure_policy,
+            } => {
+                buf.push(34);
+                buf.extend_from_slice(hook_program.as_ref());
+                buf.push(*hook_accounts_len);
+                buf.push(*failure_policy as u8);
+            }
+            Self::GetDistributionInfo => {
+                buf.push(35);
+            }
+            Self::IsRecipientPaid { recipient } => {
+                buf.push(36);
+                buf.extend_from_slice(recipient.as_ref());
+            }
+            Self::MigrateDistribution => {
+                buf.push(37);
+            }
+        }
+        buf
+    }
+}
+
+pub fn init_distribution(
+    program_id: &Pubkey,
+    token: &Pubkey,
+    dist_account: &Pubkey,
+    fee_payer_account: &Pubkey,
+    seed: &Pubkey,
+    project_name: &str,
+    seed_bump: u8,
+    max_recipients: u16,
+    dist_authority_account: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::InitializeDistribution {
+        seed: *seed,
+        project_name: project_name.to_owned(),
+        seed_bump,
+        max_recipients,
+        dist_authority: *dist_authority_account,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*fee_payer_account, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*token, false),
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(crate::find_config_address(program_id).0, false),
+        ],
+        data,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn fund_distribution(
+    program_id: &Pubkey,
+    source_account: &Pubkey,
+    source_token_account: &Pubkey,
+    dist_account: &Pubkey,
+    dist_token_account: &Pubkey,
+    treasury_token_account: Option<&Pubkey>,
+    amount: u64,
+    delegated: bool,
+) -> Instruction {
+    let data = DistInstruction::FundDistribution { amount, delegated }.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*source_account, true),
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*dist_account, false),
+        AccountMeta::new(*dist_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(crate::find_config_address(program_id).0, false),
+    ];
+    if let Some(treasury_token_account) = treasury_token_account {
+        accounts.push(AccountMeta::new(*treasury_token_account, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn propose_dist_authority(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    new_dist_authority: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::ProposeDistAuthority {
+        new_dist_authority: *new_dist_authority,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+        ],
+        data,
+    }
+}
+
+pub fn accept_dist_authority(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    new_dist_authority: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::AcceptDistAuthority.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*new_dist_authority, true),
+        ],
+        data,
+    }
+}
+
+pub fn cancel_dist_authority(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::CancelDistAuthority.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+        ],
+        data,
+    }
+}
+
+pub fn begin_distribution(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    funding_source: Option<&Pubkey>,
+    num_recipients: u16,
+) -> Instruction {
+    let data = DistInstruction::BeginDistribution {
+        num_recipients,
+        raffle: None,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*dist_account, false),
+        AccountMeta::new_readonly(*dist_authority, true),
+    ];
+    if let Some(funding_source) = funding_source {
+        accounts.push(AccountMeta::new_readonly(*funding_source, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Begins a raffle drawing `num_winners` winners out of the committed
+/// entrants.
+pub fn begin_raffle(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    funding_source: Option<&Pubkey>,
+    num_winners: u16,
+    raffle: RaffleCommit,
+) -> Instruction {
+    let data = DistInstruction::BeginDistribution {
+        num_recipients: num_winners,
+        raffle: Some(raffle),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*dist_account, false),
+        AccountMeta::new_readonly(*dist_authority, true),
+    ];
+    if let Some(funding_source) = funding_source {
+        accounts.push(AccountMeta::new_readonly(*funding_source, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Recipients are given as pairs of token account and recipient wallet. The
+/// token account is the one the wallet owns, or its forward address if it
+/// set one.
+///
+/// `gate_mint` must be given for a gated distribution. Each recipient
+/// wallet's associated token account of the gate mint is then passed as its
+/// gate token account, here and in the other payout instructions.
+///
+/// `hook` must be given for a distribution with a payout hook, as the hook
+/// program and the extra accounts passed to it for each recipient.
+#[allow(clippy::too_many_arguments)]
+pub fn distribute(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    dist_token_account: &Pubkey,
+    payer: &Pubkey,
+    recipients: &[(&Pubkey, &Pubkey)],
+    gate_mint: Option<&Pubkey>,
+    hook: Option<(&Pubkey, &[Vec<AccountMeta>])>,
+    best_effort: bool,
+) -> Instruction {
+    let data = DistInstruction::Distribute { best_effort }.pack();
+
+    let mut accounts = Vec::with_capacity(7 + 5 * recipients.len());
+    accounts.push(AccountMeta::new(*dist_account, false));
+    accounts.push(AccountMeta::new_readonly(*dist_authority, true));
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    accounts.push(AccountMeta::new(*dist_token_account, false));
+    accounts.push(AccountMeta::new_readonly(
+        crate::find_config_address(program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    if let Some((hook_program, _)) = hook {
+        accounts.push(AccountMeta::new_readonly(*hook_program, false));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_hook_authority_address(program_id, dist_account).0,
+            false,
+        ));
+    }
+    for (i, (recipient_token_account, recipient_wallet)) in recipients.iter().enumerate() {
+        accounts.push(AccountMeta::new(**recipient_token_account, false));
+        accounts.push(AccountMeta::new(
+            crate::find_receipt_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_denylist_address(program_id, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_forward_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
+        if let Some(gate_mint) = gate_mint {
+            accounts.push(AccountMeta::new_readonly(
+                spl_associated_token_account::get_associated_token_address(
+                    recipient_wallet,
+                    gate_mint,
+                ),
+                false,
+            ));
+        }
+        if let Some((_, hook_accounts)) = hook {
+            accounts.extend_from_slice(&hook_accounts[i]);
+        }
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Recipients of an NFT batch distribution are given as triples of token
+/// account, the wallet that owns it and the mint of the NFT it gets.
+#[allow(clippy::too_many_arguments)]
+pub fn distribute_nfts(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    payer: &Pubkey,
+    recipients: &[(&Pubkey, &Pubkey, &Pubkey)],
+    gate_mint: Option<&Pubkey>,
+    hook: Option<(&Pubkey, &[Vec<AccountMeta>])>,
+    best_effort: bool,
+) -> Instruction {
+    let data = DistInstruction::Distribute { best_effort }.pack();
+
+    let mut accounts = Vec::with_capacity(7 + 5 * recipients.len());
+    accounts.push(AccountMeta::new(*dist_account, false));
+    accounts.push(AccountMeta::new_readonly(*dist_authority, true));
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    // There is no distribution token account to pay out of
+    accounts.push(AccountMeta::new(*dist_account, false));
+    accounts.push(AccountMeta::new_readonly(
+        crate::find_config_address(program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    if let Some((hook_program, _)) = hook {
+        accounts.push(AccountMeta::new_readonly(*hook_program, false));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_hook_authority_address(program_id, dist_account).0,
+            false,
+        ));
+    }
+    for (i, (recipient_token_account, recipient_wallet, mint)) in recipients.iter().enumerate() {
+        accounts.push(AccountMeta::new(**recipient_token_account, false));
+        accounts.push(AccountMeta::new(
+            crate::find_receipt_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_denylist_address(program_id, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            crate::find_pool_item_address(program_id, dist_account, mint).0,
+            false,
+        ));
+        if let Some(gate_mint) = gate_mint {
+            accounts.push(AccountMeta::new_readonly(
+                spl_associated_token_account::get_associated_token_address(
+                    recipient_wallet,
+                    gate_mint,
+                ),
+                false,
+            ));
+        }
+        if let Some((_, hook_accounts)) = hook {
+            accounts.extend_from_slice(&hook_accounts[i]);
+        }
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn set_metadata(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    fee_payer_account: &Pubkey,
+    description: &str,
+    uri: &str,
+    creator_label: &str,
+    category: &str,
+) -> Instruction {
+    let data = DistInstruction::SetMetadata {
+        description: description.to_owned(),
+        uri: uri.to_owned(),
+        creator_label: creator_label.to_owned(),
+        category: category.to_owned(),
+    }
+    .pack();
+
+    let (metadata_account, _) = crate::find_metadata_address(program_id, dist_account);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+            AccountMeta::new(*fee_payer_account, true),
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn initialize_config(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+    admin: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::InitializeConfig { admin: *admin }.pack();
+
+    let (config_account, _) = crate::find_config_address(program_id);
+    let (program_data_account, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*upgrade_authority, true),
+            AccountMeta::new(config_account, false),
+            AccountMeta::new_readonly(program_data_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn update_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    paused: bool,
+    protocol_fee_bps: u16,
+    treasury: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::UpdateConfig {
+        paused,
+        protocol_fee_bps,
+        treasury: *treasury,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(crate::find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+        ],
+        data,
+    }
+}
+
+pub fn set_allowed_mints(program_id: &Pubkey, admin: &Pubkey, mints: &[Pubkey]) -> Instruction {
+    let data = DistInstruction::SetAllowedMints {
+        mints: mints.to_vec(),
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(crate::find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+        ],
+        data,
+    }
+}
+
+/// Recipients are given as pairs of wallet and the forward address it set,
+/// if any.
+///
+/// `hook` must be given for a distribution with a payout hook, as for
+/// [distribute](fn.distribute.html).
+#[allow(clippy::too_many_arguments)]
+pub fn distribute_to_wallets(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    dist_token_account: &Pubkey,
+    payer: &Pubkey,
+    token: &Pubkey,
+    recipients: &[(&Pubkey, Option<&Pubkey>)],
+    gate_mint: Option<&Pubkey>,
+    hook: Option<(&Pubkey, &[Vec<AccountMeta>])>,
+    best_effort: bool,
+) -> Instruction {
+    let data = DistInstruction::DistributeToWallets { best_effort }.pack();
+
+    let mut accounts = Vec::with_capacity(11 + 6 * recipients.len());
+    accounts.push(AccountMeta::new(*dist_account, false));
+    accounts.push(AccountMeta::new_readonly(*dist_authority, true));
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    accounts.push(AccountMeta::new(*dist_token_account, false));
+    accounts.push(AccountMeta::new_readonly(
+        crate::find_config_address(program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new_readonly(*token, false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        spl_associated_token_account::id(),
+        false,
+    ));
+    if let Some((hook_program, _)) = hook {
+        accounts.push(AccountMeta::new_readonly(*hook_program, false));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_hook_authority_address(program_id, dist_account).0,
+            false,
+        ));
+    }
+    for (i, (recipient_wallet, forward_address)) in recipients.iter().enumerate() {
+        let recipient_token_account = match forward_address {
+            Some(forward_address) => **forward_address,
+            None => {
+                spl_associated_token_account::get_associated_token_address(recipient_wallet, token)
+            }
+        };
+        accounts.push(AccountMeta::new_readonly(**recipient_wallet, false));
+        accounts.push(AccountMeta::new(recipient_token_account, false));
+        accounts.push(AccountMeta::new(
+            crate::find_receipt_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_denylist_address(program_id, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_forward_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
+        if let Some(gate_mint) = gate_mint {
+            accounts.push(AccountMeta::new_readonly(
+                spl_associated_token_account::get_associated_token_address(
+                    recipient_wallet,
+                    gate_mint,
+                ),
+                false,
+            ));
+        }
+        if let Some((_, hook_accounts)) = hook {
+            accounts.extend_from_slice(&hook_accounts[i]);
+        }
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn set_release_rate(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    release_rate: u64,
+    recipients_root: &Hash,
+) -> Instruction {
+    let data = DistInstruction::SetReleaseRate {
+        release_rate,
+        recipients_root: *recipients_root,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+        ],
+        data,
+    }
+}
+
+/// Recipients are given as pairs of the token account paid, which is the
+/// forward address of wallets that set one, and the recipient wallet, with
+/// the merkle proof of each wallet in `proofs`.
+#[allow(clippy::too_many_arguments)]
+pub fn drip(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    dist_token_account: &Pubkey,
+    payer: &Pubkey,
+    recipients: &[(&Pubkey, &Pubkey)],
+    proofs: Vec<Vec<Hash>>,
+    gate_mint: Option<&Pubkey>,
+) -> Instruction {
+    let data = DistInstruction::Drip { proofs }.pack();
+
+    let mut accounts = Vec::with_capacity(7 + 5 * recipients.len());
+    accounts.push(AccountMeta::new(*dist_account, false));
+    accounts.push(AccountMeta::new_readonly(*dist_authority, true));
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    accounts.push(AccountMeta::new(*dist_token_account, false));
+    accounts.push(AccountMeta::new_readonly(
+        crate::find_config_address(program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    for (recipient_token_account, recipient_wallet) in recipients.iter() {
+        accounts.push(AccountMeta::new(**recipient_token_account, false));
+        accounts.push(AccountMeta::new(
+            crate::find_receipt_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_denylist_address(program_id, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_forward_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
+        if let Some(gate_mint) = gate_mint {
+            accounts.push(AccountMeta::new_readonly(
+                spl_associated_token_account::get_associated_token_address(
+                    recipient_wallet,
+                    gate_mint,
+                ),
+                false,
+            ));
+        }
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn start_round(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    fee_payer_account: &Pubkey,
+    round: u32,
+) -> Instruction {
+    let data = DistInstruction::StartRound.pack();
+
+    let (round_account, _) = crate::find_round_address(program_id, dist_account, round);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+            AccountMeta::new(*fee_payer_account, true),
+            AccountMeta::new(round_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn add_to_denylist(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    fee_payer_account: &Pubkey,
+    wallets: &[Pubkey],
+) -> Instruction {
+    let data = DistInstruction::AddToDenylist {
+        wallets: wallets.to_vec(),
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(4 + wallets.len());
+    accounts.push(AccountMeta::new_readonly(
+        crate::find_config_address(program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(*admin, true));
+    accounts.push(AccountMeta::new(*fee_payer_account, true));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    for wallet in wallets {
+        accounts.push(AccountMeta::new(
+            crate::find_denylist_address(program_id, wallet).0,
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn remove_from_denylist(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    rent_receiver: &Pubkey,
+    wallets: &[Pubkey],
+) -> Instruction {
+    let data = DistInstruction::RemoveFromDenylist {
+        wallets: wallets.to_vec(),
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3 + wallets.len());
+    accounts.push(AccountMeta::new_readonly(
+        crate::find_config_address(program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(*admin, true));
+    accounts.push(AccountMeta::new(*rent_receiver, false));
+    for wallet in wallets {
+        accounts.push(AccountMeta::new(
+            crate::find_denylist_address(program_id, wallet).0,
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn set_mint_distribution(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    token: &Pubkey,
+    supply_cap: u64,
+) -> Instruction {
+    let data = DistInstruction::SetMintDistribution { supply_cap }.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+            AccountMeta::new_readonly(*token, false),
+        ],
+        data,
+    }
+}
+
+/// NFTs are given as pairs of mint and the owner's token account holding it.
+pub fn deposit_nfts(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    owner: &Pubkey,
+    payer: &Pubkey,
+    nfts: &[(&Pubkey, &Pubkey)],
+) -> Instruction {
+    let data = DistInstruction::DepositNfts.pack();
+
+    let mut accounts = Vec::with_capacity(7 + 3 * nfts.len());
+    accounts.push(AccountMeta::new(*dist_account, false));
+    accounts.push(AccountMeta::new_readonly(*dist_authority, true));
+    accounts.push(AccountMeta::new_readonly(*owner, true));
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for (mint, token_account) in nfts.iter() {
+        accounts.push(AccountMeta::new_readonly(**mint, false));
+        accounts.push(AccountMeta::new(**token_account, false));
+        accounts.push(AccountMeta::new(
+            crate::find_pool_item_address(program_id, dist_account, mint).0,
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn set_redemption(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    payer: &Pubkey,
+    input_mint: &Pubkey,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    cap: u64,
+    burn_input: bool,
+) -> Instruction {
+    let data = DistInstruction::SetRedemption {
+        input_mint: *input_mint,
+        rate_numerator,
+        rate_denominator,
+        cap,
+        burn_input,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+            AccountMeta::new_readonly(*input_mint, false),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(
+                    dist_account,
+                    input_mint,
+                ),
+                false,
+            ),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+        data,
+    }
+}
+
+/// `output_token_account` must be owned by `output_wallet`.
+#[allow(clippy::too_many_arguments)]
+pub fn redeem(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    redeemer: &Pubkey,
+    dist_token_account: &Pubkey,
+    input_mint: &Pubkey,
+    input_token_account: &Pubkey,
+    output_token_account: &Pubkey,
+    output_wallet: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let data = DistInstruction::Redeem { amount }.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*redeemer, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*dist_token_account, false),
+            AccountMeta::new_readonly(crate::find_config_address(program_id).0, false),
+            AccountMeta::new(*input_mint, false),
+            AccountMeta::new(*input_token_account, false),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(
+                    dist_account,
+                    input_mint,
+                ),
+                false,
+            ),
+            AccountMeta::new(*output_token_account, false),
+            AccountMeta::new_readonly(
+                crate::find_denylist_address(program_id, output_wallet).0,
+                false,
+            ),
+        ],
+        data,
+    }
+}
+
+pub fn draw(program_id: &Pubkey, dist_account: &Pubkey) -> Instruction {
+    let data = DistInstruction::Draw.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        ],
+        data,
+    }
+}
+
+/// `winner_token_account` must be owned by `winner_wallet`, or be its
+/// forward address if it set one.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_raffle_prize(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_token_account: &Pubkey,
+    winner_token_account: &Pubkey,
+    winner_wallet: &Pubkey,
+    payer: &Pubkey,
+    index: u32,
+    proof: Vec<Hash>,
+    gate_mint: Option<&Pubkey>,
+) -> Instruction {
+    let data = DistInstruction::ClaimRafflePrize { index, proof }.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*dist_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*dist_token_account, false),
+        AccountMeta::new_readonly(crate::find_config_address(program_id).0, false),
+        AccountMeta::new(*winner_token_account, false),
+        AccountMeta::new_readonly(
+            crate::find_denylist_address(program_id, winner_wallet).0,
+            false,
+        ),
+        AccountMeta::new_readonly(
+            crate::find_forward_address(program_id, dist_account, winner_wallet).0,
+            false,
+        ),
+        AccountMeta::new(
+            crate::find_receipt_address(program_id, dist_account, winner_wallet).0,
+            false,
+        ),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(gate_mint) = gate_mint {
+        accounts.push(AccountMeta::new_readonly(
+            spl_associated_token_account::get_associated_token_address(winner_wallet, gate_mint),
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_grant(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    source_token_account: &Pubkey,
+    token: &Pubkey,
+    payer: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> Instruction {
+    let data = DistInstruction::CreateGrant {
+        recipient: *recipient,
+        amount,
+        start_epoch,
+        end_epoch,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+            AccountMeta::new(*source_token_account, false),
+            AccountMeta::new(
+                crate::find_grant_address(program_id, dist_account, recipient).0,
+                false,
+            ),
+            AccountMeta::new(
+                crate::find_grant_vault_address(program_id, dist_account, recipient).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*token, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data,
+    }
+}
+
+/// `recipient_token_account` must be owned by `recipient`.
+pub fn release_grant(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    recipient: &Pubkey,
+    recipient_token_account: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::ReleaseGrant.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*dist_account, false),
+            AccountMeta::new(
+                crate::find_grant_address(program_id, dist_account, recipient).0,
+                false,
+            ),
+            AccountMeta::new(
+                crate::find_grant_vault_address(program_id, dist_account, recipient).0,
+                false,
+            ),
+            AccountMeta::new(*recipient_token_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(crate::find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(crate::find_denylist_address(program_id, recipient).0, false),
+        ],
+        data,
+    }
+}
+
+pub fn revoke_grant(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
     dist_authority: &Pubkey,
-    new_dist_authority: &Pubkey,
+    recipient: &Pubkey,
+    destination_token_account: &Pubkey,
 ) -> Instruction {
-    let data = DistInstruction::SetDistAuthority {
-        new_dist_authority: *new_dist_authority,
+    let data = DistInstruction::RevokeGrant.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+            AccountMeta::new(
+                crate::find_grant_address(program_id, dist_account, recipient).0,
+                false,
+            ),
+            AccountMeta::new(
+                crate::find_grant_vault_address(program_id, dist_account, recipient).0,
+                false,
+            ),
+            AccountMeta::new(*destination_token_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn set_gate(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    gate_mint: &Pubkey,
+    min_balance: u64,
+) -> Instruction {
+    let data = DistInstruction::SetGate {
+        gate_mint: *gate_mint,
+        min_balance,
     }
     .pack();
 
@@ -291,13 +2554,20 @@ pub fn set_dist_authority(
     }
 }
 
-pub fn begin_distribution(
+pub fn set_hook(
     program_id: &Pubkey,
     dist_account: &Pubkey,
     dist_authority: &Pubkey,
-    num_recipients: u16,
+    hook_program: &Pubkey,
+    hook_accounts_len: u8,
+    failure_policy: HookFailurePolicy,
 ) -> Instruction {
-    let data = DistInstruction::BeginDistribution { num_recipients }.pack();
+    let data = DistInstruction::SetHook {
+        hook_program: *hook_program,
+        hook_accounts_len,
+        failure_policy,
+    }
+    .pack();
 
     Instruction {
         program_id: *program_id,
@@ -309,22 +2579,310 @@ pub fn begin_distribution(
     }
 }
 
-pub fn distribute(
+pub fn get_distribution_info(program_id: &Pubkey, dist_account: &Pubkey) -> Instruction {
+    let data = DistInstruction::GetDistributionInfo.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new_readonly(*dist_account, false)],
+        data,
+    }
+}
+
+pub fn is_recipient_paid(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    recipient: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::IsRecipientPaid {
+        recipient: *recipient,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*dist_account, false),
+            AccountMeta::new_readonly(
+                crate::find_receipt_address(program_id, dist_account, recipient).0,
+                false,
+            ),
+        ],
+        data,
+    }
+}
+
+pub fn migrate_distribution(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::MigrateDistribution.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new(*dist_authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn set_forward_address(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    wallet: &Pubkey,
+    destination: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::SetForwardAddress.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*dist_account, false),
+            AccountMeta::new_readonly(*wallet, true),
+            AccountMeta::new(
+                crate::find_forward_address(program_id, dist_account, wallet).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*destination, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn set_attester(
     program_id: &Pubkey,
     dist_account: &Pubkey,
     dist_authority: &Pubkey,
+    attester: &Pubkey,
+) -> Instruction {
+    let data = DistInstruction::SetAttester {
+        attester: *attester,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+        ],
+        data,
+    }
+}
+
+/// `recipient_token_account` must be owned by `recipient_wallet`, or be its
+/// forward address if it set one. The instruction must come right after the
+/// Ed25519 program instruction verifying the voucher, see
+/// [new_ed25519_instruction](../ed25519/fn.new_ed25519_instruction.html).
+#[allow(clippy::too_many_arguments)]
+pub fn claim_with_voucher(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
     dist_token_account: &Pubkey,
-    recipient_token_accounts: &[&Pubkey],
+    recipient_token_account: &Pubkey,
+    recipient_wallet: &Pubkey,
+    payer: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+    gate_mint: Option<&Pubkey>,
 ) -> Instruction {
-    let data = DistInstruction::Distribute.pack();
+    let data = DistInstruction::ClaimWithVoucher {
+        amount,
+        nonce,
+        expiry,
+    }
+    .pack();
 
-    let mut accounts = Vec::with_capacity(4 + recipient_token_accounts.len());
-    accounts.push(AccountMeta::new(*dist_account, false));
-    accounts.push(AccountMeta::new_readonly(*dist_authority, true));
-    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
-    accounts.push(AccountMeta::new(*dist_token_account, false));
-    for recipient in recipient_token_accounts.iter() {
-        accounts.push(AccountMeta::new(**recipient, false));
+    let mut accounts = voucher_claim_accounts(
+        program_id,
+        dist_account,
+        dist_token_account,
+        recipient_token_account,
+        recipient_wallet,
+        payer,
+        nonce,
+    );
+    if let Some(gate_mint) = gate_mint {
+        accounts.push(AccountMeta::new_readonly(
+            spl_associated_token_account::get_associated_token_address(recipient_wallet, gate_mint),
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn set_relay_fee_cap(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    relay_fee_cap: u64,
+) -> Instruction {
+    let data = DistInstruction::SetRelayFeeCap { relay_fee_cap }.pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+        ],
+        data,
+    }
+}
+
+/// `recipient_token_account` must be owned by `recipient_wallet`, or be its
+/// forward address if it set one. The instruction must come right after the
+/// Ed25519 program instruction verifying both the voucher and the
+/// recipient's relay message.
+#[allow(clippy::too_many_arguments)]
+pub fn relay_claim(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_token_account: &Pubkey,
+    recipient_token_account: &Pubkey,
+    recipient_wallet: &Pubkey,
+    relayer: &Pubkey,
+    relayer_token_account: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+    relayer_fee: u64,
+    gate_mint: Option<&Pubkey>,
+) -> Instruction {
+    let data = DistInstruction::RelayClaim {
+        amount,
+        nonce,
+        expiry,
+        relayer_fee,
+    }
+    .pack();
+
+    let mut accounts = voucher_claim_accounts(
+        program_id,
+        dist_account,
+        dist_token_account,
+        recipient_token_account,
+        recipient_wallet,
+        relayer,
+        nonce,
+    );
+    accounts.push(AccountMeta::new(*relayer_token_account, false));
+    if let Some(gate_mint) = gate_mint {
+        accounts.push(AccountMeta::new_readonly(
+            spl_associated_token_account::get_associated_token_address(recipient_wallet, gate_mint),
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+// Accounts shared by `ClaimWithVoucher` and `RelayClaim`
+fn voucher_claim_accounts(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_token_account: &Pubkey,
+    recipient_token_account: &Pubkey,
+    recipient_wallet: &Pubkey,
+    payer: &Pubkey,
+    nonce: u64,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*dist_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*dist_token_account, false),
+        AccountMeta::new_readonly(crate::find_config_address(program_id).0, false),
+        AccountMeta::new(*recipient_token_account, false),
+        AccountMeta::new_readonly(
+            crate::find_denylist_address(program_id, recipient_wallet).0,
+            false,
+        ),
+        AccountMeta::new_readonly(
+            crate::find_forward_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ),
+        AccountMeta::new(
+            crate::find_voucher_address(program_id, dist_account, recipient_wallet, nonce).0,
+            false,
+        ),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+    ]
+}
+
+pub fn set_decay(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    grace_epochs: u64,
+    bps_per_epoch: u16,
+) -> Instruction {
+    let data = DistInstruction::SetDecay {
+        grace_epochs,
+        bps_per_epoch,
+    }
+    .pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*dist_account, false),
+            AccountMeta::new_readonly(*dist_authority, true),
+        ],
+        data,
+    }
+}
+
+/// Each recipient is a pair of the token account its bonus is paid to and
+/// the recipient wallet. The token account must be owned by the wallet, or
+/// be its forward address if it set one.
+pub fn settle(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    dist_authority: &Pubkey,
+    dist_token_account: &Pubkey,
+    recipients: &[(&Pubkey, &Pubkey)],
+) -> Instruction {
+    let data = DistInstruction::Settle.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*dist_account, false),
+        AccountMeta::new_readonly(*dist_authority, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*dist_token_account, false),
+        AccountMeta::new_readonly(crate::find_config_address(program_id).0, false),
+    ];
+    for &(recipient_token_account, recipient_wallet) in recipients {
+        accounts.push(AccountMeta::new(*recipient_token_account, false));
+        accounts.push(AccountMeta::new(
+            crate::find_receipt_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_denylist_address(program_id, recipient_wallet).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            crate::find_forward_address(program_id, dist_account, recipient_wallet).0,
+            false,
+        ));
     }
 
     Instruction {
@@ -333,3 +2891,278 @@ pub fn distribute(
         data,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_round_trip(instruction: DistInstruction) {
+        let packed = instruction.pack();
+        assert_eq!(DistInstruction::unpack(&packed), Ok(instruction));
+    }
+
+    #[test]
+    fn round_trips_initialize_distribution() {
+        check_round_trip(DistInstruction::InitializeDistribution {
+            seed: Pubkey::new_unique(),
+            project_name: "Wave ✓".to_string(),
+            seed_bump: 254,
+            max_recipients: 500,
+            dist_authority: Pubkey::new_unique(),
+        });
+    }
+
+    #[test]
+    fn round_trips_migrate_distribution() {
+        check_round_trip(DistInstruction::MigrateDistribution);
+    }
+
+    #[test]
+    fn rejects_truncated_initialize_distribution() {
+        let packed = DistInstruction::InitializeDistribution {
+            seed: Pubkey::new_unique(),
+            project_name: "Wave".to_string(),
+            seed_bump: 254,
+            max_recipients: 500,
+            dist_authority: Pubkey::new_unique(),
+        }
+        .pack();
+
+        for len in 0..packed.len() {
+            assert_eq!(
+                DistInstruction::unpack(&packed[..len]),
+                Err(DistError::InvalidInstruction.into())
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_set_metadata() {
+        check_round_trip(DistInstruction::SetMetadata {
+            description: "A distribution".to_string(),
+            uri: "https://example.com".to_string(),
+            creator_label: String::new(),
+            category: "airdrop".to_string(),
+        });
+    }
+
+    #[test]
+    fn round_trips_config_instructions() {
+        for instruction in [
+            DistInstruction::InitializeConfig {
+                admin: Pubkey::new_unique(),
+            },
+            DistInstruction::UpdateConfig {
+                paused: true,
+                protocol_fee_bps: 25,
+                treasury: Pubkey::new_unique(),
+            },
+            DistInstruction::SetAllowedMints { mints: vec![] },
+            DistInstruction::SetAllowedMints {
+                mints: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_distribute() {
+        for instruction in [
+            DistInstruction::Distribute { best_effort: false },
+            DistInstruction::Distribute { best_effort: true },
+            DistInstruction::DistributeToWallets { best_effort: true },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_streaming_instructions() {
+        for instruction in [
+            DistInstruction::SetReleaseRate {
+                release_rate: 1_000,
+                recipients_root: Hash::new_unique(),
+            },
+            DistInstruction::Drip { proofs: vec![] },
+            DistInstruction::Drip {
+                proofs: vec![vec![Hash::new_unique(), Hash::new_unique()], vec![]],
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_denylist_instructions() {
+        for instruction in [
+            DistInstruction::AddToDenylist {
+                wallets: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            },
+            DistInstruction::RemoveFromDenylist {
+                wallets: vec![Pubkey::new_unique()],
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_fund_distribution() {
+        for instruction in [
+            DistInstruction::FundDistribution {
+                amount: u64::MAX,
+                delegated: true,
+            },
+            DistInstruction::FundDistribution {
+                amount: 1,
+                delegated: false,
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_redemption_instructions() {
+        for instruction in [
+            DistInstruction::SetRedemption {
+                input_mint: Pubkey::new_unique(),
+                rate_numerator: 3,
+                rate_denominator: 2,
+                cap: 1_000,
+                burn_input: true,
+            },
+            DistInstruction::Redeem { amount: 5 },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_raffle_instructions() {
+        for instruction in [
+            DistInstruction::BeginDistribution {
+                num_recipients: 3,
+                raffle: None,
+            },
+            DistInstruction::BeginDistribution {
+                num_recipients: 3,
+                raffle: Some(RaffleCommit {
+                    entrants_root: Hash::new_unique(),
+                    num_entrants: 100,
+                }),
+            },
+            DistInstruction::Draw,
+            DistInstruction::ClaimRafflePrize {
+                index: 7,
+                proof: vec![Hash::new_unique(), Hash::new_unique()],
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_grant_instructions() {
+        for instruction in [
+            DistInstruction::CreateGrant {
+                recipient: Pubkey::new_unique(),
+                amount: 1_000,
+                start_epoch: 10,
+                end_epoch: 20,
+            },
+            DistInstruction::ReleaseGrant,
+            DistInstruction::RevokeGrant,
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_create_grant() {
+        let packed = DistInstruction::CreateGrant {
+            recipient: Pubkey::new_unique(),
+            amount: 1_000,
+            start_epoch: 10,
+            end_epoch: 20,
+        }
+        .pack();
+
+        assert!(DistInstruction::unpack(&packed[..packed.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn round_trips_voucher_instructions() {
+        for instruction in [
+            DistInstruction::SetAttester {
+                attester: Pubkey::new_unique(),
+            },
+            DistInstruction::ClaimWithVoucher {
+                amount: 500,
+                nonce: 1,
+                expiry: -1,
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_relay_instructions() {
+        for instruction in [
+            DistInstruction::SetRelayFeeCap { relay_fee_cap: 10 },
+            DistInstruction::RelayClaim {
+                amount: 500,
+                nonce: 2,
+                expiry: i64::MAX,
+                relayer_fee: 10,
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_decay_instructions() {
+        for instruction in [
+            DistInstruction::SetDecay {
+                grace_epochs: 4,
+                bps_per_epoch: 10_000,
+            },
+            DistInstruction::Settle,
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_set_hook() {
+        for instruction in [
+            DistInstruction::SetHook {
+                hook_program: Pubkey::new_unique(),
+                hook_accounts_len: 2,
+                failure_policy: HookFailurePolicy::Skip,
+            },
+            DistInstruction::SetHook {
+                hook_program: Pubkey::default(),
+                hook_accounts_len: 0,
+                failure_policy: HookFailurePolicy::Abort,
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+
+    #[test]
+    fn round_trips_query_instructions() {
+        for instruction in [
+            DistInstruction::GetDistributionInfo,
+            DistInstruction::IsRecipientPaid {
+                recipient: Pubkey::new_unique(),
+            },
+        ] {
+            check_round_trip(instruction);
+        }
+    }
+}
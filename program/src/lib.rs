@@ -1,8 +1,13 @@
 #![cfg_attr(not(test), forbid(unsafe_code))]
 
+pub mod cpi;
+pub mod ed25519;
 pub mod error;
+pub mod hook;
 pub mod instruction;
+pub mod merkle;
 pub mod processor;
+pub mod query;
 pub mod state;
 
 #[cfg(not(feature = "no-entrypoint"))]
@@ -10,7 +15,12 @@ mod entrypoint;
 
 // Export current sdk types for downstream users building with a different sdk version
 pub use solana_program;
-use solana_program::{entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey};
+use solana_program::{
+    entrypoint::ProgramResult,
+    hash::{hash, Hash},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
 
 solana_program::declare_id!("JAC1e5fURM1BQVZeVd8zKBDa8jWozVyfVRX5VRDx1Zet");
 
@@ -21,3 +31,154 @@ pub fn check_program_account(program_id: &Pubkey) -> ProgramResult {
     }
     Ok(())
 }
+
+/// Hashes a UTF-8 project name into the seed used for the distribution address
+pub fn project_name_seed(project_name: &str) -> Hash {
+    hash(project_name.as_bytes())
+}
+
+/// Derives the distribution account address and bump seed for a seed,
+/// project name and creator
+pub fn find_distribution_address(
+    program_id: &Pubkey,
+    seed: &Pubkey,
+    project_name: &str,
+    creator: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            seed.as_ref(),
+            project_name_seed(project_name).as_ref(),
+            creator.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the metadata account address and bump seed for a distribution
+pub fn find_metadata_address(program_id: &Pubkey, dist_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[state::METADATA_SEED, dist_account.as_ref()], program_id)
+}
+
+/// Derives the program config account address and bump seed
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[state::CONFIG_SEED], program_id)
+}
+
+/// Derives the receipt account address and bump seed for a recipient wallet
+/// of a distribution
+pub fn find_receipt_address(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    recipient: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            state::RECEIPT_SEED,
+            dist_account.as_ref(),
+            recipient.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the summary account address and bump seed of a distribution round
+pub fn find_round_address(program_id: &Pubkey, dist_account: &Pubkey, round: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            state::ROUND_SEED,
+            dist_account.as_ref(),
+            &round.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the denylist entry address and bump seed for a wallet
+pub fn find_denylist_address(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[state::DENYLIST_SEED, wallet.as_ref()], program_id)
+}
+
+/// Derives the address and bump seed of the pool item token account holding
+/// an NFT mint of an NFT batch distribution
+pub fn find_pool_item_address(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[state::POOL_ITEM_SEED, dist_account.as_ref(), mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the address and bump seed of the hook authority that signs the
+/// payout hook calls of a distribution
+pub fn find_hook_authority_address(program_id: &Pubkey, dist_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[hook::HOOK_AUTHORITY_SEED, dist_account.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the grant account address and bump seed for a recipient wallet
+/// of a distribution
+pub fn find_grant_address(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    recipient: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[state::GRANT_SEED, dist_account.as_ref(), recipient.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the address and bump seed of the token account holding the
+/// unreleased tokens of a grant
+pub fn find_grant_vault_address(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    recipient: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            state::GRANT_VAULT_SEED,
+            dist_account.as_ref(),
+            recipient.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the forward address account address and bump seed for a
+/// recipient wallet of a distribution
+pub fn find_forward_address(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    wallet: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[state::FORWARD_SEED, dist_account.as_ref(), wallet.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the address and bump seed of the account marking a voucher nonce
+/// of a recipient wallet as used
+pub fn find_voucher_address(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    recipient: &Pubkey,
+    nonce: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            state::VOUCHER_SEED,
+            dist_account.as_ref(),
+            recipient.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
@@ -0,0 +1,129 @@
+//! Merkle trees of raffle entrants and drip recipients
+
+use solana_program::{
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+};
+
+// Leaves and inner nodes are hashed with distinct prefixes so that an inner
+// node can't pass for a leaf.
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// The leaf of the entrant at `index`.
+pub fn entrant_leaf(index: u32, wallet: &Pubkey) -> Hash {
+    hashv(&[LEAF_PREFIX, &index.to_le_bytes(), wallet.as_ref()])
+}
+
+/// The leaf of a recipient wallet of a streaming distribution.
+pub fn recipient_leaf(wallet: &Pubkey) -> Hash {
+    hashv(&[LEAF_PREFIX, wallet.as_ref()])
+}
+
+// Pairs are sorted before hashing, so proofs don't need to say which side
+// each sibling is on.
+fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    if a.as_ref() <= b.as_ref() {
+        hashv(&[NODE_PREFIX, a.as_ref(), b.as_ref()])
+    } else {
+        hashv(&[NODE_PREFIX, b.as_ref(), a.as_ref()])
+    }
+}
+
+/// Checks that `proof` leads from `leaf` to `root`.
+pub fn verify_proof(root: &Hash, leaf: Hash, proof: &[Hash]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
+
+// Hashes one level of the tree into the next. An odd node out is carried up
+// as it is.
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => hash_pair(a, b),
+            [a] => *a,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// The root of the tree of `leaves`, or the default hash if there are none.
+pub fn root(leaves: &[Hash]) -> Hash {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+/// The proof of the leaf at `index`, or `None` if there is no such leaf.
+pub fn proof(leaves: &[Hash], index: usize) -> Option<Vec<Hash>> {
+    if index >= leaves.len() {
+        return None;
+    }
+
+    let mut proof = vec![];
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(wallets: &[Pubkey]) -> Vec<Hash> {
+        wallets
+            .iter()
+            .enumerate()
+            .map(|(index, wallet)| entrant_leaf(index as u32, wallet))
+            .collect()
+    }
+
+    #[test]
+    fn proves_every_leaf() {
+        for num_leaves in 1..=9 {
+            let wallets: Vec<Pubkey> = (0..num_leaves).map(|_| Pubkey::new_unique()).collect();
+            let leaves = leaves(&wallets);
+            let root = root(&leaves);
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = proof(&leaves, index).unwrap();
+                assert!(verify_proof(&root, *leaf, &proof));
+            }
+            assert_eq!(proof(&leaves, num_leaves), None);
+        }
+    }
+
+    #[test]
+    fn rejects_other_entries() {
+        let wallets: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let leaves = leaves(&wallets);
+        let root = root(&leaves);
+        let proof = proof(&leaves, 2).unwrap();
+
+        assert!(!verify_proof(&root, entrant_leaf(3, &wallets[2]), &proof));
+        assert!(!verify_proof(&root, entrant_leaf(2, &wallets[3]), &proof));
+        assert!(!verify_proof(&root, leaves[2], &proof[1..]));
+    }
+
+    #[test]
+    fn roots_of_small_trees() {
+        assert_eq!(root(&[]), Hash::default());
+
+        let leaf = entrant_leaf(0, &Pubkey::new_unique());
+        assert_eq!(root(&[leaf]), leaf);
+        assert_eq!(proof(&[leaf], 0), Some(vec![]));
+    }
+}
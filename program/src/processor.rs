@@ -1,23 +1,47 @@
 //! Program state processor
 
+use borsh::BorshSerialize;
+
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Slot,
     entrypoint::ProgramResult,
+    hash::{hashv, Hash},
+    instruction::AccountMeta,
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     program_memory::sol_memcmp,
+    program_option::COption,
     program_pack::IsInitialized,
     program_pack::Pack,
     pubkey::{Pubkey, PUBKEY_BYTES},
     system_instruction,
-    sysvar::{rent::Rent, Sysvar},
+    sysvar::{self, clock::Clock, rent::Rent, Sysvar},
+};
+
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
 };
 
 use crate::{
+    ed25519,
     error::DistError,
-    instruction::DistInstruction,
-    state::{Distribution, PdaSeed},
+    find_config_address, find_denylist_address, find_forward_address, find_grant_address,
+    find_grant_vault_address, find_hook_authority_address, find_metadata_address,
+    find_pool_item_address, find_receipt_address, find_round_address, find_voucher_address,
+    hook::{self, PayoutHook},
+    instruction::{DistInstruction, RaffleCommit},
+    merkle, project_name_seed,
+    query::{DistributionInfo, RecipientPaid},
+    state::{
+        DenylistEntry, Distribution, DistributionKind, DistributionMetadata, DistributionV1,
+        FixedString, ForwardAddress, Grant, HookFailurePolicy, PdaSeed, ProgramConfig,
+        RecipientReceipt, RoundSummary, UsedVoucher, CONFIG_SEED, GRANT_VAULT_SEED, MAX_DECAY_BPS,
+        MAX_HOOK_ACCOUNTS, MAX_PROTOCOL_FEE_BPS, MAX_RAFFLE_WINNERS, POOL_ITEM_SEED,
+        RAFFLE_DRAW_DELAY_SLOTS, ROUND_SEED, VOUCHER_SEED,
+    },
 };
 
 /// Processes a [DistInstruction](enum.DistInstruction.html).
@@ -47,23 +71,235 @@ pub fn process_instruction(
                 dist_authority,
             )
         }
-        DistInstruction::FundDistribution { amount } => {
+        DistInstruction::FundDistribution { amount, delegated } => {
             msg!("Instruction: FundDistribution");
-            process_fund_distribution(program_id, accounts, amount)
+            process_fund_distribution(program_id, accounts, amount, delegated)
         }
-        DistInstruction::SetDistAuthority {
+        DistInstruction::ProposeDistAuthority {
             ref new_dist_authority,
         } => {
-            msg!("Instruction: SetDistAuthority");
-            process_set_dist_authority(program_id, accounts, new_dist_authority)
+            msg!("Instruction: ProposeDistAuthority");
+            process_propose_dist_authority(program_id, accounts, new_dist_authority)
         }
-        DistInstruction::BeginDistribution { num_recipients } => {
+        DistInstruction::BeginDistribution {
+            num_recipients,
+            ref raffle,
+        } => {
             msg!("Instruction: BeginDistribution");
-            process_begin_distribution(program_id, accounts, num_recipients)
+            process_begin_distribution(program_id, accounts, num_recipients, raffle.as_ref())
         }
-        DistInstruction::Distribute => {
+        DistInstruction::Distribute { best_effort } => {
             msg!("Instruction: Distribute");
-            process_distribute(program_id, accounts)
+            process_distribute(program_id, accounts, best_effort)
+        }
+        DistInstruction::SetMetadata {
+            ref description,
+            ref uri,
+            ref creator_label,
+            ref category,
+        } => {
+            msg!("Instruction: SetMetadata");
+            process_set_metadata(
+                program_id,
+                accounts,
+                description,
+                uri,
+                creator_label,
+                category,
+            )
+        }
+        DistInstruction::InitializeConfig { ref admin } => {
+            msg!("Instruction: InitializeConfig");
+            process_initialize_config(program_id, accounts, admin)
+        }
+        DistInstruction::UpdateConfig {
+            paused,
+            protocol_fee_bps,
+            ref treasury,
+        } => {
+            msg!("Instruction: UpdateConfig");
+            process_update_config(program_id, accounts, paused, protocol_fee_bps, treasury)
+        }
+        DistInstruction::SetAllowedMints { ref mints } => {
+            msg!("Instruction: SetAllowedMints");
+            process_set_allowed_mints(program_id, accounts, mints)
+        }
+        DistInstruction::AcceptDistAuthority => {
+            msg!("Instruction: AcceptDistAuthority");
+            process_accept_dist_authority(program_id, accounts)
+        }
+        DistInstruction::CancelDistAuthority => {
+            msg!("Instruction: CancelDistAuthority");
+            process_cancel_dist_authority(program_id, accounts)
+        }
+        DistInstruction::DistributeToWallets { best_effort } => {
+            msg!("Instruction: DistributeToWallets");
+            process_distribute_to_wallets(program_id, accounts, best_effort)
+        }
+        DistInstruction::SetReleaseRate {
+            release_rate,
+            recipients_root,
+        } => {
+            msg!("Instruction: SetReleaseRate");
+            process_set_release_rate(program_id, accounts, release_rate, recipients_root)
+        }
+        DistInstruction::Drip { ref proofs } => {
+            msg!("Instruction: Drip");
+            process_drip(program_id, accounts, proofs)
+        }
+        DistInstruction::StartRound => {
+            msg!("Instruction: StartRound");
+            process_start_round(program_id, accounts)
+        }
+        DistInstruction::AddToDenylist { ref wallets } => {
+            msg!("Instruction: AddToDenylist");
+            process_add_to_denylist(program_id, accounts, wallets)
+        }
+        DistInstruction::RemoveFromDenylist { ref wallets } => {
+            msg!("Instruction: RemoveFromDenylist");
+            process_remove_from_denylist(program_id, accounts, wallets)
+        }
+        DistInstruction::SetMintDistribution { supply_cap } => {
+            msg!("Instruction: SetMintDistribution");
+            process_set_mint_distribution(program_id, accounts, supply_cap)
+        }
+        DistInstruction::DepositNfts => {
+            msg!("Instruction: DepositNfts");
+            process_deposit_nfts(program_id, accounts)
+        }
+        DistInstruction::SetRedemption {
+            ref input_mint,
+            rate_numerator,
+            rate_denominator,
+            cap,
+            burn_input,
+        } => {
+            msg!("Instruction: SetRedemption");
+            process_set_redemption(
+                program_id,
+                accounts,
+                input_mint,
+                rate_numerator,
+                rate_denominator,
+                cap,
+                burn_input,
+            )
+        }
+        DistInstruction::Redeem { amount } => {
+            msg!("Instruction: Redeem");
+            process_redeem(program_id, accounts, amount)
+        }
+        DistInstruction::Draw => {
+            msg!("Instruction: Draw");
+            process_draw(program_id, accounts)
+        }
+        DistInstruction::ClaimRafflePrize { index, ref proof } => {
+            msg!("Instruction: ClaimRafflePrize");
+            process_claim_raffle_prize(program_id, accounts, index, proof)
+        }
+        DistInstruction::CreateGrant {
+            ref recipient,
+            amount,
+            start_epoch,
+            end_epoch,
+        } => {
+            msg!("Instruction: CreateGrant");
+            process_create_grant(
+                program_id,
+                accounts,
+                recipient,
+                amount,
+                start_epoch,
+                end_epoch,
+            )
+        }
+        DistInstruction::ReleaseGrant => {
+            msg!("Instruction: ReleaseGrant");
+            process_release_grant(program_id, accounts)
+        }
+        DistInstruction::RevokeGrant => {
+            msg!("Instruction: RevokeGrant");
+            process_revoke_grant(program_id, accounts)
+        }
+        DistInstruction::SetForwardAddress => {
+            msg!("Instruction: SetForwardAddress");
+            process_set_forward_address(program_id, accounts)
+        }
+        DistInstruction::SetGate {
+            ref gate_mint,
+            min_balance,
+        } => {
+            msg!("Instruction: SetGate");
+            process_set_gate(program_id, accounts, gate_mint, min_balance)
+        }
+        DistInstruction::SetAttester { ref attester } => {
+            msg!("Instruction: SetAttester");
+            process_set_attester(program_id, accounts, attester)
+        }
+        DistInstruction::ClaimWithVoucher {
+            amount,
+            nonce,
+            expiry,
+        } => {
+            msg!("Instruction: ClaimWithVoucher");
+            process_claim_with_voucher(program_id, accounts, amount, nonce, expiry, None)
+        }
+        DistInstruction::SetRelayFeeCap { relay_fee_cap } => {
+            msg!("Instruction: SetRelayFeeCap");
+            process_set_relay_fee_cap(program_id, accounts, relay_fee_cap)
+        }
+        DistInstruction::RelayClaim {
+            amount,
+            nonce,
+            expiry,
+            relayer_fee,
+        } => {
+            msg!("Instruction: RelayClaim");
+            process_claim_with_voucher(
+                program_id,
+                accounts,
+                amount,
+                nonce,
+                expiry,
+                Some(relayer_fee),
+            )
+        }
+        DistInstruction::SetDecay {
+            grace_epochs,
+            bps_per_epoch,
+        } => {
+            msg!("Instruction: SetDecay");
+            process_set_decay(program_id, accounts, grace_epochs, bps_per_epoch)
+        }
+        DistInstruction::Settle => {
+            msg!("Instruction: Settle");
+            process_settle(program_id, accounts)
+        }
+        DistInstruction::SetHook {
+            hook_program,
+            hook_accounts_len,
+            failure_policy,
+        } => {
+            msg!("Instruction: SetHook");
+            process_set_hook(
+                program_id,
+                accounts,
+                &hook_program,
+                hook_accounts_len,
+                failure_policy,
+            )
+        }
+        DistInstruction::GetDistributionInfo => {
+            msg!("Instruction: GetDistributionInfo");
+            process_get_distribution_info(program_id, accounts)
+        }
+        DistInstruction::IsRecipientPaid { recipient } => {
+            msg!("Instruction: IsRecipientPaid");
+            process_is_recipient_paid(program_id, accounts, &recipient)
+        }
+        DistInstruction::MigrateDistribution => {
+            msg!("Instruction: MigrateDistribution");
+            process_migrate_distribution(program_id, accounts)
         }
     }
 }
@@ -73,7 +309,7 @@ fn process_initialize_distribution(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     seed: &Pubkey,
-    project_name: &Pubkey,
+    project_name: &str,
     seed_bump: u8,
     max_recipients: u16,
     dist_authority: &Pubkey,
@@ -90,13 +326,35 @@ fn process_initialize_distribution(
     // TODO check owner
 
     let dist_account_info = next_account_info(accounts_iter)?;
+
+    let config_info = next_account_info(accounts_iter)?;
+    let config = load_config(program_id, config_info)?;
+
     let rent = Rent::get()?;
 
     if !fee_payer_info.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let pda_seed = PdaSeed::new(*seed,*project_name, seed_bump);
+    if config.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    if !config.is_mint_allowed(token_info.key) {
+        return Err(DistError::MintNotAllowed.into());
+    }
+
+    if project_name.is_empty() {
+        return Err(DistError::InvalidProjectName.into());
+    }
+    let stored_project_name = FixedString::truncated(project_name);
+
+    let pda_seed = PdaSeed::new(
+        *seed,
+        project_name_seed(project_name),
+        *fee_payer_info.key,
+        seed_bump,
+    );
 
     let dist_account_pubkey = pda_seed.create_pubkey(program_id)?;
     if !cmp_pubkeys(dist_account_info.key, &dist_account_pubkey) {
@@ -120,7 +378,7 @@ fn process_initialize_distribution(
             fee_payer_info.clone(),
             dist_account_info.clone(),
         ],
-        &[&[pda_seed.seed.as_ref(),pda_seed.project_name.as_ref() ,&[pda_seed.bump]]],
+        &[&pda_seed.signer_seeds()],
     )?;
 
     let mut dist = Distribution::unpack_unchecked(&dist_account_info.data.borrow())?;
@@ -131,6 +389,7 @@ fn process_initialize_distribution(
 
     dist.init(
         pda_seed,
+        stored_project_name,
         *dist_authority,
         *token_info.key,
         max_recipients,
@@ -146,6 +405,7 @@ fn process_fund_distribution(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
+    delegated: bool,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
@@ -166,11 +426,17 @@ fn process_fund_distribution(
     let token_program_id = next_account_info(accounts_iter)?;
     spl_token::check_program_account(token_program_id.key)?;
 
+    let config_info = next_account_info(accounts_iter)?;
+    let config = load_config(program_id, config_info)?;
+    if config.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
     if !cmp_pubkeys(source_token_account_info.owner, token_program_id.key) {
         return Err(ProgramError::InvalidArgument);
     }
 
-    if !cmp_pubkeys(dist_token_account_info.owner, token_program_id.key) {
+    if !delegated && !cmp_pubkeys(dist_token_account_info.owner, token_program_id.key) {
         return Err(ProgramError::InvalidArgument);
     }
 
@@ -182,36 +448,101 @@ fn process_fund_distribution(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // TODO: For safety, check that dist_token_account_info matches
-    // dist_account_info's token account.
+    match dist.kind() {
+        DistributionKind::Escrow | DistributionKind::Redemption | DistributionKind::Raffle => {}
+        DistributionKind::Mint => return Err(DistError::DistributionIsMinting.into()),
+        DistributionKind::NftBatch => return Err(DistError::UnsupportedDistributionKind.into()),
+    }
 
-    let pull_tokens = spl_token::instruction::transfer(
-        token_program_id.key,
-        source_token_account_info.key,
-        dist_token_account_info.key,
-        source_account_info.key,
-        &[],
-        amount,
-    )?;
+    // A distribution is either funded in custody or by delegation, never
+    // both, as payouts come out of a single token account.
+    let funded_before = dist.funded_amount() > 0;
+    match dist.funding_source() {
+        Some(funding_source) => {
+            if !delegated {
+                return Err(DistError::FundingModeMismatch.into());
+            }
+            if !cmp_pubkeys(funding_source, source_token_account_info.key) {
+                return Err(DistError::FundingSourceMismatch.into());
+            }
+        }
+        None if delegated && funded_before => {
+            return Err(DistError::FundingModeMismatch.into());
+        }
+        None => {}
+    }
 
-    invoke(
-        &pull_tokens,
-        &[
-            token_program_id.clone(),
-            source_token_account_info.clone(),
-            dist_token_account_info.clone(),
-            source_account_info.clone(),
-        ],
-    )?;
+    if !delegated
+        && !cmp_pubkeys(
+            dist_token_account_info.key,
+            &get_associated_token_address(dist_account_info.key, dist.token()),
+        )
+    {
+        return Err(DistError::DistTokenAccountMismatch.into());
+    }
+
+    let protocol_fee = config.protocol_fee(amount);
+    if protocol_fee > 0 {
+        let treasury_token_account_info = next_account_info(accounts_iter)?;
+        if !cmp_pubkeys(treasury_token_account_info.owner, token_program_id.key) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let treasury_token_account =
+            spl_token::state::Account::unpack(&treasury_token_account_info.data.borrow())?;
+        if !cmp_pubkeys(&treasury_token_account.owner, &config.treasury)
+            || !cmp_pubkeys(&treasury_token_account.mint, dist.token())
+        {
+            return Err(DistError::InvalidTreasuryAccount.into());
+        }
+
+        transfer_tokens(
+            token_program_id,
+            source_token_account_info,
+            treasury_token_account_info,
+            source_account_info,
+            protocol_fee,
+            &[],
+        )?;
+    }
+
+    let dist_amount = amount - protocol_fee;
+
+    if delegated {
+        // The tokens stay with the funder; the distribution only records the
+        // source it may spend from as delegate.
+        check_funding_source(
+            &dist,
+            &pda_pubkey,
+            source_token_account_info,
+            dist.unpaid_amount().checked_add(dist_amount).unwrap(),
+        )?;
+        let source_token_account =
+            spl_token::state::Account::unpack(&source_token_account_info.data.borrow())?;
+        if !cmp_pubkeys(&source_token_account.owner, source_account_info.key) {
+            return Err(DistError::FundingSourceMismatch.into());
+        }
+
+        dist.set_funding_source(*source_token_account_info.key);
+    } else {
+        transfer_tokens(
+            token_program_id,
+            source_token_account_info,
+            dist_token_account_info,
+            source_account_info,
+            dist_amount,
+            &[],
+        )?;
+    }
 
-    dist.record_funded_amount(amount);
+    dist.record_funded_amount(dist_amount);
 
     Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
 
     Ok(())
 }
 
-fn process_set_dist_authority(
+fn process_propose_dist_authority(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     new_dist_authority: &Pubkey,
@@ -234,7 +565,72 @@ fn process_set_dist_authority(
         return Err(DistError::UnauthorizedDistAuthority.into());
     }
 
-    dist.set_dist_authority(*new_dist_authority);
+    if *new_dist_authority == Pubkey::default() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    dist.set_pending_dist_authority(Some(*new_dist_authority));
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_accept_dist_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let new_dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !new_dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    let pending_dist_authority = *dist
+        .pending_dist_authority()
+        .ok_or(DistError::NoPendingDistAuthority)?;
+
+    if !cmp_pubkeys(&pending_dist_authority, new_dist_authority_account_info.key) {
+        return Err(DistError::PendingDistAuthorityMismatch.into());
+    }
+
+    dist.set_dist_authority(pending_dist_authority);
+    dist.set_pending_dist_authority(None);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_cancel_dist_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if dist.pending_dist_authority().is_none() {
+        return Err(DistError::NoPendingDistAuthority.into());
+    }
+
+    dist.set_pending_dist_authority(None);
 
     Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
 
@@ -245,6 +641,7 @@ fn process_begin_distribution(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     num_recipients: u16,
+    raffle: Option<&RaffleCommit>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
@@ -268,14 +665,52 @@ fn process_begin_distribution(
         return Err(DistError::DistributionAlreadyStarted.into());
     }
 
+    if dist.funding_source().is_some() {
+        // The funder may have revoked or spent part of the delegation since
+        // funding.
+        let funding_source_info = next_account_info(accounts_iter)?;
+        let pda_pubkey = dist.pda_seed().create_pubkey(program_id)?;
+        check_funding_source(
+            &dist,
+            &pda_pubkey,
+            funding_source_info,
+            dist.unpaid_amount(),
+        )?;
+    }
+
+    // Every recipient of an NFT batch needs an NFT of its own
+    if dist.kind() == DistributionKind::NftBatch && num_recipients as u64 > dist.funded_amount() {
+        return Err(DistError::TooManyRecipients.into());
+    }
+
+    if let Some(raffle) = raffle {
+        if dist.kind() != DistributionKind::Escrow
+            || num_recipients as usize > MAX_RAFFLE_WINNERS
+            || num_recipients as u32 > raffle.num_entrants
+        {
+            return Err(DistError::InvalidRaffle.into());
+        }
+
+        dist.commit_raffle(
+            raffle.entrants_root,
+            raffle.num_entrants,
+            Clock::get()?.slot + RAFFLE_DRAW_DELAY_SLOTS,
+        );
+    }
+
     dist.set_num_recipients(num_recipients);
+    dist.set_release_start_epoch(Clock::get()?.epoch);
 
     Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
 
     Ok(())
 }
 
-fn process_distribute(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+fn process_distribute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    best_effort: bool,
+) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
     let dist_account_info = next_account_info(accounts_iter)?;
@@ -293,53 +728,3338 @@ fn process_distribute(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramR
 
     let dist_token_account_info = next_account_info(accounts_iter)?;
 
+    let config_info = next_account_info(accounts_iter)?;
+    if load_config(program_id, config_info)?.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+
     let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
 
     if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
         return Err(DistError::UnauthorizedDistAuthority.into());
     }
 
-    let recipient_share = dist.recipient_share();
+    check_payout_source(&dist, dist_account_info.key, dist_token_account_info)?;
 
-    for recipient_token_account_info in accounts_iter {
-        if dist.sent_recipients() >= dist.max_recipients() {
-            return Err(DistError::TooManyRecipients.into());
-        }
+    if matches!(
+        dist.kind(),
+        DistributionKind::Redemption | DistributionKind::Raffle
+    ) {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
 
-        if !cmp_pubkeys(recipient_token_account_info.owner, token_program_id.key) {
-            return Err(ProgramError::InvalidArgument);
-        }
+    if dist.is_streaming() {
+        return Err(DistError::DistributionIsStreaming.into());
+    }
 
-        let distribute_tokens = spl_token::instruction::transfer(
-            token_program_id.key,
-            dist_token_account_info.key,
-            recipient_token_account_info.key,
+    if dist.is_settled() {
+        return Err(DistError::DistributionSettled.into());
+    }
+
+    check_no_attester(&dist)?;
+
+    let hook = next_payout_hook(program_id, &dist, dist_account_info.key, accounts_iter)?;
+
+    // Recipients of a gated distribution also name their gate token account,
+    // and then the extra accounts of the payout hook
+    let recipient_accounts_len = 4 + dist.gate().is_some() as usize + dist.hook_accounts_len();
+    let recipient_accounts_chunks = accounts_iter
+        .as_slice()
+        .chunks_exact(recipient_accounts_len);
+    if !recipient_accounts_chunks.remainder().is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut payouts = Vec::with_capacity(recipient_accounts_chunks.len());
+    for (index, recipient_accounts) in recipient_accounts_chunks.enumerate() {
+        check_recipient(
+            program_id,
+            &dist,
             dist_account_info.key,
-            &[],
-            recipient_share,
+            token_program_id,
+            recipient_accounts,
+            index,
+            best_effort,
+            &mut payouts,
         )?;
+    }
 
-        invoke_signed(
-            &distribute_tokens,
-            &[
-                token_program_id.clone(),
-                dist_token_account_info.clone(),
-                recipient_token_account_info.clone(),
-                dist_account_info.clone(),
-            ],
-            &[&[dist.data.pda_seed.seed.as_ref(),dist.data.pda_seed.project_name.as_ref() , &[dist.data.pda_seed.bump]]],
+    for payout in payouts {
+        let (recipient, recipient_token_account_info, hook_accounts) = (
+            payout.recipient,
+            payout.token_account_info,
+            payout.hook_accounts,
+        );
+
+        let amount = pay_recipient(
+            program_id,
+            &mut dist,
+            token_program_id,
+            system_program_info,
+            payer_info,
+            dist_account_info,
+            dist_token_account_info,
+            payout,
         )?;
 
-        dist.record_sent_recipient(*recipient_token_account_info.key);
+        if let Some((hook_program_info, hook_authority_info, hook_authority_bump)) = hook {
+            call_payout_hook(
+                &dist,
+                hook_program_info,
+                hook_authority_info,
+                hook_authority_bump,
+                dist_account_info,
+                recipient_token_account_info,
+                hook_accounts,
+                recipient,
+                amount,
+            )?;
+        }
     }
 
-    if dist.sent_recipients() >= dist.max_recipients() {}
-
     Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
 
     Ok(())
 }
 
+fn process_distribute_to_wallets(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    best_effort: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let dist_token_account_info = next_account_info(accounts_iter)?;
+
+    let config_info = next_account_info(accounts_iter)?;
+    if load_config(program_id, config_info)?.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mint_info = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let associated_token_program_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(
+        associated_token_program_info.key,
+        &spl_associated_token_account::id(),
+    ) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    check_payout_source(&dist, dist_account_info.key, dist_token_account_info)?;
+
+    if matches!(
+        dist.kind(),
+        DistributionKind::NftBatch | DistributionKind::Redemption | DistributionKind::Raffle
+    ) {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    if !cmp_pubkeys(mint_info.key, dist.token()) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if dist.is_streaming() {
+        return Err(DistError::DistributionIsStreaming.into());
+    }
+
+    if dist.is_settled() {
+        return Err(DistError::DistributionSettled.into());
+    }
+
+    check_no_attester(&dist)?;
+
+    let hook = next_payout_hook(program_id, &dist, dist_account_info.key, accounts_iter)?;
+
+    // As for `Distribute`, followed by the extra accounts of the payout hook
+    let recipient_accounts_len = 5 + dist.gate().is_some() as usize + dist.hook_accounts_len();
+    let recipient_accounts_chunks = accounts_iter
+        .as_slice()
+        .chunks_exact(recipient_accounts_len);
+    if !recipient_accounts_chunks.remainder().is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut payouts = Vec::with_capacity(recipient_accounts_chunks.len());
+    for (index, recipient_accounts) in recipient_accounts_chunks.enumerate() {
+        let recipient_wallet_info = &recipient_accounts[0];
+        let recipient_token_account_info = &recipient_accounts[1];
+        let forward_info = &recipient_accounts[4];
+
+        // A forward address is checked with the other recipient accounts,
+        // once it's known to be the wallet's own
+        let recipient_token_account =
+            get_associated_token_address(recipient_wallet_info.key, mint_info.key);
+        if forward_info.data_is_empty() {
+            if !cmp_pubkeys(recipient_token_account_info.key, &recipient_token_account) {
+                return Err(ProgramError::InvalidSeeds);
+            }
+        } else {
+            let forward = ForwardAddress::unpack(&forward_info.data.borrow())
+                .map_err(|_| DistError::InvalidForwardAccount)?;
+            if !cmp_pubkeys(&forward.wallet, recipient_wallet_info.key) {
+                return Err(DistError::InvalidForwardAccount.into());
+            }
+        }
+
+        if forward_info.data_is_empty() && recipient_token_account_info.data_is_empty() {
+            // The associated token program reads rent with `Rent::get`, so
+            // this form of the instruction takes no rent sysvar account.
+            let create_token_account = create_associated_token_account(
+                payer_info.key,
+                recipient_wallet_info.key,
+                mint_info.key,
+            );
+
+            invoke(
+                &create_token_account,
+                &[
+                    payer_info.clone(),
+                    recipient_token_account_info.clone(),
+                    recipient_wallet_info.clone(),
+                    mint_info.clone(),
+                    system_program_info.clone(),
+                    token_program_id.clone(),
+                    associated_token_program_info.clone(),
+                ],
+            )?;
+        }
+
+        check_recipient(
+            program_id,
+            &dist,
+            dist_account_info.key,
+            token_program_id,
+            &recipient_accounts[1..],
+            index,
+            best_effort,
+            &mut payouts,
+        )?;
+    }
+
+    for payout in payouts {
+        let (recipient, recipient_token_account_info, hook_accounts) = (
+            payout.recipient,
+            payout.token_account_info,
+            payout.hook_accounts,
+        );
+
+        let amount = pay_recipient(
+            program_id,
+            &mut dist,
+            token_program_id,
+            system_program_info,
+            payer_info,
+            dist_account_info,
+            dist_token_account_info,
+            payout,
+        )?;
+
+        if let Some((hook_program_info, hook_authority_info, hook_authority_bump)) = hook {
+            call_payout_hook(
+                &dist,
+                hook_program_info,
+                hook_authority_info,
+                hook_authority_bump,
+                dist_account_info,
+                recipient_token_account_info,
+                hook_accounts,
+                recipient,
+                amount,
+            )?;
+        }
+    }
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// A recipient that passed its checks and is waiting to be paid.
+struct Payout<'b, 'a> {
+    token_account_info: &'b AccountInfo<'a>,
+    receipt_info: &'b AccountInfo<'a>,
+    pool_item_info: Option<&'b AccountInfo<'a>>,
+    hook_accounts: &'b [AccountInfo<'a>],
+    recipient: Pubkey,
+    receipt_bump: u8,
+}
+
+/// Checks the recipient at `index` and queues it for payment. The recipient
+/// accounts are its token account, receipt account and denylist entry,
+/// followed by its forward address account, or the pool item it gets for an
+/// NFT batch, its gate token account for a gated distribution and any extra
+/// accounts for the payout hook. A recipient that fails its checks is
+/// skipped in best-effort mode and fails the instruction otherwise.
+#[allow(clippy::too_many_arguments)]
+fn check_recipient<'b, 'a>(
+    program_id: &Pubkey,
+    dist: &Distribution,
+    dist_account: &Pubkey,
+    token_program_id: &AccountInfo,
+    recipient_accounts: &'b [AccountInfo<'a>],
+    index: usize,
+    best_effort: bool,
+    payouts: &mut Vec<Payout<'b, 'a>>,
+) -> ProgramResult {
+    let recipient_token_account_info = &recipient_accounts[0];
+    let receipt_info = &recipient_accounts[1];
+    let denylist_entry_info = &recipient_accounts[2];
+    let (pool_item_info, forward_info) = match dist.kind() {
+        DistributionKind::NftBatch => (Some(&recipient_accounts[3]), None),
+        _ => (None, Some(&recipient_accounts[3])),
+    };
+    let gate_info = recipient_accounts.get(4);
+    let hook_accounts = &recipient_accounts[4 + dist.gate().is_some() as usize..];
+
+    let mint = match pool_item_info {
+        Some(pool_item_info) => {
+            check_pool_item(dist_account, token_program_id, pool_item_info, payouts)
+        }
+        None => Ok(*dist.token()),
+    };
+
+    let checked = mint
+        .and_then(|mint| {
+            check_recipient_account(&mint, token_program_id, recipient_token_account_info)
+        })
+        .and_then(|owner| match forward_info {
+            Some(forward_info) => check_forward_address(
+                program_id,
+                dist_account,
+                recipient_token_account_info,
+                &owner,
+                forward_info,
+            ),
+            None => Ok(owner),
+        })
+        .and_then(|recipient| {
+            check_denylist(program_id, denylist_entry_info, &recipient)?;
+            check_gate(dist, gate_info, &recipient)?;
+
+            let (receipt, receipt_bump) =
+                find_receipt_address(program_id, dist_account, &recipient);
+            if !cmp_pubkeys(receipt_info.key, &receipt) {
+                return Err(DistError::InvalidReceiptAccount);
+            }
+
+            let paid_in_round = matches!(
+                load_receipt(program_id, receipt_info)?,
+                Some(receipt) if receipt.round == dist.round()
+            );
+            let paid_in_batch = payouts
+                .iter()
+                .any(|payout| cmp_pubkeys(&payout.recipient, &recipient));
+            if paid_in_round || paid_in_batch {
+                return Err(DistError::RecipientAlreadyPaid);
+            }
+
+            Ok(Payout {
+                token_account_info: recipient_token_account_info,
+                receipt_info,
+                pool_item_info,
+                hook_accounts,
+                recipient,
+                receipt_bump,
+            })
+        });
+
+    match checked {
+        Ok(payout) => payouts.push(payout),
+        Err(error) if best_effort => {
+            msg!(
+                "Skip: index={} account={} reason={:?}",
+                index,
+                recipient_token_account_info.key,
+                error
+            );
+        }
+        Err(error) => return Err(error.into()),
+    }
+
+    Ok(())
+}
+
+/// Checks that a pool item still holds its NFT and isn't already queued for
+/// another recipient, and returns the NFT mint.
+fn check_pool_item(
+    dist_account: &Pubkey,
+    token_program_id: &AccountInfo,
+    pool_item_info: &AccountInfo,
+    payouts: &[Payout],
+) -> Result<Pubkey, DistError> {
+    if !cmp_pubkeys(pool_item_info.owner, token_program_id.key) {
+        return Err(DistError::InvalidPoolItemAccount);
+    }
+
+    let pool_item = spl_token::state::Account::unpack(&pool_item_info.data.borrow())
+        .map_err(|_| DistError::InvalidPoolItemAccount)?;
+
+    // Only pool items are held by the distribution itself
+    if !cmp_pubkeys(&pool_item.owner, dist_account) || pool_item.amount != 1 {
+        return Err(DistError::InvalidPoolItemAccount);
+    }
+
+    let queued = payouts.iter().any(|payout| {
+        matches!(
+            payout.pool_item_info,
+            Some(queued) if cmp_pubkeys(queued.key, pool_item_info.key)
+        )
+    });
+    if queued {
+        return Err(DistError::InvalidPoolItemAccount);
+    }
+
+    Ok(pool_item.mint)
+}
+
+/// Returns the recipient wallet a token account is paid for: the wallet that
+/// set it as its forward address, or else the token account's owner, as long
+/// as the owner hasn't set a forward address of its own.
+fn check_forward_address(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    recipient_token_account_info: &AccountInfo,
+    owner: &Pubkey,
+    forward_info: &AccountInfo,
+) -> Result<Pubkey, DistError> {
+    if forward_info.data_is_empty() {
+        let (forward_pubkey, _) = find_forward_address(program_id, dist_account, owner);
+        if !cmp_pubkeys(forward_info.key, &forward_pubkey) {
+            return Err(DistError::InvalidForwardAccount);
+        }
+        return Ok(*owner);
+    }
+
+    if !cmp_pubkeys(program_id, forward_info.owner) {
+        return Err(DistError::InvalidForwardAccount);
+    }
+
+    let forward = ForwardAddress::unpack(&forward_info.data.borrow())
+        .map_err(|_| DistError::InvalidForwardAccount)?;
+
+    let (forward_pubkey, _) = find_forward_address(program_id, dist_account, &forward.wallet);
+    if !cmp_pubkeys(forward_info.key, &forward_pubkey) {
+        return Err(DistError::InvalidForwardAccount);
+    }
+
+    if !cmp_pubkeys(recipient_token_account_info.key, &forward.destination) {
+        return Err(DistError::ForwardAddressMismatch);
+    }
+
+    Ok(forward.wallet)
+}
+
+/// Checks that a recipient wallet holds enough of the gate mint, if the
+/// distribution is gated.
+fn check_gate(
+    dist: &Distribution,
+    gate_info: Option<&AccountInfo>,
+    wallet: &Pubkey,
+) -> Result<(), DistError> {
+    let (gate_mint, min_balance) = match dist.gate() {
+        Some(gate) => gate,
+        None => return Ok(()),
+    };
+
+    let gate_info = gate_info.ok_or(DistError::InvalidGateAccount)?;
+    if !cmp_pubkeys(gate_info.owner, &spl_token::id()) {
+        return Err(DistError::InvalidGateAccount);
+    }
+
+    let gate_account = spl_token::state::Account::unpack(&gate_info.data.borrow())
+        .map_err(|_| DistError::InvalidGateAccount)?;
+    if !cmp_pubkeys(&gate_account.mint, gate_mint) || !cmp_pubkeys(&gate_account.owner, wallet) {
+        return Err(DistError::InvalidGateAccount);
+    }
+
+    if gate_account.amount < min_balance {
+        msg!(
+            "Recipient {} holds {} of gate mint {}",
+            wallet,
+            gate_account.amount,
+            gate_mint
+        );
+        return Err(DistError::InsufficientGateBalance);
+    }
+
+    Ok(())
+}
+
+/// Fails if the wallet's denylist entry exists.
+fn check_denylist(
+    program_id: &Pubkey,
+    denylist_entry_info: &AccountInfo,
+    wallet: &Pubkey,
+) -> Result<(), DistError> {
+    let (denylist_entry, _) = find_denylist_address(program_id, wallet);
+    if !cmp_pubkeys(denylist_entry_info.key, &denylist_entry) {
+        return Err(DistError::InvalidDenylistAccount);
+    }
+
+    if denylist_entry_info.data_is_empty() || !cmp_pubkeys(program_id, denylist_entry_info.owner) {
+        return Ok(());
+    }
+
+    let entry = DenylistEntry::unpack_from_slice(&denylist_entry_info.data.borrow())
+        .map_err(|_| DistError::InvalidDenylistAccount)?;
+    if entry.is_initialized() {
+        return Err(DistError::RecipientDenylisted);
+    }
+
+    Ok(())
+}
+
+/// Loads a recipient's receipt, which is `None` if the recipient has never
+/// been paid by the distribution.
+fn load_receipt(
+    program_id: &Pubkey,
+    receipt_info: &AccountInfo,
+) -> Result<Option<RecipientReceipt>, DistError> {
+    if receipt_info.data_is_empty() {
+        return Ok(None);
+    }
+
+    if !cmp_pubkeys(program_id, receipt_info.owner) {
+        return Err(DistError::InvalidReceiptAccount);
+    }
+
+    RecipientReceipt::unpack(&receipt_info.data.borrow())
+        .map(Some)
+        .map_err(|_| DistError::InvalidReceiptAccount)
+}
+
+/// Loads a grant of the distribution and checks that `grant_vault_info` is
+/// its vault.
+fn load_grant(
+    program_id: &Pubkey,
+    dist_account: &Pubkey,
+    grant_info: &AccountInfo,
+    grant_vault_info: &AccountInfo,
+) -> Result<Grant, DistError> {
+    if !cmp_pubkeys(program_id, grant_info.owner) {
+        return Err(DistError::InvalidGrantAccount);
+    }
+
+    let grant =
+        Grant::unpack(&grant_info.data.borrow()).map_err(|_| DistError::InvalidGrantAccount)?;
+    if !cmp_pubkeys(&grant.distribution, dist_account) {
+        return Err(DistError::InvalidGrantAccount);
+    }
+
+    let (grant_vault_pubkey, _) =
+        find_grant_vault_address(program_id, dist_account, &grant.recipient);
+    if !cmp_pubkeys(grant_vault_info.key, &grant_vault_pubkey) {
+        return Err(DistError::InvalidGrantAccount);
+    }
+
+    Ok(grant)
+}
+
+/// Checks that a recipient token account can receive `mint` and returns the
+/// wallet that owns it.
+fn check_recipient_account(
+    mint: &Pubkey,
+    token_program_id: &AccountInfo,
+    recipient_token_account_info: &AccountInfo,
+) -> Result<Pubkey, DistError> {
+    if !cmp_pubkeys(recipient_token_account_info.owner, token_program_id.key) {
+        return Err(DistError::InvalidRecipientAccount);
+    }
+
+    let recipient_token_account =
+        spl_token::state::Account::unpack(&recipient_token_account_info.data.borrow())
+            .map_err(|_| DistError::InvalidRecipientAccount)?;
+
+    if !cmp_pubkeys(&recipient_token_account.mint, mint) {
+        msg!(
+            "Recipient {} holds mint {}",
+            recipient_token_account_info.key,
+            recipient_token_account.mint
+        );
+        return Err(DistError::RecipientMintMismatch);
+    }
+
+    if recipient_token_account.is_frozen() {
+        msg!("Recipient {} is frozen", recipient_token_account_info.key);
+        return Err(DistError::RecipientAccountFrozen);
+    }
+
+    Ok(recipient_token_account.owner)
+}
+
+/// Checks that payouts come out of the funder's token account when the
+/// distribution is funded by delegation, the mint for a mint distribution,
+/// or else the distribution's associated token account.
+fn check_payout_source(
+    dist: &Distribution,
+    dist_pubkey: &Pubkey,
+    dist_token_account_info: &AccountInfo,
+) -> Result<(), DistError> {
+    match dist.kind() {
+        DistributionKind::Escrow | DistributionKind::Redemption | DistributionKind::Raffle => {}
+        DistributionKind::Mint => {
+            if !cmp_pubkeys(dist.token(), dist_token_account_info.key) {
+                return Err(DistError::DistributionMintMismatch);
+            }
+            return Ok(());
+        }
+        // Each recipient names the pool item it's paid out of
+        DistributionKind::NftBatch => return Ok(()),
+    }
+
+    match dist.funding_source() {
+        Some(funding_source) if !cmp_pubkeys(funding_source, dist_token_account_info.key) => {
+            Err(DistError::FundingSourceMismatch)
+        }
+        Some(_) => Ok(()),
+        // Other token accounts the distribution owns, like grant vaults,
+        // hold tokens set aside from its funds
+        None => {
+            let dist_token_account = get_associated_token_address(dist_pubkey, dist.token());
+            if !cmp_pubkeys(dist_token_account_info.key, &dist_token_account) {
+                return Err(DistError::DistTokenAccountMismatch);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Checks that a funder token account delegates at least `required` tokens
+/// of the distribution token to the distribution.
+fn check_funding_source(
+    dist: &Distribution,
+    dist_pubkey: &Pubkey,
+    funding_source_info: &AccountInfo,
+    required: u64,
+) -> Result<(), DistError> {
+    if !cmp_pubkeys(funding_source_info.owner, &spl_token::id()) {
+        return Err(DistError::FundingSourceMismatch);
+    }
+
+    let funding_source = spl_token::state::Account::unpack(&funding_source_info.data.borrow())
+        .map_err(|_| DistError::FundingSourceMismatch)?;
+
+    if let Some(funding_source_pubkey) = dist.funding_source() {
+        if !cmp_pubkeys(funding_source_pubkey, funding_source_info.key) {
+            return Err(DistError::FundingSourceMismatch);
+        }
+    }
+
+    if !cmp_pubkeys(&funding_source.mint, dist.token()) {
+        return Err(DistError::FundingSourceMismatch);
+    }
+
+    if funding_source.is_frozen() {
+        return Err(DistError::InsufficientDelegation);
+    }
+
+    let delegated_amount = match funding_source.delegate {
+        COption::Some(delegate) if cmp_pubkeys(&delegate, dist_pubkey) => {
+            funding_source.delegated_amount
+        }
+        _ => 0,
+    };
+
+    if delegated_amount < required {
+        msg!(
+            "Funding source {} delegates {} of {}",
+            funding_source_info.key,
+            delegated_amount,
+            required
+        );
+        return Err(DistError::InsufficientDelegation);
+    }
+
+    Ok(())
+}
+
+/// Sends one recipient share out of the distribution token account and
+/// writes the recipient's receipt. Returns the amount paid.
+#[allow(clippy::too_many_arguments)]
+fn pay_recipient<'a>(
+    program_id: &Pubkey,
+    dist: &mut Distribution,
+    token_program_id: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    dist_account_info: &AccountInfo<'a>,
+    dist_token_account_info: &AccountInfo<'a>,
+    payout: Payout<'_, 'a>,
+) -> Result<u64, ProgramError> {
+    if dist.sent_recipients() >= dist.max_recipients() {
+        return Err(DistError::TooManyRecipients.into());
+    }
+
+    let amount = dist.decayed_share(Clock::get()?.epoch);
+    let forfeited_amount = dist.recipient_share() - amount;
+    if forfeited_amount > 0 {
+        msg!(
+            "Recipient {} forfeits {} to decay",
+            payout.recipient,
+            forfeited_amount
+        );
+    }
+
+    pay_out_tokens(
+        dist,
+        token_program_id,
+        payout.pool_item_info.unwrap_or(dist_token_account_info),
+        payout.token_account_info,
+        dist_account_info,
+        amount,
+    )?;
+
+    // The emptied pool item's rent goes back to the payer
+    if let Some(pool_item_info) = payout.pool_item_info {
+        close_token_account(
+            token_program_id,
+            pool_item_info,
+            payer_info,
+            dist_account_info,
+            &[&dist.pda_seed().signer_seeds()],
+        )?;
+    }
+
+    let mut receipt = RecipientReceipt::default();
+    receipt.init(
+        *dist_account_info.key,
+        payout.recipient,
+        payout.receipt_bump,
+        dist.round(),
+        amount,
+    );
+
+    // Recipients paid in an earlier round already have a receipt
+    if payout.receipt_info.data_is_empty() {
+        create_receipt_account(
+            program_id,
+            system_program_info,
+            payer_info,
+            payout.receipt_info,
+            &receipt,
+        )?;
+    }
+
+    RecipientReceipt::pack(receipt, &mut payout.receipt_info.data.borrow_mut())?;
+
+    dist.record_sent_recipient(*payout.token_account_info.key);
+    dist.record_paid_amount(amount);
+    dist.record_forfeited_amount(forfeited_amount);
+
+    Ok(amount)
+}
+
+/// Reads the payout hook program and hook authority accounts, if the
+/// distribution has a payout hook, and returns them with the hook authority
+/// bump seed.
+#[allow(clippy::type_complexity)]
+fn next_payout_hook<'b, 'a>(
+    program_id: &Pubkey,
+    dist: &Distribution,
+    dist_account: &Pubkey,
+    accounts_iter: &mut std::slice::Iter<'b, AccountInfo<'a>>,
+) -> Result<Option<(&'b AccountInfo<'a>, &'b AccountInfo<'a>, u8)>, ProgramError> {
+    let hook_program = match dist.hook_program() {
+        Some(hook_program) => hook_program,
+        None => return Ok(None),
+    };
+
+    let hook_program_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(hook_program_info.key, hook_program) {
+        return Err(DistError::InvalidHookProgram.into());
+    }
+
+    let hook_authority_info = next_account_info(accounts_iter)?;
+    let (hook_authority, hook_authority_bump) =
+        find_hook_authority_address(program_id, dist_account);
+    if !cmp_pubkeys(hook_authority_info.key, &hook_authority) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(Some((
+        hook_program_info,
+        hook_authority_info,
+        hook_authority_bump,
+    )))
+}
+
+/// Fails payout instructions that don't call the payout hook, so that no
+/// recipient of a distribution with a hook is paid without it.
+fn check_no_payout_hook(dist: &Distribution) -> Result<(), DistError> {
+    if let Some(hook_program) = dist.hook_program() {
+        msg!(
+            "Payout hook {} is only called by Distribute and DistributeToWallets",
+            hook_program
+        );
+        return Err(DistError::HookUnavailable);
+    }
+
+    Ok(())
+}
+
+/// Fails for a distribution paying out vouchers, as the equal shares of
+/// `Distribute` and `Drip` don't account for what vouchers have paid.
+fn check_no_attester(dist: &Distribution) -> Result<(), DistError> {
+    if dist.attester().is_some() {
+        msg!("Distribution pays out vouchers, not equal shares");
+        return Err(DistError::SharesAndVouchersMixed);
+    }
+
+    Ok(())
+}
+
+/// Calls the payout hook of a distribution about a recipient it paid, see
+/// [hook](../hook/index.html).
+#[allow(clippy::too_many_arguments)]
+fn call_payout_hook<'a>(
+    dist: &Distribution,
+    hook_program_info: &AccountInfo<'a>,
+    hook_authority_info: &AccountInfo<'a>,
+    hook_authority_bump: u8,
+    dist_account_info: &AccountInfo<'a>,
+    recipient_token_account_info: &AccountInfo<'a>,
+    hook_accounts: &[AccountInfo<'a>],
+    recipient: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    if !hook_program_info.executable {
+        return match dist.hook_failure_policy() {
+            HookFailurePolicy::Abort => Err(DistError::HookUnavailable.into()),
+            HookFailurePolicy::Skip => {
+                msg!(
+                    "Hook {} unavailable, not called for recipient {}",
+                    hook_program_info.key,
+                    recipient
+                );
+                Ok(())
+            }
+        };
+    }
+
+    let extra_accounts: Vec<AccountMeta> = hook_accounts
+        .iter()
+        .map(|account_info| AccountMeta {
+            pubkey: *account_info.key,
+            is_signer: account_info.is_signer,
+            is_writable: account_info.is_writable,
+        })
+        .collect();
+    let payout_hook = hook::payout_hook(
+        hook_program_info.key,
+        hook_authority_info.key,
+        recipient_token_account_info.key,
+        &PayoutHook {
+            distribution: *dist_account_info.key,
+            recipient,
+            amount,
+        },
+        &extra_accounts,
+    );
+
+    let mut account_infos = Vec::with_capacity(4 + hook_accounts.len());
+    account_infos.push(hook_authority_info.clone());
+    account_infos.push(dist_account_info.clone());
+    account_infos.push(recipient_token_account_info.clone());
+    account_infos.extend_from_slice(hook_accounts);
+    account_infos.push(hook_program_info.clone());
+
+    // The hook authority signs, so the hook knows who is calling. The
+    // distribution itself never signs, as it holds the tokens
+    invoke_signed(
+        &payout_hook,
+        &account_infos,
+        &[&[
+            hook::HOOK_AUTHORITY_SEED,
+            dist_account_info.key.as_ref(),
+            &[hook_authority_bump],
+        ]],
+    )
+}
+
+/// Sends `amount` of the distribution token to a recipient, minting it for a
+/// mint distribution and transferring it out of `source_info` otherwise.
+fn pay_out_tokens<'a>(
+    dist: &Distribution,
+    token_program_id: &AccountInfo<'a>,
+    source_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    dist_account_info: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let signer_seeds = dist.pda_seed().signer_seeds();
+
+    match dist.kind() {
+        DistributionKind::Mint => mint_tokens(
+            token_program_id,
+            source_info,
+            destination_info,
+            dist_account_info,
+            amount,
+            &[&signer_seeds],
+        ),
+        DistributionKind::Escrow
+        | DistributionKind::NftBatch
+        | DistributionKind::Redemption
+        | DistributionKind::Raffle => transfer_tokens(
+            token_program_id,
+            source_info,
+            destination_info,
+            dist_account_info,
+            amount,
+            &[&signer_seeds],
+        ),
+    }
+}
+
+fn create_receipt_account<'a>(
+    program_id: &Pubkey,
+    system_program_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    receipt_info: &AccountInfo<'a>,
+    receipt: &RecipientReceipt,
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let state_size = RecipientReceipt::LEN;
+
+    let create_receipt_account = system_instruction::create_account(
+        payer_info.key,
+        receipt_info.key,
+        rent.minimum_balance(state_size),
+        state_size as u64,
+        program_id,
+    );
+
+    invoke_signed(
+        &create_receipt_account,
+        &[
+            system_program_info.clone(),
+            payer_info.clone(),
+            receipt_info.clone(),
+        ],
+        &[&receipt.signer_seeds()],
+    )
+}
+
+fn process_set_release_rate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    release_rate: u64,
+    recipients_root: Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if dist.has_started() {
+        return Err(DistError::DistributionAlreadyStarted.into());
+    }
+
+    // A stream only drips to the wallets committed to here
+    if release_rate > 0 && recipients_root == Hash::default() {
+        msg!("Streaming distributions need a recipients root");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    dist.set_release_rate(release_rate);
+    dist.set_recipients_root(recipients_root);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_mint_distribution(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    supply_cap: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mint_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(mint_info.owner, &spl_token::id()) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if dist.has_started() {
+        return Err(DistError::DistributionAlreadyStarted.into());
+    }
+
+    if dist.kind() != DistributionKind::Escrow
+        || dist.funded_amount() > 0
+        || dist.funding_source().is_some()
+    {
+        return Err(DistError::DistributionAlreadyFunded.into());
+    }
+
+    if !cmp_pubkeys(dist.token(), mint_info.key) {
+        return Err(DistError::DistributionMintMismatch.into());
+    }
+
+    let mint = spl_token::state::Mint::unpack(&mint_info.data.borrow())?;
+    let is_mint_authority = match mint.mint_authority {
+        COption::Some(mint_authority) => cmp_pubkeys(&mint_authority, dist_account_info.key),
+        COption::None => false,
+    };
+    if !is_mint_authority {
+        return Err(DistError::InvalidMintAuthority.into());
+    }
+
+    if supply_cap == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    dist.set_mint_supply_cap(supply_cap);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_deposit_nfts(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_info = next_account_info(accounts_iter)?;
+    if !dist_authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let owner_info = next_account_info(accounts_iter)?;
+    if !owner_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+    let rent_info = next_account_info(accounts_iter)?;
+    let rent = Rent::from_account_info(rent_info)?;
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if dist.has_started() {
+        return Err(DistError::DistributionAlreadyStarted.into());
+    }
+
+    match dist.kind() {
+        DistributionKind::NftBatch => {}
+        DistributionKind::Escrow
+            if dist.funded_amount() == 0 && dist.funding_source().is_none() =>
+        {
+            dist.set_nft_batch();
+        }
+        _ => return Err(DistError::DistributionAlreadyFunded.into()),
+    }
+
+    let nft_accounts_chunks = accounts_iter.as_slice().chunks_exact(3);
+    if !nft_accounts_chunks.remainder().is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    for nft_accounts in nft_accounts_chunks {
+        let mint_info = &nft_accounts[0];
+        let source_token_account_info = &nft_accounts[1];
+        let pool_item_info = &nft_accounts[2];
+
+        if !cmp_pubkeys(mint_info.owner, token_program_id.key) {
+            return Err(DistError::InvalidNftMint.into());
+        }
+
+        // Only a mint that can never mint more, nor freeze the pooled
+        // token, holds a single NFT for good
+        let mint = spl_token::state::Mint::unpack(&mint_info.data.borrow())?;
+        if mint.decimals != 0
+            || mint.supply != 1
+            || mint.mint_authority.is_some()
+            || mint.freeze_authority.is_some()
+        {
+            return Err(DistError::InvalidNftMint.into());
+        }
+
+        let (pool_item_pubkey, pool_item_bump) =
+            find_pool_item_address(program_id, dist_account_info.key, mint_info.key);
+        if !cmp_pubkeys(pool_item_info.key, &pool_item_pubkey) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if !pool_item_info.data_is_empty() {
+            return Err(DistError::AlreadyInitialized.into());
+        }
+
+        let create_pool_item_account = system_instruction::create_account(
+            payer_info.key,
+            pool_item_info.key,
+            rent.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            token_program_id.key,
+        );
+
+        invoke_signed(
+            &create_pool_item_account,
+            &[
+                system_program_info.clone(),
+                payer_info.clone(),
+                pool_item_info.clone(),
+            ],
+            &[&[
+                POOL_ITEM_SEED,
+                dist_account_info.key.as_ref(),
+                mint_info.key.as_ref(),
+                &[pool_item_bump],
+            ]],
+        )?;
+
+        let initialize_account = spl_token::instruction::initialize_account(
+            token_program_id.key,
+            pool_item_info.key,
+            mint_info.key,
+            dist_account_info.key,
+        )?;
+
+        invoke(
+            &initialize_account,
+            &[
+                token_program_id.clone(),
+                pool_item_info.clone(),
+                mint_info.clone(),
+                dist_account_info.clone(),
+                rent_info.clone(),
+            ],
+        )?;
+
+        transfer_tokens(
+            token_program_id,
+            source_token_account_info,
+            pool_item_info,
+            owner_info,
+            1,
+            &[],
+        )?;
+
+        dist.record_funded_amount(1);
+    }
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_redemption(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input_mint: &Pubkey,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    cap: u64,
+    burn_input: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let input_mint_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(input_mint_info.key, input_mint) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let input_vault_info = next_account_info(accounts_iter)?;
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let associated_token_program_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(
+        associated_token_program_info.key,
+        &spl_associated_token_account::id(),
+    ) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    // Redeemers rely on the terms not changing under them
+    if dist.has_started() || dist.paid_amount() > 0 {
+        return Err(DistError::DistributionAlreadyStarted.into());
+    }
+
+    if !matches!(
+        dist.kind(),
+        DistributionKind::Escrow | DistributionKind::Redemption
+    ) {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    // A cap of zero would take no redemptions at all
+    if rate_numerator == 0 || rate_denominator == 0 || cap == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Kept input tokens go to the vault, which must exist before the first
+    // redemption
+    if !burn_input {
+        let input_vault = get_associated_token_address(dist_account_info.key, input_mint);
+        if !cmp_pubkeys(input_vault_info.key, &input_vault) {
+            return Err(DistError::InvalidRedemptionVault.into());
+        }
+
+        if input_vault_info.data_is_empty() {
+            let create_input_vault =
+                create_associated_token_account(payer_info.key, dist_account_info.key, input_mint);
+
+            invoke(
+                &create_input_vault,
+                &[
+                    payer_info.clone(),
+                    input_vault_info.clone(),
+                    dist_account_info.clone(),
+                    input_mint_info.clone(),
+                    system_program_info.clone(),
+                    token_program_id.clone(),
+                    associated_token_program_info.clone(),
+                ],
+            )?;
+        }
+    }
+
+    dist.set_redemption(
+        *input_mint,
+        rate_numerator,
+        rate_denominator,
+        cap,
+        burn_input,
+    );
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_redeem(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let redeemer_info = next_account_info(accounts_iter)?;
+    if !redeemer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let dist_token_account_info = next_account_info(accounts_iter)?;
+
+    let config_info = next_account_info(accounts_iter)?;
+    if load_config(program_id, config_info)?.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    let input_mint_info = next_account_info(accounts_iter)?;
+    let input_token_account_info = next_account_info(accounts_iter)?;
+    let input_vault_info = next_account_info(accounts_iter)?;
+    let output_token_account_info = next_account_info(accounts_iter)?;
+    let denylist_entry_info = next_account_info(accounts_iter)?;
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if dist.kind() != DistributionKind::Redemption {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    check_payout_source(&dist, dist_account_info.key, dist_token_account_info)?;
+    check_no_payout_hook(&dist)?;
+
+    if !cmp_pubkeys(input_mint_info.key, dist.input_mint()) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let output_wallet =
+        check_recipient_account(dist.token(), token_program_id, output_token_account_info)?;
+    check_denylist(program_id, denylist_entry_info, &output_wallet)?;
+
+    let output = dist
+        .redemption_output(amount)
+        .ok_or(ProgramError::InvalidArgument)?;
+    if output == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if output > dist.redeemable_amount() {
+        return Err(DistError::RedemptionCapReached.into());
+    }
+
+    if dist.burn_input() {
+        let burn = spl_token::instruction::burn(
+            token_program_id.key,
+            input_token_account_info.key,
+            input_mint_info.key,
+            redeemer_info.key,
+            &[],
+            amount,
+        )?;
+
+        invoke(
+            &burn,
+            &[
+                token_program_id.clone(),
+                input_token_account_info.clone(),
+                input_mint_info.clone(),
+                redeemer_info.clone(),
+            ],
+        )?;
+    } else {
+        let input_vault = get_associated_token_address(dist_account_info.key, dist.input_mint());
+        if !cmp_pubkeys(input_vault_info.key, &input_vault) {
+            return Err(DistError::InvalidRedemptionVault.into());
+        }
+
+        transfer_tokens(
+            token_program_id,
+            input_token_account_info,
+            input_vault_info,
+            redeemer_info,
+            amount,
+            &[],
+        )?;
+    }
+
+    pay_out_tokens(
+        &dist,
+        token_program_id,
+        dist_token_account_info,
+        output_token_account_info,
+        dist_account_info,
+        output,
+    )?;
+
+    dist.record_paid_amount(output);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_draw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let slot_hashes_info = next_account_info(accounts_iter)?;
+    if !sysvar::slot_hashes::check_id(slot_hashes_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if dist.kind() != DistributionKind::Raffle {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    if !dist.has_started() {
+        return Err(DistError::DistributionNotStarted.into());
+    }
+
+    if dist.raffle_drawn() {
+        return Err(DistError::RaffleAlreadyDrawn.into());
+    }
+
+    let current_slot = Clock::get()?.slot;
+    if current_slot <= dist.raffle_draw_slot() {
+        return Err(DistError::RaffleDrawTooEarly.into());
+    }
+
+    let slot_hash = find_slot_hash(&slot_hashes_info.data.borrow(), dist.raffle_draw_slot());
+    let slot_hash = match slot_hash {
+        Some(slot_hash) => slot_hash,
+        None => {
+            // Never fall back to another slot's hash, which whoever draws
+            // could pick; commit to a slot that hasn't happened yet instead
+            let draw_slot = current_slot + RAFFLE_DRAW_DELAY_SLOTS;
+            msg!(
+                "No hash of raffle draw slot {}, drawing at slot {} instead",
+                dist.raffle_draw_slot(),
+                draw_slot
+            );
+            dist.set_raffle_draw_slot(draw_slot);
+
+            Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+            return Ok(());
+        }
+    };
+
+    let seed = hashv(&[
+        slot_hash.as_ref(),
+        dist.entrants_root().as_ref(),
+        dist_account_info.key.as_ref(),
+    ]);
+
+    let num_winners = dist.num_recipients() as usize;
+    let mut winners = Vec::with_capacity(num_winners);
+    let mut counter = 0u32;
+    while winners.len() < num_winners {
+        let draw = hashv(&[seed.as_ref(), &counter.to_le_bytes()]);
+        counter += 1;
+
+        let mut value = [0; 8];
+        value.copy_from_slice(&draw.as_ref()[..8]);
+        let winner = (u64::from_le_bytes(value) % dist.num_entrants() as u64) as u32;
+        if !winners.contains(&winner) {
+            winners.push(winner);
+        }
+    }
+
+    msg!("Raffle winners: {:?}", winners);
+
+    dist.set_raffle_winners(&winners);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Looks up the hash of `slot` in the slot hashes sysvar data. The sysvar is
+/// too large to deserialize, so its entries are read in place: an entry
+/// count followed by (slot, hash) pairs, newest first.
+fn find_slot_hash(data: &[u8], slot: Slot) -> Option<Hash> {
+    let num_entries = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+
+    data[8..]
+        .chunks_exact(8 + 32)
+        .take(num_entries)
+        .map(|entry| {
+            let (entry_slot, hash) = entry.split_at(8);
+            (u64::from_le_bytes(entry_slot.try_into().unwrap()), hash)
+        })
+        .take_while(|(entry_slot, _)| *entry_slot >= slot)
+        .find(|(entry_slot, _)| *entry_slot == slot)
+        .map(|(_, hash)| Hash::new(hash))
+}
+
+fn process_claim_raffle_prize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u32,
+    proof: &[Hash],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let dist_token_account_info = next_account_info(accounts_iter)?;
+
+    let config_info = next_account_info(accounts_iter)?;
+    if load_config(program_id, config_info)?.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    let winner_token_account_info = next_account_info(accounts_iter)?;
+    let denylist_entry_info = next_account_info(accounts_iter)?;
+    let forward_info = next_account_info(accounts_iter)?;
+    let receipt_info = next_account_info(accounts_iter)?;
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    let gate_info = match dist.gate() {
+        Some(_) => Some(next_account_info(accounts_iter)?),
+        None => None,
+    };
+
+    if dist.kind() != DistributionKind::Raffle {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    check_no_payout_hook(&dist)?;
+
+    if !dist.raffle_drawn() {
+        return Err(DistError::RaffleNotDrawn.into());
+    }
+
+    check_payout_source(&dist, dist_account_info.key, dist_token_account_info)?;
+
+    let position = dist
+        .raffle_winners()
+        .iter()
+        .position(|&winner| winner == index)
+        .ok_or(DistError::NotRaffleWinner)?;
+    if dist.is_raffle_prize_claimed(position) {
+        return Err(DistError::RecipientAlreadyPaid.into());
+    }
+
+    let owner = check_recipient_account(dist.token(), token_program_id, winner_token_account_info)?;
+    let winner = check_forward_address(
+        program_id,
+        dist_account_info.key,
+        winner_token_account_info,
+        &owner,
+        forward_info,
+    )?;
+
+    if !merkle::verify_proof(
+        dist.entrants_root(),
+        merkle::entrant_leaf(index, &winner),
+        proof,
+    ) {
+        return Err(DistError::InvalidMerkleProof.into());
+    }
+
+    check_denylist(program_id, denylist_entry_info, &winner)?;
+    check_gate(&dist, gate_info, &winner)?;
+
+    let (receipt_pubkey, receipt_bump) =
+        find_receipt_address(program_id, dist_account_info.key, &winner);
+    if !cmp_pubkeys(receipt_info.key, &receipt_pubkey) {
+        return Err(DistError::InvalidReceiptAccount.into());
+    }
+
+    pay_out_tokens(
+        &dist,
+        token_program_id,
+        dist_token_account_info,
+        winner_token_account_info,
+        dist_account_info,
+        dist.recipient_share(),
+    )?;
+
+    // A wallet with several winning entries adds each prize to its receipt
+    let receipt = match load_receipt(program_id, receipt_info)? {
+        Some(mut receipt) if receipt.round == dist.round() => {
+            receipt.amount = receipt
+                .amount
+                .checked_add(dist.recipient_share())
+                .ok_or(ProgramError::InvalidArgument)?;
+            receipt
+        }
+        previous_receipt => {
+            let mut receipt = RecipientReceipt::default();
+            receipt.init(
+                *dist_account_info.key,
+                winner,
+                receipt_bump,
+                dist.round(),
+                dist.recipient_share(),
+            );
+
+            if previous_receipt.is_none() {
+                create_receipt_account(
+                    program_id,
+                    system_program_info,
+                    payer_info,
+                    receipt_info,
+                    &receipt,
+                )?;
+            }
+
+            receipt
+        }
+    };
+
+    RecipientReceipt::pack(receipt, &mut receipt_info.data.borrow_mut())?;
+
+    dist.record_raffle_prize_claimed(position);
+    dist.record_sent_recipient(*winner_token_account_info.key);
+    dist.record_paid_amount(dist.recipient_share());
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_create_grant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    recipient: &Pubkey,
+    amount: u64,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let source_token_account_info = next_account_info(accounts_iter)?;
+    let grant_info = next_account_info(accounts_iter)?;
+    let grant_vault_info = next_account_info(accounts_iter)?;
+    let mint_info = next_account_info(accounts_iter)?;
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let rent_info = next_account_info(accounts_iter)?;
+    let rent = Rent::from_account_info(rent_info)?;
+
+    let dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if !cmp_pubkeys(dist.token(), mint_info.key) {
+        return Err(DistError::DistributionMintMismatch.into());
+    }
+
+    if amount == 0 || end_epoch < start_epoch {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (grant_pubkey, grant_bump) =
+        find_grant_address(program_id, dist_account_info.key, recipient);
+    if !cmp_pubkeys(grant_info.key, &grant_pubkey) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let (grant_vault_pubkey, grant_vault_bump) =
+        find_grant_vault_address(program_id, dist_account_info.key, recipient);
+    if !cmp_pubkeys(grant_vault_info.key, &grant_vault_pubkey) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !grant_info.data_is_empty() || !grant_vault_info.data_is_empty() {
+        return Err(DistError::AlreadyInitialized.into());
+    }
+
+    let mut grant = Grant::default();
+    grant.init(
+        *dist_account_info.key,
+        *recipient,
+        grant_bump,
+        amount,
+        start_epoch,
+        end_epoch,
+    );
+
+    let create_grant_account = system_instruction::create_account(
+        payer_info.key,
+        grant_info.key,
+        rent.minimum_balance(Grant::LEN),
+        Grant::LEN as u64,
+        program_id,
+    );
+
+    invoke_signed(
+        &create_grant_account,
+        &[
+            system_program_info.clone(),
+            payer_info.clone(),
+            grant_info.clone(),
+        ],
+        &[&grant.signer_seeds()],
+    )?;
+
+    let create_grant_vault_account = system_instruction::create_account(
+        payer_info.key,
+        grant_vault_info.key,
+        rent.minimum_balance(spl_token::state::Account::LEN),
+        spl_token::state::Account::LEN as u64,
+        token_program_id.key,
+    );
+
+    invoke_signed(
+        &create_grant_vault_account,
+        &[
+            system_program_info.clone(),
+            payer_info.clone(),
+            grant_vault_info.clone(),
+        ],
+        &[&[
+            GRANT_VAULT_SEED,
+            dist_account_info.key.as_ref(),
+            recipient.as_ref(),
+            &[grant_vault_bump],
+        ]],
+    )?;
+
+    let initialize_account = spl_token::instruction::initialize_account(
+        token_program_id.key,
+        grant_vault_info.key,
+        mint_info.key,
+        dist_account_info.key,
+    )?;
+
+    invoke(
+        &initialize_account,
+        &[
+            token_program_id.clone(),
+            grant_vault_info.clone(),
+            mint_info.clone(),
+            dist_account_info.clone(),
+            rent_info.clone(),
+        ],
+    )?;
+
+    transfer_tokens(
+        token_program_id,
+        source_token_account_info,
+        grant_vault_info,
+        dist_authority_account_info,
+        amount,
+        &[],
+    )?;
+
+    Grant::pack(grant, &mut grant_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_release_grant(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let grant_info = next_account_info(accounts_iter)?;
+    let grant_vault_info = next_account_info(accounts_iter)?;
+    let recipient_token_account_info = next_account_info(accounts_iter)?;
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let config_info = next_account_info(accounts_iter)?;
+    if load_config(program_id, config_info)?.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    let denylist_entry_info = next_account_info(accounts_iter)?;
+
+    let dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+    check_no_payout_hook(&dist)?;
+
+    let mut grant = load_grant(
+        program_id,
+        dist_account_info.key,
+        grant_info,
+        grant_vault_info,
+    )?;
+
+    let recipient =
+        check_recipient_account(dist.token(), token_program_id, recipient_token_account_info)?;
+    if !cmp_pubkeys(&recipient, &grant.recipient) {
+        return Err(DistError::TokenAccountOwnerMismatch.into());
+    }
+
+    check_denylist(program_id, denylist_entry_info, &recipient)?;
+
+    let amount = grant.releasable_amount(Clock::get()?.epoch);
+    if amount > 0 {
+        transfer_tokens(
+            token_program_id,
+            grant_vault_info,
+            recipient_token_account_info,
+            dist_account_info,
+            amount,
+            &[&dist.pda_seed().signer_seeds()],
+        )?;
+
+        grant.record_released(amount);
+    }
+
+    msg!("Released {} of grant", amount);
+
+    Grant::pack(grant, &mut grant_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_revoke_grant(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let grant_info = next_account_info(accounts_iter)?;
+    let grant_vault_info = next_account_info(accounts_iter)?;
+    let destination_token_account_info = next_account_info(accounts_iter)?;
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    let mut grant = load_grant(
+        program_id,
+        dist_account_info.key,
+        grant_info,
+        grant_vault_info,
+    )?;
+
+    if grant.revoked {
+        return Err(DistError::GrantRevoked.into());
+    }
+
+    let unvested = grant.revoke(Clock::get()?.epoch);
+    if unvested > 0 {
+        transfer_tokens(
+            token_program_id,
+            grant_vault_info,
+            destination_token_account_info,
+            dist_account_info,
+            unvested,
+            &[&dist.pda_seed().signer_seeds()],
+        )?;
+    }
+
+    msg!("Returned {} unvested of grant", unvested);
+
+    Grant::pack(grant, &mut grant_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_forward_address(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let wallet_info = next_account_info(accounts_iter)?;
+    if !wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let forward_info = next_account_info(accounts_iter)?;
+    let destination_info = next_account_info(accounts_iter)?;
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    // Each NFT goes to a token account of its own mint
+    if dist.kind() == DistributionKind::NftBatch {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    if !cmp_pubkeys(destination_info.owner, &spl_token::id()) {
+        return Err(DistError::InvalidRecipientAccount.into());
+    }
+    let destination = spl_token::state::Account::unpack(&destination_info.data.borrow())
+        .map_err(|_| DistError::InvalidRecipientAccount)?;
+    if !cmp_pubkeys(&destination.mint, dist.token()) {
+        return Err(DistError::RecipientMintMismatch.into());
+    }
+
+    let (forward_pubkey, forward_bump) =
+        find_forward_address(program_id, dist_account_info.key, wallet_info.key);
+    if !cmp_pubkeys(forward_info.key, &forward_pubkey) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mut forward = if forward_info.data_is_empty() {
+        let mut forward = ForwardAddress::default();
+        forward.init(*dist_account_info.key, *wallet_info.key, forward_bump);
+
+        let rent = Rent::get()?;
+        let create_forward_account = system_instruction::create_account(
+            payer_info.key,
+            forward_info.key,
+            rent.minimum_balance(ForwardAddress::LEN),
+            ForwardAddress::LEN as u64,
+            program_id,
+        );
+
+        invoke_signed(
+            &create_forward_account,
+            &[
+                system_program_info.clone(),
+                payer_info.clone(),
+                forward_info.clone(),
+            ],
+            &[&forward.signer_seeds()],
+        )?;
+
+        forward
+    } else {
+        if !cmp_pubkeys(program_id, forward_info.owner) {
+            return Err(DistError::InvalidForwardAccount.into());
+        }
+        ForwardAddress::unpack(&forward_info.data.borrow())?
+    };
+
+    forward.destination = *destination_info.key;
+
+    ForwardAddress::pack(forward, &mut forward_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_gate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    gate_mint: &Pubkey,
+    min_balance: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if *gate_mint == Pubkey::default() {
+        dist.set_gate(Pubkey::default(), 0);
+    } else if min_balance == 0 {
+        return Err(ProgramError::InvalidArgument);
+    } else {
+        dist.set_gate(*gate_mint, min_balance);
+    }
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_attester(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    attester: &Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    // Shares already handed out would not account for voucher payouts
+    if dist.num_recipients() > 0 {
+        msg!(
+            "Distribution has begun paying {} recipients equal shares",
+            dist.num_recipients()
+        );
+        return Err(DistError::SharesAndVouchersMixed.into());
+    }
+
+    // Nor would the shares of the round if vouchers have paid out of it
+    if *attester == Pubkey::default() && dist.paid_amount() > 0 {
+        msg!("Vouchers have paid {} in this round", dist.paid_amount());
+        return Err(DistError::SharesAndVouchersMixed.into());
+    }
+
+    dist.set_attester(*attester);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_relay_fee_cap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    relay_fee_cap: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    dist.set_relay_fee_cap(relay_fee_cap);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Processes a `ClaimWithVoucher` instruction, or a `RelayClaim` one paying
+/// `relayer_fee` to the relayer.
+fn process_claim_with_voucher(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+    relayer_fee: Option<u64>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let dist_token_account_info = next_account_info(accounts_iter)?;
+
+    let config_info = next_account_info(accounts_iter)?;
+    if load_config(program_id, config_info)?.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    let recipient_token_account_info = next_account_info(accounts_iter)?;
+    let denylist_entry_info = next_account_info(accounts_iter)?;
+    let forward_info = next_account_info(accounts_iter)?;
+    let used_voucher_info = next_account_info(accounts_iter)?;
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+    let instructions_sysvar_info = next_account_info(accounts_iter)?;
+
+    let relayer_token_account_info = match relayer_fee {
+        Some(_) => Some(next_account_info(accounts_iter)?),
+        None => None,
+    };
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    let gate_info = match dist.gate() {
+        Some(_) => Some(next_account_info(accounts_iter)?),
+        None => None,
+    };
+
+    if !matches!(
+        dist.kind(),
+        DistributionKind::Escrow | DistributionKind::Mint
+    ) {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    check_no_payout_hook(&dist)?;
+
+    if dist.is_streaming() {
+        return Err(DistError::DistributionIsStreaming.into());
+    }
+
+    // Settlement closes the round to payouts
+    if dist.is_settled() {
+        return Err(DistError::DistributionSettled.into());
+    }
+
+    let attester = *dist.attester().ok_or(DistError::AttesterNotSet)?;
+
+    check_payout_source(&dist, dist_account_info.key, dist_token_account_info)?;
+
+    let owner =
+        check_recipient_account(dist.token(), token_program_id, recipient_token_account_info)?;
+    let recipient = check_forward_address(
+        program_id,
+        dist_account_info.key,
+        recipient_token_account_info,
+        &owner,
+        forward_info,
+    )?;
+
+    ed25519::check_signed_message(
+        instructions_sysvar_info,
+        &attester,
+        &ed25519::voucher_message(dist_account_info.key, &recipient, amount, nonce, expiry),
+    )?;
+
+    // The recipient asks for the claim to be relayed
+    if let Some(relayer_fee) = relayer_fee {
+        ed25519::check_signed_message(
+            instructions_sysvar_info,
+            &recipient,
+            &ed25519::relay_message(
+                dist_account_info.key,
+                &recipient,
+                payer_info.key,
+                nonce,
+                relayer_fee,
+            ),
+        )?;
+
+        if relayer_fee > dist.relay_fee_cap() {
+            msg!(
+                "Relayer fee {} is over the cap of {}",
+                relayer_fee,
+                dist.relay_fee_cap()
+            );
+            return Err(DistError::RelayerFeeTooHigh.into());
+        }
+    }
+
+    if Clock::get()?.unix_timestamp > expiry {
+        return Err(DistError::VoucherExpired.into());
+    }
+
+    check_denylist(program_id, denylist_entry_info, &recipient)?;
+    check_gate(&dist, gate_info, &recipient)?;
+
+    let total_amount = amount
+        .checked_add(relayer_fee.unwrap_or_default())
+        .ok_or(ProgramError::InvalidArgument)?;
+    if total_amount > dist.unpaid_amount() {
+        msg!(
+            "Voucher for {} exceeds the unpaid {}",
+            total_amount,
+            dist.unpaid_amount()
+        );
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    let nonce_bytes = nonce.to_le_bytes();
+    let (used_voucher_pubkey, used_voucher_bump) =
+        find_voucher_address(program_id, dist_account_info.key, &recipient, nonce);
+    if !cmp_pubkeys(used_voucher_info.key, &used_voucher_pubkey) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !used_voucher_info.data_is_empty() {
+        return Err(DistError::VoucherAlreadyUsed.into());
+    }
+
+    let rent = Rent::get()?;
+    let create_used_voucher_account = system_instruction::create_account(
+        payer_info.key,
+        used_voucher_info.key,
+        rent.minimum_balance(UsedVoucher::LEN),
+        UsedVoucher::LEN as u64,
+        program_id,
+    );
+
+    invoke_signed(
+        &create_used_voucher_account,
+        &[
+            system_program_info.clone(),
+            payer_info.clone(),
+            used_voucher_info.clone(),
+        ],
+        &[&[
+            VOUCHER_SEED,
+            dist_account_info.key.as_ref(),
+            recipient.as_ref(),
+            &nonce_bytes,
+            &[used_voucher_bump],
+        ]],
+    )?;
+
+    let mut used_voucher = UsedVoucher::default();
+    used_voucher.init(*dist_account_info.key, recipient, nonce, used_voucher_bump);
+    UsedVoucher::pack(used_voucher, &mut used_voucher_info.data.borrow_mut())?;
+
+    pay_out_tokens(
+        &dist,
+        token_program_id,
+        dist_token_account_info,
+        recipient_token_account_info,
+        dist_account_info,
+        amount,
+    )?;
+
+    if let (Some(relayer_fee), Some(relayer_token_account_info)) =
+        (relayer_fee, relayer_token_account_info)
+    {
+        check_recipient_account(dist.token(), token_program_id, relayer_token_account_info)?;
+
+        pay_out_tokens(
+            &dist,
+            token_program_id,
+            dist_token_account_info,
+            relayer_token_account_info,
+            dist_account_info,
+            relayer_fee,
+        )?;
+    }
+
+    dist.record_paid_amount(total_amount);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_decay(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    grace_epochs: u64,
+    bps_per_epoch: u16,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if dist.has_started() {
+        return Err(DistError::DistributionAlreadyStarted.into());
+    }
+
+    if !matches!(
+        dist.kind(),
+        DistributionKind::Escrow | DistributionKind::Mint
+    ) {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    if dist.is_streaming() {
+        return Err(DistError::DistributionIsStreaming.into());
+    }
+
+    if bps_per_epoch > MAX_DECAY_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    dist.set_decay(grace_epochs, bps_per_epoch);
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_settle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let dist_token_account_info = next_account_info(accounts_iter)?;
+
+    let config_info = next_account_info(accounts_iter)?;
+    if load_config(program_id, config_info)?.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if !matches!(
+        dist.kind(),
+        DistributionKind::Escrow | DistributionKind::Mint
+    ) {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    check_no_payout_hook(&dist)?;
+
+    if dist.is_streaming() {
+        return Err(DistError::DistributionIsStreaming.into());
+    }
+
+    if !dist.has_started() {
+        return Err(DistError::DistributionNotStarted.into());
+    }
+
+    check_payout_source(&dist, dist_account_info.key, dist_token_account_info)?;
+
+    if !dist.is_settled() {
+        let remainder = dist.settle(Clock::get()?.epoch);
+        msg!(
+            "Settled {} forfeited, {} per recipient, {} kept funded",
+            dist.forfeited_amount(),
+            dist.settle_bonus(),
+            remainder
+        );
+    }
+
+    let recipient_accounts_chunks = accounts_iter.as_slice().chunks_exact(4);
+    if !recipient_accounts_chunks.remainder().is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    for recipient_accounts in recipient_accounts_chunks {
+        let recipient_token_account_info = &recipient_accounts[0];
+        let receipt_info = &recipient_accounts[1];
+        let denylist_entry_info = &recipient_accounts[2];
+        let forward_info = &recipient_accounts[3];
+
+        let owner =
+            check_recipient_account(dist.token(), token_program_id, recipient_token_account_info)?;
+        let recipient = check_forward_address(
+            program_id,
+            dist_account_info.key,
+            recipient_token_account_info,
+            &owner,
+            forward_info,
+        )?;
+        check_denylist(program_id, denylist_entry_info, &recipient)?;
+
+        let (receipt_pubkey, _) =
+            find_receipt_address(program_id, dist_account_info.key, &recipient);
+        if !cmp_pubkeys(receipt_info.key, &receipt_pubkey) {
+            return Err(DistError::InvalidReceiptAccount.into());
+        }
+
+        let mut receipt = match load_receipt(program_id, receipt_info)? {
+            Some(receipt) if receipt.round == dist.round() => receipt,
+            _ => return Err(DistError::RecipientNotPaid.into()),
+        };
+
+        // Receipts record the bonus paid, so it isn't paid twice
+        if receipt.settle_bonus > 0 {
+            return Err(DistError::SettleBonusAlreadyPaid.into());
+        }
+
+        if dist.settle_bonus() > 0 {
+            pay_out_tokens(
+                &dist,
+                token_program_id,
+                dist_token_account_info,
+                recipient_token_account_info,
+                dist_account_info,
+                dist.settle_bonus(),
+            )?;
+            dist.record_paid_amount(dist.settle_bonus());
+
+            receipt.settle_bonus = dist.settle_bonus();
+            RecipientReceipt::pack(receipt, &mut receipt_info.data.borrow_mut())?;
+        }
+    }
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_hook(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hook_program: &Pubkey,
+    hook_accounts_len: u8,
+    failure_policy: HookFailurePolicy,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    // The distribution signs hook calls, which mustn't come back here
+    if cmp_pubkeys(hook_program, program_id) || hook_accounts_len > MAX_HOOK_ACCOUNTS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if *hook_program == Pubkey::default() {
+        dist.set_hook(Pubkey::default(), 0, HookFailurePolicy::default());
+    } else {
+        dist.set_hook(*hook_program, hook_accounts_len, failure_policy);
+    }
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_get_distribution_info(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    let info = DistributionInfo::new(&dist);
+    set_return_data(&info.try_to_vec()?);
+
+    Ok(())
+}
+
+fn process_is_recipient_paid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    recipient: &Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let receipt_info = next_account_info(accounts_iter)?;
+    let (receipt, _) = find_receipt_address(program_id, dist_account_info.key, recipient);
+    if !cmp_pubkeys(receipt_info.key, &receipt) {
+        return Err(DistError::InvalidReceiptAccount.into());
+    }
+
+    let dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    let receipt = load_receipt(program_id, receipt_info)?;
+    let paid = RecipientPaid::new(&dist, *recipient, receipt.as_ref());
+    set_return_data(&paid.try_to_vec()?);
+
+    Ok(())
+}
+
+fn process_migrate_distribution(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let dist_v1 = DistributionV1::unpack(&dist_account_info.data.borrow())?;
+    if !cmp_pubkeys(&dist_v1.dist_authority, dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    let dist = Distribution::migrate(dist_v1);
+    if !cmp_pubkeys(
+        dist_account_info.key,
+        &dist.pda_seed().create_pubkey(program_id)?,
+    ) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rent = Rent::get()?;
+    let lamports = rent
+        .minimum_balance(Distribution::LEN)
+        .saturating_sub(dist_account_info.lamports());
+    if lamports > 0 {
+        invoke(
+            &system_instruction::transfer(
+                dist_authority_account_info.key,
+                dist_account_info.key,
+                lamports,
+            ),
+            &[
+                system_program_info.clone(),
+                dist_authority_account_info.clone(),
+                dist_account_info.clone(),
+            ],
+        )?;
+    }
+
+    dist_account_info.realloc(Distribution::LEN, true)?;
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_drip(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proofs: &[Vec<Hash>],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let token_program_id = next_account_info(accounts_iter)?;
+    spl_token::check_program_account(token_program_id.key)?;
+
+    let dist_token_account_info = next_account_info(accounts_iter)?;
+
+    let config_info = next_account_info(accounts_iter)?;
+    if load_config(program_id, config_info)?.paused {
+        return Err(DistError::ProgramPaused.into());
+    }
+
+    let payer_info = next_account_info(accounts_iter)?;
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    check_payout_source(&dist, dist_account_info.key, dist_token_account_info)?;
+    check_no_payout_hook(&dist)?;
+    check_no_attester(&dist)?;
+
+    if matches!(
+        dist.kind(),
+        DistributionKind::NftBatch | DistributionKind::Redemption | DistributionKind::Raffle
+    ) {
+        return Err(DistError::UnsupportedDistributionKind.into());
+    }
+
+    if !dist.is_streaming() {
+        return Err(DistError::DistributionNotStreaming.into());
+    }
+
+    if !dist.has_started() {
+        return Err(DistError::DistributionNotStarted.into());
+    }
+
+    let released_share = dist.released_share(Clock::get()?.epoch);
+
+    let recipient_accounts_len = 4 + dist.gate().is_some() as usize;
+    let recipient_accounts_chunks = accounts_iter
+        .as_slice()
+        .chunks_exact(recipient_accounts_len);
+    if !recipient_accounts_chunks.remainder().is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    if recipient_accounts_chunks.len() != proofs.len() {
+        msg!(
+            "Got {} proofs for {} recipients",
+            proofs.len(),
+            recipient_accounts_chunks.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    for (recipient_accounts, proof) in recipient_accounts_chunks.zip(proofs) {
+        let recipient_token_account_info = &recipient_accounts[0];
+        let receipt_info = &recipient_accounts[1];
+        let denylist_entry_info = &recipient_accounts[2];
+        let forward_info = &recipient_accounts[3];
+        let gate_info = recipient_accounts.get(4);
+
+        let owner =
+            check_recipient_account(dist.token(), token_program_id, recipient_token_account_info)?;
+        let recipient = check_forward_address(
+            program_id,
+            dist_account_info.key,
+            recipient_token_account_info,
+            &owner,
+            forward_info,
+        )?;
+
+        if !merkle::verify_proof(
+            dist.recipients_root(),
+            merkle::recipient_leaf(&recipient),
+            proof,
+        ) {
+            return Err(DistError::InvalidMerkleProof.into());
+        }
+
+        check_denylist(program_id, denylist_entry_info, &recipient)?;
+        check_gate(&dist, gate_info, &recipient)?;
+
+        let (receipt_pubkey, receipt_bump) =
+            find_receipt_address(program_id, dist_account_info.key, &recipient);
+        if !cmp_pubkeys(receipt_info.key, &receipt_pubkey) {
+            return Err(DistError::InvalidReceiptAccount.into());
+        }
+
+        let mut receipt = match load_receipt(program_id, receipt_info)? {
+            Some(receipt) if receipt.round == dist.round() => receipt,
+            previous_receipt => {
+                if dist.sent_recipients() >= dist.num_recipients() {
+                    return Err(DistError::TooManyRecipients.into());
+                }
+
+                let mut receipt = RecipientReceipt::default();
+                receipt.init(
+                    *dist_account_info.key,
+                    recipient,
+                    receipt_bump,
+                    dist.round(),
+                    0,
+                );
+
+                if previous_receipt.is_none() {
+                    create_receipt_account(
+                        program_id,
+                        system_program_info,
+                        payer_info,
+                        receipt_info,
+                        &receipt,
+                    )?;
+                }
+
+                dist.record_sent_recipient(*recipient_token_account_info.key);
+
+                receipt
+            }
+        };
+
+        let amount = released_share.saturating_sub(receipt.amount);
+        if amount > 0 {
+            pay_out_tokens(
+                &dist,
+                token_program_id,
+                dist_token_account_info,
+                recipient_token_account_info,
+                dist_account_info,
+                amount,
+            )?;
+
+            receipt.amount = released_share;
+            dist.record_paid_amount(amount);
+        }
+
+        RecipientReceipt::pack(receipt, &mut receipt_info.data.borrow_mut())?;
+    }
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_start_round(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let fee_payer_info = next_account_info(accounts_iter)?;
+    if !fee_payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let round_account_info = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let mut dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    if !dist.has_started() {
+        return Err(DistError::DistributionNotStarted.into());
+    }
+
+    let (round_pubkey, round_bump) =
+        find_round_address(program_id, dist_account_info.key, dist.round());
+    if !cmp_pubkeys(round_account_info.key, &round_pubkey) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mut summary = RoundSummary::default();
+    summary.init(*dist_account_info.key, dist.round(), round_bump);
+    summary.num_recipients = dist.num_recipients();
+    summary.sent_recipients = dist.sent_recipients();
+    summary.funded_amount = dist.funded_amount();
+    summary.paid_amount = dist.paid_amount();
+
+    let rent = Rent::get()?;
+    let state_size = RoundSummary::LEN;
+
+    let create_round_account = system_instruction::create_account(
+        fee_payer_info.key,
+        round_account_info.key,
+        rent.minimum_balance(state_size),
+        state_size as u64,
+        program_id,
+    );
+
+    let round_bytes = summary.round.to_le_bytes();
+    invoke_signed(
+        &create_round_account,
+        &[
+            system_program_info.clone(),
+            fee_payer_info.clone(),
+            round_account_info.clone(),
+        ],
+        &[&[
+            ROUND_SEED,
+            dist_account_info.key.as_ref(),
+            &round_bytes,
+            &[round_bump],
+        ]],
+    )?;
+
+    RoundSummary::pack(summary, &mut round_account_info.data.borrow_mut())?;
+
+    dist.start_round();
+
+    Distribution::pack(dist, &mut dist_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    description: &str,
+    uri: &str,
+    creator_label: &str,
+    category: &str,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let dist_account_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, dist_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let dist_authority_account_info = next_account_info(accounts_iter)?;
+    if !dist_authority_account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let fee_payer_info = next_account_info(accounts_iter)?;
+    if !fee_payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let metadata_account_info = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let dist = Distribution::unpack(&dist_account_info.data.borrow())?;
+
+    if !cmp_pubkeys(dist.dist_authority(), dist_authority_account_info.key) {
+        return Err(DistError::UnauthorizedDistAuthority.into());
+    }
+
+    let (metadata_pubkey, metadata_bump) = find_metadata_address(program_id, dist_account_info.key);
+    if !cmp_pubkeys(metadata_account_info.key, &metadata_pubkey) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mut metadata = DistributionMetadata::default();
+    metadata.init(*dist_account_info.key, metadata_bump);
+    metadata.description = FixedString::new(description).ok_or(DistError::InvalidMetadata)?;
+    metadata.uri = FixedString::new(uri).ok_or(DistError::InvalidMetadata)?;
+    metadata.creator_label = FixedString::new(creator_label).ok_or(DistError::InvalidMetadata)?;
+    metadata.category = FixedString::new(category).ok_or(DistError::InvalidMetadata)?;
+
+    if metadata_account_info.data_is_empty() {
+        let rent = Rent::get()?;
+        let state_size = DistributionMetadata::LEN;
+
+        let create_metadata_account = system_instruction::create_account(
+            fee_payer_info.key,
+            metadata_account_info.key,
+            rent.minimum_balance(state_size),
+            state_size as u64,
+            program_id,
+        );
+
+        invoke_signed(
+            &create_metadata_account,
+            &[
+                system_program_info.clone(),
+                fee_payer_info.clone(),
+                metadata_account_info.clone(),
+            ],
+            &[&metadata.signer_seeds()],
+        )?;
+    } else if !cmp_pubkeys(program_id, metadata_account_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    DistributionMetadata::pack(metadata, &mut metadata_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: &Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let upgrade_authority_info = next_account_info(accounts_iter)?;
+    if !upgrade_authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config_info = next_account_info(accounts_iter)?;
+    let program_data_info = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let (program_data_pubkey, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if !cmp_pubkeys(program_data_info.key, &program_data_pubkey) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Only whoever controls program upgrades may claim the singleton config.
+    // The program bytes following the loader state are ignored.
+    let upgrade_authority_address =
+        match bincode::deserialize::<UpgradeableLoaderState>(&program_data_info.data.borrow()) {
+            Ok(UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            }) => upgrade_authority_address,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+    if upgrade_authority_address != Some(*upgrade_authority_info.key) {
+        return Err(DistError::UnauthorizedConfigAdmin.into());
+    }
+
+    let (config_pubkey, config_bump) = find_config_address(program_id);
+    if !cmp_pubkeys(config_info.key, &config_pubkey) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::get()?;
+    let state_size = ProgramConfig::LEN;
+
+    let create_config_account = system_instruction::create_account(
+        upgrade_authority_info.key,
+        config_info.key,
+        rent.minimum_balance(state_size),
+        state_size as u64,
+        program_id,
+    );
+
+    invoke_signed(
+        &create_config_account,
+        &[
+            system_program_info.clone(),
+            upgrade_authority_info.clone(),
+            config_info.clone(),
+        ],
+        &[&[CONFIG_SEED, &[config_bump]]],
+    )?;
+
+    let mut config = ProgramConfig::unpack_unchecked(&config_info.data.borrow())?;
+
+    if config.is_initialized() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    config.init(*admin, config_bump);
+
+    ProgramConfig::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused: bool,
+    protocol_fee_bps: u16,
+    treasury: &Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let config_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, config_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let admin_info = next_account_info(accounts_iter)?;
+    if !admin_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = ProgramConfig::unpack(&config_info.data.borrow())?;
+
+    if !cmp_pubkeys(&config.admin, admin_info.key) {
+        return Err(DistError::UnauthorizedConfigAdmin.into());
+    }
+
+    if protocol_fee_bps > MAX_PROTOCOL_FEE_BPS {
+        return Err(DistError::InvalidProtocolFee.into());
+    }
+
+    config.paused = paused;
+    config.protocol_fee_bps = protocol_fee_bps;
+    config.treasury = *treasury;
+
+    ProgramConfig::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_set_allowed_mints(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mints: &[Pubkey],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let config_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, config_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let admin_info = next_account_info(accounts_iter)?;
+    if !admin_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = ProgramConfig::unpack(&config_info.data.borrow())?;
+
+    if !cmp_pubkeys(&config.admin, admin_info.key) {
+        return Err(DistError::UnauthorizedConfigAdmin.into());
+    }
+
+    if !config.set_allowed_mints(mints) {
+        return Err(DistError::TooManyAllowedMints.into());
+    }
+
+    ProgramConfig::pack(config, &mut config_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Processes an [AddToDenylist](enum.DistInstruction.html) instruction.
+fn process_add_to_denylist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallets: &[Pubkey],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let config_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, config_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let admin_info = next_account_info(accounts_iter)?;
+    if !admin_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let fee_payer_info = next_account_info(accounts_iter)?;
+    if !fee_payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    let config = ProgramConfig::unpack(&config_info.data.borrow())?;
+
+    if !cmp_pubkeys(&config.admin, admin_info.key) {
+        return Err(DistError::UnauthorizedConfigAdmin.into());
+    }
+
+    let rent = Rent::get()?;
+    let state_size = DenylistEntry::LEN;
+
+    for wallet in wallets {
+        let denylist_entry_info = next_account_info(accounts_iter)?;
+
+        let (denylist_entry_pubkey, denylist_entry_bump) =
+            find_denylist_address(program_id, wallet);
+        if !cmp_pubkeys(denylist_entry_info.key, &denylist_entry_pubkey) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if !denylist_entry_info.data_is_empty() {
+            continue;
+        }
+
+        let mut entry = DenylistEntry::default();
+        entry.init(*wallet, denylist_entry_bump);
+
+        let create_denylist_entry = system_instruction::create_account(
+            fee_payer_info.key,
+            denylist_entry_info.key,
+            rent.minimum_balance(state_size),
+            state_size as u64,
+            program_id,
+        );
+
+        invoke_signed(
+            &create_denylist_entry,
+            &[
+                system_program_info.clone(),
+                fee_payer_info.clone(),
+                denylist_entry_info.clone(),
+            ],
+            &[&entry.signer_seeds()],
+        )?;
+
+        DenylistEntry::pack(entry, &mut denylist_entry_info.data.borrow_mut())?;
+    }
+
+    Ok(())
+}
+
+fn process_remove_from_denylist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallets: &[Pubkey],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let config_info = next_account_info(accounts_iter)?;
+    if !cmp_pubkeys(program_id, config_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let admin_info = next_account_info(accounts_iter)?;
+    if !admin_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let rent_receiver_info = next_account_info(accounts_iter)?;
+
+    let config = ProgramConfig::unpack(&config_info.data.borrow())?;
+
+    if !cmp_pubkeys(&config.admin, admin_info.key) {
+        return Err(DistError::UnauthorizedConfigAdmin.into());
+    }
+
+    for wallet in wallets {
+        let denylist_entry_info = next_account_info(accounts_iter)?;
+
+        let (denylist_entry_pubkey, _) = find_denylist_address(program_id, wallet);
+        if !cmp_pubkeys(denylist_entry_info.key, &denylist_entry_pubkey) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if denylist_entry_info.data_is_empty() {
+            continue;
+        }
+
+        if !cmp_pubkeys(program_id, denylist_entry_info.owner) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        close_program_account(denylist_entry_info, rent_receiver_info)?;
+    }
+
+    Ok(())
+}
+
+/// Closes an account owned by this program, moving its rent to `receiver_info`.
+fn close_program_account(account_info: &AccountInfo, receiver_info: &AccountInfo) -> ProgramResult {
+    let lamports = account_info.lamports();
+    **receiver_info.lamports.borrow_mut() = receiver_info
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
+    **account_info.lamports.borrow_mut() = 0;
+
+    account_info.data.borrow_mut().fill(0);
+
+    Ok(())
+}
+
+/// Loads the program config. Before the config is initialized this returns
+/// the default config, which places no restrictions on distributions.
+fn load_config(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+    let (config_pubkey, _) = find_config_address(program_id);
+    if !cmp_pubkeys(config_info.key, &config_pubkey) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if config_info.data_is_empty() {
+        return Ok(ProgramConfig::default());
+    }
+
+    if !cmp_pubkeys(program_id, config_info.owner) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    ProgramConfig::unpack(&config_info.data.borrow())
+}
+
+fn transfer_tokens<'a>(
+    token_program_info: &AccountInfo<'a>,
+    source_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let transfer = spl_token::instruction::transfer(
+        token_program_info.key,
+        source_info.key,
+        destination_info.key,
+        authority_info.key,
+        &[],
+        amount,
+    )?;
+
+    invoke_signed(
+        &transfer,
+        &[
+            token_program_info.clone(),
+            source_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+fn mint_tokens<'a>(
+    token_program_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mint_to = spl_token::instruction::mint_to(
+        token_program_info.key,
+        mint_info.key,
+        destination_info.key,
+        authority_info.key,
+        &[],
+        amount,
+    )?;
+
+    invoke_signed(
+        &mint_to,
+        &[
+            token_program_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+fn close_token_account<'a>(
+    token_program_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let close_account = spl_token::instruction::close_account(
+        token_program_info.key,
+        account_info.key,
+        destination_info.key,
+        authority_info.key,
+        &[],
+    )?;
+
+    invoke_signed(
+        &close_account,
+        &[
+            token_program_info.clone(),
+            account_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
 fn cmp_pubkeys(a: &Pubkey, b: &Pubkey) -> bool {
     sol_memcmp(a.as_ref(), b.as_ref(), PUBKEY_BYTES) == 0
 }
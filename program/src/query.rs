@@ -0,0 +1,114 @@
+//! Answers of the query instructions
+//!
+//! `GetDistributionInfo` and `IsRecipientPaid` change nothing and instead
+//! write their answer as program return data, Borsh encoded, so that other
+//! programs can read it after calling them and clients by simulating them.
+//!
+//! Each answer is an enum with one variant per version, so its first byte
+//! is the version. Later versions are added as new variants and the answer
+//! is always given in the latest one.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{clock::Epoch, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::state::{Distribution, DistributionKind, RecipientReceipt};
+
+/// Answer of `GetDistributionInfo`.
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub enum DistributionInfo {
+    V1(DistributionInfoV1),
+}
+
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct DistributionInfoV1 {
+    pub project_name: String,
+    pub creator: Pubkey,
+    pub dist_authority: Pubkey,
+    pub token: Pubkey,
+    pub kind: DistributionKind,
+    pub round: u32,
+    pub started: bool,
+    pub settled: bool,
+    pub max_recipients: u16,
+    pub num_recipients: u16,
+    pub sent_recipients: u16,
+    /// Funded amount of the current round, in base units of the token, as
+    /// are the other amounts.
+    pub funded_amount: u64,
+    pub paid_amount: u64,
+    pub recipient_share: u64,
+    /// Tokens released per epoch from `release_start_epoch`, or zero for a
+    /// one-shot distribution.
+    pub release_rate: u64,
+    pub release_start_epoch: Epoch,
+    pub gate_mint: Option<Pubkey>,
+}
+
+impl DistributionInfo {
+    pub fn new(dist: &Distribution) -> Self {
+        DistributionInfo::V1(DistributionInfoV1 {
+            project_name: dist.project_name().to_string(),
+            creator: *dist.creator(),
+            dist_authority: *dist.dist_authority(),
+            token: *dist.token(),
+            kind: dist.kind(),
+            round: dist.round(),
+            started: dist.has_started(),
+            settled: dist.is_settled(),
+            max_recipients: dist.max_recipients(),
+            num_recipients: dist.num_recipients(),
+            sent_recipients: dist.sent_recipients(),
+            funded_amount: dist.funded_amount(),
+            paid_amount: dist.paid_amount(),
+            recipient_share: dist.recipient_share(),
+            release_rate: dist.release_rate(),
+            release_start_epoch: dist.release_start_epoch(),
+            gate_mint: dist.gate().map(|(gate_mint, _)| *gate_mint),
+        })
+    }
+
+    /// Unpacks the return data of `GetDistributionInfo`.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(input).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/// Answer of `IsRecipientPaid`.
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub enum RecipientPaid {
+    V1(RecipientPaidV1),
+}
+
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct RecipientPaidV1 {
+    pub recipient: Pubkey,
+    pub round: u32,
+    /// Whether the recipient was paid in the current round.
+    pub paid: bool,
+    /// Amount paid in the current round, in base units of the token.
+    pub amount: u64,
+    /// The last round the recipient was paid in, if ever.
+    pub last_paid_round: Option<u32>,
+}
+
+impl RecipientPaid {
+    pub fn new(dist: &Distribution, recipient: Pubkey, receipt: Option<&RecipientReceipt>) -> Self {
+        let paid_receipt = receipt.filter(|receipt| receipt.round == dist.round());
+
+        RecipientPaid::V1(RecipientPaidV1 {
+            recipient,
+            round: dist.round(),
+            paid: paid_receipt.is_some(),
+            amount: paid_receipt
+                .map(|receipt| receipt.amount)
+                .unwrap_or_default(),
+            last_paid_round: receipt.map(|receipt| receipt.round),
+        })
+    }
+
+    /// Unpacks the return data of `IsRecipientPaid`.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(input).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
@@ -1,10 +1,12 @@
 //! State transition types
 
-use std::io::BufWriter;
+use std::io::{BufWriter, Error, ErrorKind, Write};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use solana_program::{
+    clock::{Epoch, Slot},
+    hash::{Hash, HASH_BYTES},
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::{Pubkey, PubkeyError, PUBKEY_BYTES},
@@ -14,50 +16,369 @@ const UNINITIALIZED_VERSION: u8 = 0;
 
 const VERSION_1: u8 = 1;
 
-const PDA_SEED_SIZE: usize = PUBKEY_BYTES + PUBKEY_BYTES+1;
+const VERSION_2: u8 = 2;
+
+/// The longest project name, in bytes, kept in the distribution state.
+/// Longer names are truncated; the address seeds hash the full name.
+pub const MAX_PROJECT_NAME_LEN: usize = 64;
+
+/// The most winners a raffle can draw.
+pub const MAX_RAFFLE_WINNERS: usize = 32;
+
+/// Slots between committing a raffle and the slot whose hash draws it.
+pub const RAFFLE_DRAW_DELAY_SLOTS: Slot = 32;
+
+/// Basis points of a whole recipient share, the most it can decay by.
+pub const MAX_DECAY_BPS: u16 = 10_000;
+
+/// The most extra accounts each recipient may pass to a payout hook.
+pub const MAX_HOOK_ACCOUNTS: u8 = 8;
+
+/// A UTF-8 string stored in a fixed amount of account space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedString<const N: usize> {
+    len: u16,
+    bytes: [u8; N],
+}
+
+impl<const N: usize> FixedString<N> {
+    pub const LEN: usize = 2 + N;
+
+    /// Returns `None` if the string does not fit.
+    pub fn new(s: &str) -> Option<Self> {
+        if s.len() > N {
+            return None;
+        }
+        let mut bytes = [0; N];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Some(FixedString {
+            len: s.len() as u16,
+            bytes,
+        })
+    }
+
+    /// Keeps as much of the string as fits, cut at a character boundary.
+    pub fn truncated(s: &str) -> Self {
+        let mut end = s.len().min(N);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        Self::new(&s[..end]).unwrap()
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> Default for FixedString<N> {
+    fn default() -> Self {
+        FixedString {
+            len: 0,
+            bytes: [0; N],
+        }
+    }
+}
+
+impl<const N: usize> BorshSerialize for FixedString<N> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.len.serialize(writer)?;
+        writer.write_all(&self.bytes)
+    }
+}
+
+impl<const N: usize> BorshDeserialize for FixedString<N> {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let len = u16::deserialize(buf)?;
+        if len as usize > N || buf.len() < N {
+            return Err(Error::new(ErrorKind::InvalidData, "invalid fixed string"));
+        }
+        let (data, rest) = buf.split_at(N);
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(data);
+        *buf = rest;
+        if std::str::from_utf8(&bytes[..len as usize]).is_err() {
+            return Err(Error::new(ErrorKind::InvalidData, "invalid fixed string"));
+        }
+        Ok(FixedString { len, bytes })
+    }
+}
+
+const PDA_SEED_SIZE: usize = PUBKEY_BYTES + HASH_BYTES + PUBKEY_BYTES + 1;
 
 #[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
 pub struct PdaSeed {
     pub seed: Pubkey,
-    pub project_name: Pubkey,
+    // SHA-256 of the UTF-8 project name, or the project name seed itself
+    // for a distribution migrated from version 1.
+    pub project_name_hash: Hash,
+    // The account that created the distribution. Binding it into the
+    // address keeps others from claiming the same seed and project name.
+    // Version 1 addresses have no creator, which is kept as the default
+    // pubkey.
+    pub creator: Pubkey,
     pub bump: u8,
 }
 
 impl PdaSeed {
-    pub fn new(seed: Pubkey,project_name:Pubkey, bump: u8) -> PdaSeed {
-        PdaSeed { seed,project_name, bump }
+    pub fn new(seed: Pubkey, project_name_hash: Hash, creator: Pubkey, bump: u8) -> PdaSeed {
+        PdaSeed {
+            seed,
+            project_name_hash,
+            creator,
+            bump,
+        }
     }
 
     pub fn create_pubkey(&self, program_id: &Pubkey) -> Result<Pubkey, PubkeyError> {
-        let seeds = &[self.seed.as_ref(),self.project_name.as_ref(), &[self.bump]];
-        Pubkey::create_program_address(seeds, program_id)
+        Pubkey::create_program_address(&self.signer_seeds(), program_id)
+    }
+
+    /// Seeds used to sign on behalf of the distribution account.
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        // Seeds are hashed back to back, so an empty creator seed derives
+        // the same address as the version 1 seeds without it.
+        let creator: &[u8] = if self.creator == Pubkey::default() {
+            &[]
+        } else {
+            self.creator.as_ref()
+        };
+        [
+            self.seed.as_ref(),
+            self.project_name_hash.as_ref(),
+            creator,
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+const PDA_SEED_V1_SIZE: usize = PUBKEY_BYTES + PUBKEY_BYTES + 1;
+
+/// The address seeds of a version 1 distribution.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct PdaSeedV1 {
+    pub seed: Pubkey,
+    pub project_name: Pubkey,
+    pub bump: u8,
+}
+
+/// How a distribution comes by the tokens it pays out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub enum DistributionKind {
+    /// Funded tokens are held in escrow or delegated to the distribution.
+    Escrow,
+    /// The distribution is the mint authority and mints each payout, up to
+    /// a supply cap.
+    Mint,
+    /// The distribution holds a pool of NFTs, one per recipient, each in its
+    /// own pool item token account.
+    NftBatch,
+    /// Anyone may redeem an input token for the funded tokens at a fixed
+    /// rate, up to a cap.
+    Redemption,
+    /// The funded tokens are shared between winners drawn on-chain from a
+    /// committed set of entrants.
+    Raffle,
+}
+
+// Deriving needs `#[default]`, which is newer than the BPF toolchain.
+#[allow(clippy::derivable_impls)]
+impl Default for DistributionKind {
+    fn default() -> Self {
+        DistributionKind::Escrow
+    }
+}
+
+/// What a payout does when a distribution's payout hook program can't
+/// be called, because it was closed or isn't a program. A hook that fails
+/// while running always fails the instruction, as the runtime doesn't let
+/// a caller recover from a failed call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub enum HookFailurePolicy {
+    /// Fail the instruction, so that no recipient is paid without the hook
+    /// hearing of it.
+    Abort,
+    /// Pay the recipient anyway, logging the skipped hook call.
+    Skip,
+}
+
+// Deriving needs `#[default]`, which is newer than the BPF toolchain.
+#[allow(clippy::derivable_impls)]
+impl Default for HookFailurePolicy {
+    fn default() -> Self {
+        HookFailurePolicy::Abort
     }
 }
 
-const DISTRIBUTION_V1_SIZE: usize = PDA_SEED_SIZE + PUBKEY_BYTES + PUBKEY_BYTES + 2 + 2 + 8 + 2;
+const DISTRIBUTION_V1_SIZE: usize = PDA_SEED_V1_SIZE + PUBKEY_BYTES + PUBKEY_BYTES + 2 + 2 + 8 + 2;
+
+/// Size of a version 1 distribution account.
+pub const DISTRIBUTION_V1_LEN: usize = 1 + DISTRIBUTION_V1_SIZE;
 
+/// The state of a distribution before `MigrateDistribution`, kept so that
+/// version 1 accounts can still be read and migrated.
 #[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
 pub struct DistributionV1 {
+    pub pda_seed: PdaSeedV1,
+    pub dist_authority: Pubkey,
+    pub token: Pubkey,
+    pub max_recipients: u16,
+    pub num_recipients: u16,
+    pub funded_amount: u64,
+    pub sent_recipients: u16,
+}
+
+impl DistributionV1 {
+    /// Reads a version 1 distribution account.
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        match src.split_first() {
+            Some((&VERSION_1, data)) if src.len() == DISTRIBUTION_V1_LEN => {
+                Ok(DistributionV1::try_from_slice(data)?)
+            }
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+const DISTRIBUTION_V2_SIZE: usize = PDA_SEED_SIZE
+    + FixedString::<MAX_PROJECT_NAME_LEN>::LEN
+    + PUBKEY_BYTES
+    + PUBKEY_BYTES
+    + 2
+    + 2
+    + 8
+    + 2
+    + PUBKEY_BYTES
+    + 8
+    + 8
+    + HASH_BYTES
+    + 4
+    + 8
+    + PUBKEY_BYTES
+    + 1
+    + 8
+    + PUBKEY_BYTES
+    + 8
+    + 8
+    + 8
+    + 1
+    + HASH_BYTES
+    + 4
+    + 8
+    + 1
+    + 4 * MAX_RAFFLE_WINNERS
+    + 4
+    + PUBKEY_BYTES
+    + 8
+    + PUBKEY_BYTES
+    + 8
+    + 8
+    + 2
+    + 8
+    + 1
+    + 8
+    + PUBKEY_BYTES
+    + 1
+    + 1;
+
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct DistributionV2 {
     pub pda_seed: PdaSeed,
+    pub project_name: FixedString<MAX_PROJECT_NAME_LEN>,
     pub dist_authority: Pubkey,
     pub token: Pubkey,
     pub max_recipients: u16,
     pub num_recipients: u16,
     pub funded_amount: u64,
     pub sent_recipients: u16,
+    // Proposed dist authority that has yet to accept, or the default
+    // pubkey if there is none.
+    pub pending_dist_authority: Pubkey,
+    // Tokens released to the recipients per epoch, or zero for a one-shot
+    // distribution.
+    pub release_rate: u64,
+    pub release_start_epoch: Epoch,
+    // Merkle root of the `recipient_leaf` of each wallet a streaming
+    // distribution may drip to.
+    pub recipients_root: Hash,
+    // Rounds started on this distribution so far. Each round is begun,
+    // funded and paid out like a fresh distribution.
+    pub round: u32,
+    // Amount paid to recipients in the current round.
+    pub paid_amount: u64,
+    // Funder token account delegated to the distribution, or the default
+    // pubkey if the distribution holds the funds in custody.
+    pub funding_source: Pubkey,
+    pub kind: DistributionKind,
+    // Most the distribution may ever mint, for a mint distribution.
+    pub supply_cap: u64,
+    // Token taken from redeemers of a redemption distribution, which pays
+    // `redemption_rate_numerator / redemption_rate_denominator` funded
+    // tokens per input token until `redemption_cap` is paid out.
+    pub input_mint: Pubkey,
+    pub redemption_rate_numerator: u64,
+    pub redemption_rate_denominator: u64,
+    pub redemption_cap: u64,
+    // Whether redeemed input tokens are burned rather than kept in the
+    // distribution's input vault.
+    pub burn_input: bool,
+    // Merkle root of the `(index, wallet)` leaves of a raffle's entrants,
+    // committed when the raffle begins. Winners are drawn from the hash of
+    // `raffle_draw_slot`.
+    pub entrants_root: Hash,
+    pub num_entrants: u32,
+    pub raffle_draw_slot: Slot,
+    pub raffle_drawn: bool,
+    // Entrant indices of the drawn winners, `num_recipients` of them.
+    pub raffle_winners: [u32; MAX_RAFFLE_WINNERS],
+    // Bit `i` is set once `raffle_winners[i]` has been paid.
+    pub raffle_claimed: u32,
+    // Recipients must hold at least `gate_min_balance` of this mint when
+    // paid, unless it's the default pubkey.
+    pub gate_mint: Pubkey,
+    pub gate_min_balance: u64,
+    // Key whose Ed25519 signed vouchers recipients may claim with, or the
+    // default pubkey if vouchers aren't accepted.
+    pub attester: Pubkey,
+    // Most a relayer is reimbursed out of the distribution for each claim it
+    // relays.
+    pub relay_fee_cap: u64,
+    // Each recipient share loses `decay_bps_per_epoch` of itself for every
+    // epoch it goes unpaid past `decay_grace_epochs` epochs after the
+    // distribution began.
+    pub decay_grace_epochs: u64,
+    pub decay_bps_per_epoch: u16,
+    // Amount lost to decay by the recipients paid in the current round,
+    // and by the shares still unclaimed once it's settled.
+    pub forfeited_amount: u64,
+    // Once settled, the forfeited amount of the current round is split
+    // among its paid recipients, `settle_bonus` each. What doesn't split
+    // evenly stays funded.
+    pub settled: bool,
+    pub settle_bonus: u64,
+    // Program called after each payout with `hook_accounts_len` extra
+    // accounts per recipient, or the default pubkey if there is none.
+    pub hook_program: Pubkey,
+    pub hook_accounts_len: u8,
+    pub hook_failure_policy: HookFailurePolicy,
 }
 
-const DISTRIBUTION_SIZE: usize = 1 + DISTRIBUTION_V1_SIZE;
+const DISTRIBUTION_SIZE: usize = 1 + DISTRIBUTION_V2_SIZE;
 
 #[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
 pub struct Distribution {
     pub version: u8,
-    pub data: DistributionV1,
+    pub data: DistributionV2,
 }
 
 impl Distribution {
     pub fn new(
         pda_seed: PdaSeed,
+        project_name: FixedString<MAX_PROJECT_NAME_LEN>,
         dist_authority: Pubkey,
         token: Pubkey,
         max_recipients: u16,
@@ -66,6 +387,7 @@ impl Distribution {
         let mut dist = Distribution::default();
         dist.init(
             pda_seed,
+            project_name,
             dist_authority,
             token,
             max_recipients,
@@ -77,18 +399,49 @@ impl Distribution {
     pub fn init(
         &mut self,
         pda_seed: PdaSeed,
+        project_name: FixedString<MAX_PROJECT_NAME_LEN>,
         dist_authority: Pubkey,
         token: Pubkey,
         max_recipients: u16,
         num_recipients: u16,
     ) {
-        self.version = VERSION_1;
+        self.version = VERSION_2;
         self.data.pda_seed = pda_seed;
+        self.data.project_name = project_name;
         self.data.dist_authority = dist_authority;
         self.data.token = token;
         self.data.max_recipients = max_recipients;
         self.data.num_recipients = num_recipients;
     }
+
+    /// Carries a version 1 distribution over to the current version. It
+    /// has no project name, and keeps its version 1 address. Version 1
+    /// recorded how many recipients were paid but not which, so a
+    /// distribution that paid any moves on to a new round holding what is
+    /// left of its funds.
+    pub fn migrate(dist: DistributionV1) -> Self {
+        let pda_seed = PdaSeed::new(
+            dist.pda_seed.seed,
+            Hash::new_from_array(dist.pda_seed.project_name.to_bytes()),
+            Pubkey::default(),
+            dist.pda_seed.bump,
+        );
+        let mut migrated = Distribution::new(
+            pda_seed,
+            FixedString::default(),
+            dist.dist_authority,
+            dist.token,
+            dist.max_recipients,
+            dist.num_recipients,
+        );
+        migrated.data.funded_amount = dist.funded_amount;
+        if dist.sent_recipients > 0 {
+            migrated.data.sent_recipients = dist.sent_recipients;
+            migrated.record_paid_amount(migrated.recipient_share() * dist.sent_recipients as u64);
+            migrated.start_round();
+        }
+        migrated
+    }
 }
 
 impl Distribution {
@@ -96,6 +449,14 @@ impl Distribution {
         &self.data.pda_seed
     }
 
+    pub fn project_name(&self) -> &str {
+        self.data.project_name.as_str()
+    }
+
+    pub fn creator(&self) -> &Pubkey {
+        &self.data.pda_seed.creator
+    }
+
     pub fn token(&self) -> &Pubkey {
         &self.data.token
     }
@@ -108,6 +469,18 @@ impl Distribution {
         self.data.dist_authority = new_dist_authority;
     }
 
+    pub fn pending_dist_authority(&self) -> Option<&Pubkey> {
+        if self.data.pending_dist_authority == Pubkey::default() {
+            return None;
+        }
+
+        Some(&self.data.pending_dist_authority)
+    }
+
+    pub fn set_pending_dist_authority(&mut self, pending_dist_authority: Option<Pubkey>) {
+        self.data.pending_dist_authority = pending_dist_authority.unwrap_or_default();
+    }
+
     pub fn max_recipients(&self) -> u16 {
         self.data.max_recipients
     }
@@ -133,6 +506,11 @@ impl Distribution {
             return 0;
         }
 
+        // Every recipient of an NFT batch gets a single NFT
+        if self.data.kind == DistributionKind::NftBatch {
+            return 1;
+        }
+
         self.data.funded_amount / self.data.num_recipients as u64
     }
 
@@ -140,6 +518,255 @@ impl Distribution {
         self.data.funded_amount = self.data.funded_amount.checked_add(amount).unwrap();
     }
 
+    pub fn paid_amount(&self) -> u64 {
+        self.data.paid_amount
+    }
+
+    /// Funded amount that has yet to be paid out in the current round.
+    pub fn unpaid_amount(&self) -> u64 {
+        self.data
+            .funded_amount
+            .saturating_sub(self.data.paid_amount)
+    }
+
+    pub fn funding_source(&self) -> Option<&Pubkey> {
+        if self.data.funding_source == Pubkey::default() {
+            return None;
+        }
+
+        Some(&self.data.funding_source)
+    }
+
+    pub fn set_funding_source(&mut self, funding_source: Pubkey) {
+        self.data.funding_source = funding_source;
+    }
+
+    /// The gate mint and the least recipients must hold of it, if the
+    /// distribution is gated.
+    pub fn gate(&self) -> Option<(&Pubkey, u64)> {
+        if self.data.gate_mint == Pubkey::default() {
+            return None;
+        }
+
+        Some((&self.data.gate_mint, self.data.gate_min_balance))
+    }
+
+    pub fn set_gate(&mut self, gate_mint: Pubkey, min_balance: u64) {
+        self.data.gate_mint = gate_mint;
+        self.data.gate_min_balance = min_balance;
+    }
+
+    pub fn attester(&self) -> Option<&Pubkey> {
+        if self.data.attester == Pubkey::default() {
+            return None;
+        }
+
+        Some(&self.data.attester)
+    }
+
+    pub fn set_attester(&mut self, attester: Pubkey) {
+        self.data.attester = attester;
+    }
+
+    pub fn relay_fee_cap(&self) -> u64 {
+        self.data.relay_fee_cap
+    }
+
+    pub fn set_relay_fee_cap(&mut self, relay_fee_cap: u64) {
+        self.data.relay_fee_cap = relay_fee_cap;
+    }
+
+    pub fn hook_program(&self) -> Option<&Pubkey> {
+        if self.data.hook_program == Pubkey::default() {
+            return None;
+        }
+
+        Some(&self.data.hook_program)
+    }
+
+    /// The number of extra accounts each recipient passes to the payout
+    /// hook, zero if there is no hook.
+    pub fn hook_accounts_len(&self) -> usize {
+        match self.hook_program() {
+            Some(_) => self.data.hook_accounts_len as usize,
+            None => 0,
+        }
+    }
+
+    pub fn hook_failure_policy(&self) -> HookFailurePolicy {
+        self.data.hook_failure_policy
+    }
+
+    pub fn set_hook(
+        &mut self,
+        hook_program: Pubkey,
+        hook_accounts_len: u8,
+        failure_policy: HookFailurePolicy,
+    ) {
+        self.data.hook_program = hook_program;
+        self.data.hook_accounts_len = hook_accounts_len;
+        self.data.hook_failure_policy = failure_policy;
+    }
+
+    /// The decay grace period in epochs and the basis points of each share
+    /// lost per epoch after it, if recipient shares decay.
+    pub fn decay(&self) -> Option<(u64, u16)> {
+        if self.data.decay_bps_per_epoch == 0 {
+            return None;
+        }
+
+        Some((self.data.decay_grace_epochs, self.data.decay_bps_per_epoch))
+    }
+
+    pub fn set_decay(&mut self, grace_epochs: u64, bps_per_epoch: u16) {
+        self.data.decay_grace_epochs = grace_epochs;
+        self.data.decay_bps_per_epoch = bps_per_epoch;
+    }
+
+    /// What is left of a recipient share paid at `epoch`, after decay.
+    pub fn decayed_share(&self, epoch: Epoch) -> u64 {
+        let share = self.recipient_share();
+        let (grace_epochs, bps_per_epoch) = match self.decay() {
+            // An NFT can't be paid in part
+            Some(_) if self.data.kind == DistributionKind::NftBatch => return share,
+            Some(decay) => decay,
+            None => return share,
+        };
+
+        let decay_epochs = epoch
+            .saturating_sub(self.data.release_start_epoch)
+            .saturating_sub(grace_epochs);
+        let decayed_bps = (bps_per_epoch as u128 * decay_epochs as u128).min(MAX_DECAY_BPS as u128);
+        (share as u128 * (MAX_DECAY_BPS as u128 - decayed_bps) / MAX_DECAY_BPS as u128) as u64
+    }
+
+    pub fn forfeited_amount(&self) -> u64 {
+        self.data.forfeited_amount
+    }
+
+    pub fn record_forfeited_amount(&mut self, amount: u64) {
+        self.data.forfeited_amount = self.data.forfeited_amount.checked_add(amount).unwrap();
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.data.settled
+    }
+
+    pub fn settle_bonus(&self) -> u64 {
+        self.data.settle_bonus
+    }
+
+    /// Closes the current round to new payouts and splits what was lost to
+    /// decay by `epoch` among its paid recipients, both by their own shares
+    /// and by the shares never claimed. The decayed value of the shares
+    /// never claimed, and the remainder of the split, stay funded for the
+    /// next round. Returns that remainder.
+    pub fn settle(&mut self, epoch: Epoch) -> u64 {
+        let unclaimed_recipients = self
+            .data
+            .num_recipients
+            .saturating_sub(self.data.sent_recipients);
+        let unclaimed_forfeit = (self.recipient_share() - self.decayed_share(epoch))
+            .checked_mul(unclaimed_recipients as u64)
+            .unwrap();
+        self.record_forfeited_amount(unclaimed_forfeit);
+
+        self.data.settled = true;
+        if self.data.sent_recipients == 0 {
+            return self.data.forfeited_amount;
+        }
+
+        self.data.settle_bonus = self.data.forfeited_amount / self.data.sent_recipients as u64;
+        self.data.forfeited_amount % self.data.sent_recipients as u64
+    }
+
+    pub fn record_paid_amount(&mut self, amount: u64) {
+        self.data.paid_amount = self.data.paid_amount.checked_add(amount).unwrap();
+    }
+
+    pub fn round(&self) -> u32 {
+        self.data.round
+    }
+
+    /// Closes the current round and opens the next one. Whatever wasn't paid
+    /// out stays funded for the next round.
+    pub fn start_round(&mut self) {
+        self.data.funded_amount = self
+            .data
+            .funded_amount
+            .saturating_sub(self.data.paid_amount);
+        self.data.paid_amount = 0;
+        self.data.num_recipients = 0;
+        self.data.sent_recipients = 0;
+        self.data.forfeited_amount = 0;
+        self.data.settled = false;
+        self.data.settle_bonus = 0;
+        self.data.round += 1;
+
+        // Each round draws its own raffle, if any
+        if self.data.kind == DistributionKind::Raffle {
+            self.data.kind = DistributionKind::Escrow;
+            self.data.entrants_root = Hash::default();
+            self.data.num_entrants = 0;
+            self.data.raffle_draw_slot = 0;
+            self.data.raffle_drawn = false;
+            self.data.raffle_winners = [0; MAX_RAFFLE_WINNERS];
+            self.data.raffle_claimed = 0;
+        }
+    }
+
+    /// Turns this into a raffle between `num_entrants` entrants, drawn from
+    /// the hash of `draw_slot`.
+    pub fn commit_raffle(&mut self, entrants_root: Hash, num_entrants: u32, draw_slot: Slot) {
+        self.data.kind = DistributionKind::Raffle;
+        self.data.entrants_root = entrants_root;
+        self.data.num_entrants = num_entrants;
+        self.data.raffle_draw_slot = draw_slot;
+    }
+
+    pub fn entrants_root(&self) -> &Hash {
+        &self.data.entrants_root
+    }
+
+    pub fn num_entrants(&self) -> u32 {
+        self.data.num_entrants
+    }
+
+    pub fn raffle_draw_slot(&self) -> Slot {
+        self.data.raffle_draw_slot
+    }
+
+    pub fn set_raffle_draw_slot(&mut self, draw_slot: Slot) {
+        self.data.raffle_draw_slot = draw_slot;
+    }
+
+    pub fn raffle_drawn(&self) -> bool {
+        self.data.raffle_drawn
+    }
+
+    /// Entrant indices of the winners, empty until the raffle is drawn.
+    pub fn raffle_winners(&self) -> &[u32] {
+        if !self.data.raffle_drawn {
+            return &[];
+        }
+
+        &self.data.raffle_winners[..self.data.num_recipients as usize]
+    }
+
+    /// Records the drawn winners. There must be one per recipient.
+    pub fn set_raffle_winners(&mut self, winners: &[u32]) {
+        self.data.raffle_winners[..winners.len()].copy_from_slice(winners);
+        self.data.raffle_drawn = true;
+    }
+
+    pub fn is_raffle_prize_claimed(&self, winner: usize) -> bool {
+        self.data.raffle_claimed & (1 << winner) != 0
+    }
+
+    pub fn record_raffle_prize_claimed(&mut self, winner: usize) {
+        self.data.raffle_claimed |= 1 << winner;
+    }
+
     pub fn sent_recipients(&self) -> u16 {
         self.data.sent_recipients
     }
@@ -151,13 +778,137 @@ impl Distribution {
     pub fn has_started(&self) -> bool {
         self.data.num_recipients > 0
     }
+
+    pub fn release_rate(&self) -> u64 {
+        self.data.release_rate
+    }
+
+    pub fn set_release_rate(&mut self, release_rate: u64) {
+        self.data.release_rate = release_rate;
+    }
+
+    pub fn recipients_root(&self) -> &Hash {
+        &self.data.recipients_root
+    }
+
+    pub fn set_recipients_root(&mut self, recipients_root: Hash) {
+        self.data.recipients_root = recipients_root;
+    }
+
+    pub fn kind(&self) -> DistributionKind {
+        self.data.kind
+    }
+
+    /// Turns this into an NFT batch distribution, funded by depositing NFTs
+    /// into its pool.
+    pub fn set_nft_batch(&mut self) {
+        self.data.kind = DistributionKind::NftBatch;
+    }
+
+    /// Turns this into a redemption distribution. Redeemed input tokens are
+    /// burned when `burn_input` is set and kept in the distribution's input
+    /// vault otherwise.
+    pub fn set_redemption(
+        &mut self,
+        input_mint: Pubkey,
+        rate_numerator: u64,
+        rate_denominator: u64,
+        cap: u64,
+        burn_input: bool,
+    ) {
+        self.data.kind = DistributionKind::Redemption;
+        self.data.input_mint = input_mint;
+        self.data.redemption_rate_numerator = rate_numerator;
+        self.data.redemption_rate_denominator = rate_denominator;
+        self.data.redemption_cap = cap;
+        self.data.burn_input = burn_input;
+    }
+
+    pub fn input_mint(&self) -> &Pubkey {
+        &self.data.input_mint
+    }
+
+    pub fn redemption_rate(&self) -> (u64, u64) {
+        (
+            self.data.redemption_rate_numerator,
+            self.data.redemption_rate_denominator,
+        )
+    }
+
+    pub fn redemption_cap(&self) -> u64 {
+        self.data.redemption_cap
+    }
+
+    pub fn burn_input(&self) -> bool {
+        self.data.burn_input
+    }
+
+    /// Funded tokens paid for redeeming `amount` input tokens, or `None` if
+    /// the rate isn't set.
+    pub fn redemption_output(&self, amount: u64) -> Option<u64> {
+        if self.data.redemption_rate_denominator == 0 {
+            return None;
+        }
+
+        let output = amount as u128 * self.data.redemption_rate_numerator as u128
+            / self.data.redemption_rate_denominator as u128;
+        u64::try_from(output).ok()
+    }
+
+    /// Funded tokens that can still be paid to redeemers: the lesser of the
+    /// unpaid funded amount and what's left under the cap.
+    pub fn redeemable_amount(&self) -> u64 {
+        self.unpaid_amount().min(
+            self.data
+                .redemption_cap
+                .saturating_sub(self.data.paid_amount),
+        )
+    }
+
+    pub fn supply_cap(&self) -> u64 {
+        self.data.supply_cap
+    }
+
+    /// Turns this into a mint distribution. The whole supply cap counts as
+    /// funded, so each recipient share is minted out of it.
+    pub fn set_mint_supply_cap(&mut self, supply_cap: u64) {
+        self.data.kind = DistributionKind::Mint;
+        self.data.supply_cap = supply_cap;
+        self.data.funded_amount = supply_cap;
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.data.release_rate > 0
+    }
+
+    pub fn release_start_epoch(&self) -> Epoch {
+        self.data.release_start_epoch
+    }
+
+    pub fn set_release_start_epoch(&mut self, epoch: Epoch) {
+        self.data.release_start_epoch = epoch;
+    }
+
+    /// The part of each recipient's share a streaming distribution has
+    /// released by `epoch`.
+    pub fn released_share(&self, epoch: Epoch) -> u64 {
+        if self.data.num_recipients == 0 {
+            return 0;
+        }
+
+        let elapsed_epochs = epoch.saturating_sub(self.data.release_start_epoch);
+        let released = (self.data.release_rate as u128 * elapsed_epochs as u128)
+            .min(self.data.funded_amount as u128) as u64;
+
+        released / self.data.num_recipients as u64
+    }
 }
 
 impl Default for Distribution {
     fn default() -> Self {
         return Distribution {
             version: UNINITIALIZED_VERSION,
-            data: DistributionV1::default(),
+            data: DistributionV2::default(),
         };
     }
 }
@@ -178,7 +929,8 @@ impl Pack for Distribution {
         if version == UNINITIALIZED_VERSION {
             return Ok(Distribution::default());
         }
-        if version == VERSION_1 {
+        // Version 1 accounts are smaller and must be migrated first
+        if version == VERSION_2 {
             return Ok(Distribution::try_from_slice(src)?);
         }
         Err(ProgramError::InvalidAccountData)
@@ -189,3 +941,795 @@ impl Pack for Distribution {
         self.serialize(&mut bw).unwrap()
     }
 }
+
+/// Seed prefix of the metadata account that belongs to a distribution.
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+pub const MAX_URI_LEN: usize = 200;
+
+pub const MAX_CREATOR_LABEL_LEN: usize = 64;
+
+pub const MAX_CATEGORY_LEN: usize = 32;
+
+const DISTRIBUTION_METADATA_SIZE: usize = 1
+    + PUBKEY_BYTES
+    + 1
+    + FixedString::<MAX_DESCRIPTION_LEN>::LEN
+    + FixedString::<MAX_URI_LEN>::LEN
+    + FixedString::<MAX_CREATOR_LABEL_LEN>::LEN
+    + FixedString::<MAX_CATEGORY_LEN>::LEN;
+
+/// Descriptive information about a distribution, kept in its own account
+/// so it can change without touching the distribution state.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct DistributionMetadata {
+    pub version: u8,
+    pub distribution: Pubkey,
+    pub bump: u8,
+    pub description: FixedString<MAX_DESCRIPTION_LEN>,
+    // Link to the off-chain campaign page.
+    pub uri: FixedString<MAX_URI_LEN>,
+    pub creator_label: FixedString<MAX_CREATOR_LABEL_LEN>,
+    pub category: FixedString<MAX_CATEGORY_LEN>,
+}
+
+impl DistributionMetadata {
+    pub fn init(&mut self, distribution: Pubkey, bump: u8) {
+        self.version = VERSION_1;
+        self.distribution = distribution;
+        self.bump = bump;
+    }
+
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [
+            METADATA_SEED,
+            self.distribution.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+impl Sealed for DistributionMetadata {}
+
+impl IsInitialized for DistributionMetadata {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl Pack for DistributionMetadata {
+    const LEN: usize = DISTRIBUTION_METADATA_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = src[0];
+        if version == UNINITIALIZED_VERSION {
+            return Ok(DistributionMetadata::default());
+        }
+        if version == VERSION_1 {
+            return Ok(DistributionMetadata::try_from_slice(src)?);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut bw = BufWriter::with_capacity(Self::LEN, dst);
+        self.serialize(&mut bw).unwrap()
+    }
+}
+
+/// Seed of the program-wide configuration account.
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// The most mints the configuration can allow.
+pub const MAX_ALLOWED_MINTS: usize = 16;
+
+/// Protocol fees are expressed in basis points of the funded amount.
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 10_000;
+
+const PROGRAM_CONFIG_SIZE: usize =
+    1 + PUBKEY_BYTES + 1 + 1 + 2 + PUBKEY_BYTES + 1 + PUBKEY_BYTES * MAX_ALLOWED_MINTS;
+
+/// Program-wide settings controlled by the program admin.
+///
+/// Until the configuration is initialized, distributions run without a
+/// protocol fee or mint restrictions.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct ProgramConfig {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub bump: u8,
+    // Blocks new distributions, funding and payouts while set.
+    pub paused: bool,
+    pub protocol_fee_bps: u16,
+    // Owner of the token accounts that collect the protocol fee.
+    pub treasury: Pubkey,
+    pub num_allowed_mints: u8,
+    // An empty allowlist allows every mint.
+    pub allowed_mints: [Pubkey; MAX_ALLOWED_MINTS],
+}
+
+impl ProgramConfig {
+    pub fn init(&mut self, admin: Pubkey, bump: u8) {
+        self.version = VERSION_1;
+        self.admin = admin;
+        self.bump = bump;
+    }
+
+    pub fn allowed_mints(&self) -> &[Pubkey] {
+        &self.allowed_mints[..self.num_allowed_mints as usize]
+    }
+
+    /// Returns `false` if there are more mints than fit in the allowlist.
+    pub fn set_allowed_mints(&mut self, mints: &[Pubkey]) -> bool {
+        if mints.len() > MAX_ALLOWED_MINTS {
+            return false;
+        }
+        self.allowed_mints = Default::default();
+        self.allowed_mints[..mints.len()].copy_from_slice(mints);
+        self.num_allowed_mints = mints.len() as u8;
+        true
+    }
+
+    pub fn is_mint_allowed(&self, mint: &Pubkey) -> bool {
+        self.num_allowed_mints == 0 || self.allowed_mints().contains(mint)
+    }
+
+    /// The part of a funded amount that goes to the treasury.
+    pub fn protocol_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.protocol_fee_bps as u128 / MAX_PROTOCOL_FEE_BPS as u128) as u64
+    }
+}
+
+impl Sealed for ProgramConfig {}
+
+impl IsInitialized for ProgramConfig {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl Pack for ProgramConfig {
+    const LEN: usize = PROGRAM_CONFIG_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = src[0];
+        if version == UNINITIALIZED_VERSION {
+            return Ok(ProgramConfig::default());
+        }
+        if version == VERSION_1 {
+            return Ok(ProgramConfig::try_from_slice(src)?);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut bw = BufWriter::with_capacity(Self::LEN, dst);
+        self.serialize(&mut bw).unwrap()
+    }
+}
+
+/// Seed prefix of the receipt account kept for each paid recipient.
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+const RECIPIENT_RECEIPT_SIZE: usize = 1 + PUBKEY_BYTES + PUBKEY_BYTES + 1 + 8 + 4 + 8;
+
+/// Proof that a recipient wallet has been paid by a distribution. A receipt
+/// from an earlier round is reused when the recipient is paid again.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct RecipientReceipt {
+    pub version: u8,
+    pub distribution: Pubkey,
+    pub recipient: Pubkey,
+    pub bump: u8,
+    // Amount paid in `round`.
+    pub amount: u64,
+    pub round: u32,
+    // Settle bonus paid in `round`, zero until it's paid.
+    pub settle_bonus: u64,
+}
+
+impl RecipientReceipt {
+    pub fn init(
+        &mut self,
+        distribution: Pubkey,
+        recipient: Pubkey,
+        bump: u8,
+        round: u32,
+        amount: u64,
+    ) {
+        self.version = VERSION_1;
+        self.distribution = distribution;
+        self.recipient = recipient;
+        self.bump = bump;
+        self.round = round;
+        self.amount = amount;
+    }
+
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            RECEIPT_SEED,
+            self.distribution.as_ref(),
+            self.recipient.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+impl Sealed for RecipientReceipt {}
+
+impl IsInitialized for RecipientReceipt {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl Pack for RecipientReceipt {
+    const LEN: usize = RECIPIENT_RECEIPT_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = src[0];
+        if version == UNINITIALIZED_VERSION {
+            return Ok(RecipientReceipt::default());
+        }
+        if version == VERSION_1 {
+            return Ok(RecipientReceipt::try_from_slice(src)?);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut bw = BufWriter::with_capacity(Self::LEN, dst);
+        self.serialize(&mut bw).unwrap()
+    }
+}
+
+/// Seed prefix of the summary account written when a round closes.
+pub const ROUND_SEED: &[u8] = b"round";
+
+const ROUND_SUMMARY_SIZE: usize = 1 + PUBKEY_BYTES + 4 + 1 + 2 + 2 + 8 + 8;
+
+/// Totals of a closed distribution round.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct RoundSummary {
+    pub version: u8,
+    pub distribution: Pubkey,
+    pub round: u32,
+    pub bump: u8,
+    pub num_recipients: u16,
+    pub sent_recipients: u16,
+    pub funded_amount: u64,
+    pub paid_amount: u64,
+}
+
+impl RoundSummary {
+    pub fn init(&mut self, distribution: Pubkey, round: u32, bump: u8) {
+        self.version = VERSION_1;
+        self.distribution = distribution;
+        self.round = round;
+        self.bump = bump;
+    }
+}
+
+impl Sealed for RoundSummary {}
+
+impl IsInitialized for RoundSummary {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl Pack for RoundSummary {
+    const LEN: usize = ROUND_SUMMARY_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = src[0];
+        if version == UNINITIALIZED_VERSION {
+            return Ok(RoundSummary::default());
+        }
+        if version == VERSION_1 {
+            return Ok(RoundSummary::try_from_slice(src)?);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut bw = BufWriter::with_capacity(Self::LEN, dst);
+        self.serialize(&mut bw).unwrap()
+    }
+}
+
+/// Seed prefix of the entry that puts a wallet on the program-wide denylist.
+pub const DENYLIST_SEED: &[u8] = b"denylist";
+
+const DENYLIST_ENTRY_SIZE: usize = 1 + PUBKEY_BYTES + 1;
+
+/// Marks a wallet that no distribution may pay. The entry exists for as long
+/// as the wallet is denylisted.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct DenylistEntry {
+    pub version: u8,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+impl DenylistEntry {
+    pub fn init(&mut self, wallet: Pubkey, bump: u8) {
+        self.version = VERSION_1;
+        self.wallet = wallet;
+        self.bump = bump;
+    }
+
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [
+            DENYLIST_SEED,
+            self.wallet.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+impl Sealed for DenylistEntry {}
+
+impl IsInitialized for DenylistEntry {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl Pack for DenylistEntry {
+    const LEN: usize = DENYLIST_ENTRY_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = src[0];
+        if version == UNINITIALIZED_VERSION {
+            return Ok(DenylistEntry::default());
+        }
+        if version == VERSION_1 {
+            return Ok(DenylistEntry::try_from_slice(src)?);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut bw = BufWriter::with_capacity(Self::LEN, dst);
+        self.serialize(&mut bw).unwrap()
+    }
+}
+
+/// Seed prefix of the token accounts an NFT batch distribution holds its
+/// NFTs in, one per mint. Pool items are plain token accounts owned by the
+/// distribution.
+pub const POOL_ITEM_SEED: &[u8] = b"pool_item";
+
+/// Seed prefix of the grant account kept for each grant recipient.
+pub const GRANT_SEED: &[u8] = b"grant";
+
+/// Seed prefix of the token account holding a grant's unreleased tokens.
+/// Grant vaults are plain token accounts owned by the distribution.
+pub const GRANT_VAULT_SEED: &[u8] = b"grant_vault";
+
+const GRANT_SIZE: usize = 1 + PUBKEY_BYTES + PUBKEY_BYTES + 1 + 8 + 8 + 8 + 8 + 1;
+
+/// Tokens held in escrow for one recipient of a distribution, vesting
+/// linearly from `start_epoch` to `end_epoch`.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Grant {
+    pub version: u8,
+    pub distribution: Pubkey,
+    pub recipient: Pubkey,
+    pub bump: u8,
+    // Revoking a grant cuts this down to what had vested by then.
+    pub amount: u64,
+    pub released: u64,
+    pub start_epoch: Epoch,
+    pub end_epoch: Epoch,
+    pub revoked: bool,
+}
+
+impl Grant {
+    pub fn init(
+        &mut self,
+        distribution: Pubkey,
+        recipient: Pubkey,
+        bump: u8,
+        amount: u64,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+    ) {
+        self.version = VERSION_1;
+        self.distribution = distribution;
+        self.recipient = recipient;
+        self.bump = bump;
+        self.amount = amount;
+        self.start_epoch = start_epoch;
+        self.end_epoch = end_epoch;
+    }
+
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            GRANT_SEED,
+            self.distribution.as_ref(),
+            self.recipient.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+
+    /// How much of the grant has vested by `epoch`.
+    pub fn vested_amount(&self, epoch: Epoch) -> u64 {
+        if self.revoked || epoch >= self.end_epoch {
+            return self.amount;
+        }
+        if epoch <= self.start_epoch {
+            return 0;
+        }
+
+        let elapsed = (epoch - self.start_epoch) as u128;
+        let duration = (self.end_epoch - self.start_epoch) as u128;
+        (self.amount as u128 * elapsed / duration) as u64
+    }
+
+    /// How much of the grant has vested by `epoch` but not been released.
+    pub fn releasable_amount(&self, epoch: Epoch) -> u64 {
+        self.vested_amount(epoch).saturating_sub(self.released)
+    }
+
+    /// Cuts the grant down to what has vested by `epoch` and returns the
+    /// unvested rest.
+    pub fn revoke(&mut self, epoch: Epoch) -> u64 {
+        let vested = self.vested_amount(epoch);
+        let unvested = self.amount - vested;
+        self.amount = vested;
+        self.revoked = true;
+        unvested
+    }
+
+    pub fn record_released(&mut self, amount: u64) {
+        self.released += amount;
+    }
+}
+
+impl Sealed for Grant {}
+
+impl IsInitialized for Grant {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl Pack for Grant {
+    const LEN: usize = GRANT_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = src[0];
+        if version == UNINITIALIZED_VERSION {
+            return Ok(Grant::default());
+        }
+        if version == VERSION_1 {
+            return Ok(Grant::try_from_slice(src)?);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut bw = BufWriter::with_capacity(Self::LEN, dst);
+        self.serialize(&mut bw).unwrap()
+    }
+}
+
+/// Seed prefix of the account forwarding a recipient's payouts.
+pub const FORWARD_SEED: &[u8] = b"forward";
+
+const FORWARD_ADDRESS_SIZE: usize = 1 + PUBKEY_BYTES + PUBKEY_BYTES + 1 + PUBKEY_BYTES;
+
+/// A token account that a recipient wallet has its payouts from a
+/// distribution sent to instead of its own.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct ForwardAddress {
+    pub version: u8,
+    pub distribution: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8,
+    pub destination: Pubkey,
+}
+
+impl ForwardAddress {
+    pub fn init(&mut self, distribution: Pubkey, wallet: Pubkey, bump: u8) {
+        self.version = VERSION_1;
+        self.distribution = distribution;
+        self.wallet = wallet;
+        self.bump = bump;
+    }
+
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            FORWARD_SEED,
+            self.distribution.as_ref(),
+            self.wallet.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+impl Sealed for ForwardAddress {}
+
+impl IsInitialized for ForwardAddress {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl Pack for ForwardAddress {
+    const LEN: usize = FORWARD_ADDRESS_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = src[0];
+        if version == UNINITIALIZED_VERSION {
+            return Ok(ForwardAddress::default());
+        }
+        if version == VERSION_1 {
+            return Ok(ForwardAddress::try_from_slice(src)?);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut bw = BufWriter::with_capacity(Self::LEN, dst);
+        self.serialize(&mut bw).unwrap()
+    }
+}
+
+/// Seed prefix of the account marking a voucher nonce of a recipient as used.
+pub const VOUCHER_SEED: &[u8] = b"voucher";
+
+const USED_VOUCHER_SIZE: usize = 1 + PUBKEY_BYTES + PUBKEY_BYTES + 8 + 1;
+
+/// Marks a voucher as claimed. The account is created on the voucher's
+/// claim, so each nonce of a recipient can only be claimed once.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct UsedVoucher {
+    pub version: u8,
+    pub distribution: Pubkey,
+    pub recipient: Pubkey,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+impl UsedVoucher {
+    pub fn init(&mut self, distribution: Pubkey, recipient: Pubkey, nonce: u64, bump: u8) {
+        self.version = VERSION_1;
+        self.distribution = distribution;
+        self.recipient = recipient;
+        self.nonce = nonce;
+        self.bump = bump;
+    }
+}
+
+impl Sealed for UsedVoucher {}
+
+impl IsInitialized for UsedVoucher {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl Pack for UsedVoucher {
+    const LEN: usize = USED_VOUCHER_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = src[0];
+        if version == UNINITIALIZED_VERSION {
+            return Ok(UsedVoucher::default());
+        }
+        if version == VERSION_1 {
+            return Ok(UsedVoucher::try_from_slice(src)?);
+        }
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut bw = BufWriter::with_capacity(Self::LEN, dst);
+        self.serialize(&mut bw).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version_1_account(dist: &DistributionV1) -> Vec<u8> {
+        let mut data = vec![VERSION_1];
+        data.extend_from_slice(&dist.try_to_vec().unwrap());
+        data
+    }
+
+    fn distribution_v1(sent_recipients: u16) -> DistributionV1 {
+        let program_id = Pubkey::new_unique();
+        let seed = Pubkey::new_unique();
+        let project_name = Pubkey::new_unique();
+        let (_, bump) =
+            Pubkey::find_program_address(&[seed.as_ref(), project_name.as_ref()], &program_id);
+        DistributionV1 {
+            pda_seed: PdaSeedV1 {
+                seed,
+                project_name,
+                bump,
+            },
+            dist_authority: Pubkey::new_unique(),
+            token: Pubkey::new_unique(),
+            max_recipients: 10,
+            num_recipients: 4,
+            funded_amount: 1_000,
+            sent_recipients,
+        }
+    }
+
+    #[test]
+    fn reads_version_1_accounts() {
+        let dist = distribution_v1(0);
+        let data = version_1_account(&dist);
+        assert_eq!(data.len(), DISTRIBUTION_V1_LEN);
+        assert_eq!(DistributionV1::unpack(&data), Ok(dist));
+
+        // Only once migrated can they be read as the current version
+        assert_eq!(
+            Distribution::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn migrated_distribution_keeps_its_address() {
+        let program_id = Pubkey::new_unique();
+        let mut dist = distribution_v1(0);
+        let seeds = [
+            dist.pda_seed.seed.as_ref(),
+            dist.pda_seed.project_name.as_ref(),
+        ];
+        let (address, bump) = Pubkey::find_program_address(&seeds, &program_id);
+        dist.pda_seed.bump = bump;
+
+        let migrated = Distribution::migrate(dist.clone());
+        assert!(migrated.is_initialized());
+        assert_eq!(migrated.pda_seed().create_pubkey(&program_id), Ok(address));
+        assert_eq!(migrated.dist_authority(), &dist.dist_authority);
+        assert_eq!(migrated.token(), &dist.token);
+        assert_eq!(migrated.max_recipients(), 10);
+        assert_eq!(migrated.num_recipients(), 4);
+        assert_eq!(migrated.funded_amount(), 1_000);
+        assert_eq!(migrated.round(), 0);
+
+        let mut data = vec![0; Distribution::LEN];
+        Distribution::pack(migrated.clone(), &mut data).unwrap();
+        assert_eq!(Distribution::unpack(&data), Ok(migrated));
+    }
+
+    #[test]
+    fn migrated_distribution_moves_on_from_a_paid_round() {
+        let migrated = Distribution::migrate(distribution_v1(3));
+        assert_eq!(migrated.round(), 1);
+        assert_eq!(migrated.num_recipients(), 0);
+        assert_eq!(migrated.sent_recipients(), 0);
+        assert_eq!(migrated.funded_amount(), 250);
+    }
+
+    fn grant(amount: u64, start_epoch: Epoch, end_epoch: Epoch) -> Grant {
+        let mut grant = Grant::default();
+        grant.init(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            255,
+            amount,
+            start_epoch,
+            end_epoch,
+        );
+        grant
+    }
+
+    #[test]
+    fn grant_vests_linearly() {
+        let grant = grant(1_000, 10, 20);
+
+        assert_eq!(grant.vested_amount(0), 0);
+        assert_eq!(grant.vested_amount(10), 0);
+        assert_eq!(grant.vested_amount(11), 100);
+        assert_eq!(grant.vested_amount(15), 500);
+        assert_eq!(grant.vested_amount(19), 900);
+        assert_eq!(grant.vested_amount(20), 1_000);
+        assert_eq!(grant.vested_amount(Epoch::MAX), 1_000);
+    }
+
+    #[test]
+    fn grant_without_duration_vests_at_end() {
+        let grant = grant(1_000, 10, 10);
+
+        assert_eq!(grant.vested_amount(9), 0);
+        assert_eq!(grant.vested_amount(10), 1_000);
+    }
+
+    #[test]
+    fn grant_vesting_rounds_down_without_overflow() {
+        let grant = grant(u64::MAX, 0, 3);
+
+        assert_eq!(grant.vested_amount(1), u64::MAX / 3);
+        assert_eq!(grant.vested_amount(3), u64::MAX);
+    }
+
+    #[test]
+    fn revoked_grant_keeps_what_vested() {
+        let mut grant = grant(1_000, 10, 20);
+        grant.released = 200;
+
+        assert_eq!(grant.revoke(14), 600);
+        assert_eq!(grant.vested_amount(14), 400);
+        assert_eq!(grant.vested_amount(20), 400);
+        assert_eq!(grant.releasable_amount(20), 200);
+    }
+
+    fn distribution(funded_amount: u64, decay: Option<(u64, u16)>) -> Distribution {
+        let mut dist = Distribution::default();
+        dist.record_funded_amount(funded_amount);
+        dist.set_num_recipients(1);
+        dist.set_release_start_epoch(10);
+        if let Some((grace_epochs, bps_per_epoch)) = decay {
+            dist.set_decay(grace_epochs, bps_per_epoch);
+        }
+        dist
+    }
+
+    #[test]
+    fn share_decays_after_grace_epochs() {
+        let dist = distribution(1_000, Some((2, 2_500)));
+
+        assert_eq!(dist.decayed_share(0), 1_000);
+        assert_eq!(dist.decayed_share(12), 1_000);
+        assert_eq!(dist.decayed_share(13), 750);
+        assert_eq!(dist.decayed_share(15), 250);
+        assert_eq!(dist.decayed_share(16), 0);
+        assert_eq!(dist.decayed_share(17), 0);
+    }
+
+    #[test]
+    fn share_decays_at_most_to_nothing() {
+        let dist = distribution(1_000, Some((0, MAX_DECAY_BPS)));
+        assert_eq!(dist.decayed_share(10), 1_000);
+        assert_eq!(dist.decayed_share(11), 0);
+
+        let dist = distribution(u64::MAX, Some((0, u16::MAX)));
+        assert_eq!(dist.decayed_share(Epoch::MAX), 0);
+
+        let dist = distribution(10_000, Some((0, MAX_DECAY_BPS - 1)));
+        assert_eq!(dist.decayed_share(11), 1);
+        assert_eq!(dist.decayed_share(12), 0);
+    }
+
+    #[test]
+    fn settle_splits_unclaimed_forfeits_and_keeps_the_remainder() {
+        let mut dist = distribution(900, Some((0, 1_000)));
+        dist.set_num_recipients(3);
+        dist.record_sent_recipient(Pubkey::new_unique());
+        dist.record_sent_recipient(Pubkey::new_unique());
+        dist.record_paid_amount(300 + 239);
+        dist.record_forfeited_amount(61);
+
+        // The unclaimed share of 300 has lost 90 by then
+        assert_eq!(dist.settle(13), 1);
+        assert_eq!(dist.forfeited_amount(), 151);
+        assert_eq!(dist.settle_bonus(), 75);
+
+        // What's left of the unclaimed share and the remainder stay funded
+        dist.record_paid_amount(2 * 75);
+        assert_eq!(dist.unpaid_amount(), 210 + 1);
+    }
+
+    #[test]
+    fn share_without_decay_is_whole() {
+        let dist = distribution(1_000, None);
+        assert_eq!(dist.decayed_share(Epoch::MAX), 1_000);
+
+        let mut dist = distribution(1_000, Some((0, 5_000)));
+        dist.data.kind = DistributionKind::NftBatch;
+        assert_eq!(dist.decayed_share(Epoch::MAX), 1);
+    }
+}
@@ -0,0 +1,452 @@
+//! A small stand-in for the runtime, to run instructions of this program
+//! and the programs it calls without a validator.
+//!
+//! Accounts live in memory for the length of a test. Cross-program
+//! invocations go through the syscall stubs, which check the accounts and
+//! signatures like the runtime does and dispatch to the token programs, a
+//! minimal system program or any program added with [`add_program`].
+
+#![allow(dead_code)]
+
+use std::{cell::RefCell, collections::HashMap, sync::Once};
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::{Clock, Epoch},
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::SystemInstruction,
+    system_program, sysvar,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use wave_dist::instruction;
+
+pub type ProcessInstruction = fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+thread_local! {
+    static PROGRAMS: RefCell<HashMap<Pubkey, ProcessInstruction>> = RefCell::new(HashMap::new());
+    static CALL_STACK: RefCell<Vec<Pubkey>> = const { RefCell::new(vec![]) };
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+    static RETURN_DATA: RefCell<Option<(Pubkey, Vec<u8>)>> = const { RefCell::new(None) };
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+}
+
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+
+        if !account_infos
+            .iter()
+            .any(|account_info| *account_info.key == instruction.program_id)
+        {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let caller = CALL_STACK.with(|call_stack| *call_stack.borrow().last().unwrap());
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut accounts = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let mut account_info = account_infos
+                .iter()
+                .find(|account_info| *account_info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?
+                .clone();
+            if meta.is_writable && !account_info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if meta.is_signer && !account_info.is_signer && !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            account_info.is_signer = meta.is_signer;
+            account_info.is_writable = meta.is_writable;
+            accounts.push(account_info);
+        }
+
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = None);
+        process(&instruction.program_id, &accounts, &instruction.data)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Clock) = CLOCK.with(|clock| clock.borrow().clone()) };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        let program_id = CALL_STACK.with(|call_stack| *call_stack.borrow().last().unwrap());
+        RETURN_DATA
+            .with(|return_data| *return_data.borrow_mut() = Some((program_id, data.to_vec())));
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RETURN_DATA.with(|return_data| return_data.borrow().clone())
+    }
+}
+
+/// Adds a program that instructions and invocations can be sent to.
+pub fn add_program(program_id: Pubkey, process_instruction: ProcessInstruction) {
+    PROGRAMS.with(|programs| {
+        programs
+            .borrow_mut()
+            .insert(program_id, process_instruction)
+    });
+}
+
+/// Moves the clock to `epoch`.
+pub fn set_epoch(epoch: Epoch) {
+    CLOCK.with(|clock| clock.borrow_mut().epoch = epoch);
+}
+
+/// The instructions invoked by programs since the last [`Env::new`].
+pub fn invoked() -> Vec<Instruction> {
+    INVOKED.with(|invoked| invoked.borrow().clone())
+}
+
+fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+    let process_instruction = if *program_id == system_program::id() {
+        process_system_instruction
+    } else if *program_id == spl_token::id() {
+        spl_token::processor::Processor::process
+    } else if *program_id == spl_associated_token_account::id() {
+        spl_associated_token_account::processor::process_instruction
+    } else if *program_id == wave_dist::id() {
+        wave_dist::processor::process_instruction
+    } else {
+        PROGRAMS
+            .with(|programs| programs.borrow().get(program_id).copied())
+            .ok_or(ProgramError::IncorrectProgramId)?
+    };
+
+    CALL_STACK.with(|call_stack| call_stack.borrow_mut().push(*program_id));
+    let result = process_instruction(program_id, accounts, input);
+    CALL_STACK.with(|call_stack| call_stack.borrow_mut().pop());
+    result
+}
+
+/// The instructions of the system program the programs under test use.
+fn process_system_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    match bincode::deserialize(input).map_err(|_| ProgramError::InvalidInstructionData)? {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            let (from, to) = (&accounts[0], &accounts[1]);
+            if !from.is_signer || !to.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if to.lamports() > 0 || !to.data_is_empty() || *to.owner != system_program::id() {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            move_lamports(from, to, lamports)?;
+            allocate(to, space);
+            to.assign(&owner);
+        }
+        SystemInstruction::Transfer { lamports } => {
+            if !accounts[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            move_lamports(&accounts[0], &accounts[1], lamports)?;
+        }
+        SystemInstruction::Allocate { space } => {
+            if !accounts[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if !accounts[0].data_is_empty() {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            allocate(&accounts[0], space);
+        }
+        SystemInstruction::Assign { owner } => {
+            if !accounts[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            accounts[0].assign(&owner);
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    let from_lamports = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+fn allocate(account_info: &AccountInfo, space: u64) {
+    *account_info.data.borrow_mut() = Box::leak(vec![0; space as usize].into_boxed_slice());
+}
+
+/// The accounts of a test, which instructions are run against.
+pub struct Env {
+    accounts: HashMap<Pubkey, AccountInfo<'static>>,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(TestStubs));
+        });
+        INVOKED.with(|invoked| invoked.borrow_mut().clear());
+        CLOCK.with(|clock| *clock.borrow_mut() = Clock::default());
+
+        let mut env = Env {
+            accounts: HashMap::new(),
+        };
+        for program_id in [
+            system_program::id(),
+            spl_token::id(),
+            spl_associated_token_account::id(),
+            wave_dist::id(),
+        ] {
+            env.add_program_account(program_id);
+        }
+        env.add_account(
+            sysvar::rent::id(),
+            1,
+            bincode::serialize(&Rent::default()).unwrap(),
+            sysvar::id(),
+        );
+        env
+    }
+
+    pub fn add_account(&mut self, key: Pubkey, lamports: u64, data: Vec<u8>, owner: Pubkey) {
+        let account_info = AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            false,
+            Box::leak(Box::new(lamports)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        );
+        self.accounts.insert(key, account_info);
+    }
+
+    pub fn add_program_account(&mut self, program_id: Pubkey) {
+        self.add_account(program_id, 1, vec![], solana_program::bpf_loader::id());
+        self.accounts.get_mut(&program_id).unwrap().executable = true;
+    }
+
+    /// Adds a wallet holding some SOL.
+    pub fn add_wallet(&mut self) -> Pubkey {
+        let wallet = Pubkey::new_unique();
+        self.add_account(wallet, 10 * LAMPORTS_PER_SOL, vec![], system_program::id());
+        wallet
+    }
+
+    pub fn add_mint(&mut self, mint_authority: &Pubkey, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: COption::Some(*mint_authority),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        self.add_account(
+            mint,
+            Rent::default().minimum_balance(data.len()),
+            data,
+            spl_token::id(),
+        );
+        mint
+    }
+
+    /// Adds the associated token account of a wallet holding `amount`.
+    pub fn add_token_account(&mut self, wallet: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let token_account =
+            spl_associated_token_account::get_associated_token_address(wallet, mint);
+        self.add_token_account_at(token_account, wallet, mint, amount);
+        token_account
+    }
+
+    /// Adds a token account of a wallet holding `amount` at `token_account`.
+    pub fn add_token_account_at(
+        &mut self,
+        token_account: Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *wallet,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..spl_token::state::Account::default()
+        }
+        .pack_into_slice(&mut data);
+        self.add_account(
+            token_account,
+            Rent::default().minimum_balance(data.len()),
+            data,
+            spl_token::id(),
+        );
+    }
+
+    /// The account at `key`, which is an empty system account unless
+    /// something was put there.
+    pub fn account(&mut self, key: &Pubkey) -> AccountInfo<'static> {
+        if !self.accounts.contains_key(key) {
+            self.add_account(*key, 0, vec![], system_program::id());
+        }
+        self.accounts[key].clone()
+    }
+
+    pub fn token_account(&mut self, key: &Pubkey) -> spl_token::state::Account {
+        spl_token::state::Account::unpack(&self.account(key).data.borrow()).unwrap()
+    }
+
+    /// Runs an instruction as the only one of a transaction signed by all of
+    /// its signers.
+    pub fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+        let accounts: Vec<AccountInfo> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let mut account_info = self.account(&meta.pubkey);
+                account_info.is_signer = meta.is_signer;
+                account_info.is_writable = meta.is_writable;
+                account_info
+            })
+            .collect();
+
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = None);
+        process(&instruction.program_id, &accounts, &instruction.data)
+    }
+}
+
+pub struct Funded {
+    pub funder: Pubkey,
+    pub mint: Pubkey,
+    pub dist_account: Pubkey,
+    pub dist_token_account: Pubkey,
+}
+
+/// Creates a distribution for up to `max_recipients` recipients and funds
+/// it with `amount` tokens.
+pub fn fund_distribution(env: &mut Env, amount: u64, max_recipients: u16) -> Funded {
+    let program_id = wave_dist::id();
+
+    let funder = env.add_wallet();
+    let mint = env.add_mint(&funder, 0);
+    let funder_token_account = env.add_token_account(&funder, &mint, amount);
+
+    let seed = Pubkey::new_unique();
+    let (dist_account, seed_bump) =
+        wave_dist::find_distribution_address(&program_id, &seed, "test", &funder);
+    let dist_token_account = get_associated_token_address(&dist_account, &mint);
+
+    for instruction in [
+        instruction::init_distribution(
+            &program_id,
+            &mint,
+            &dist_account,
+            &funder,
+            &seed,
+            "test",
+            seed_bump,
+            max_recipients,
+            &funder,
+        ),
+        create_associated_token_account(&funder, &dist_account, &mint),
+        instruction::fund_distribution(
+            &program_id,
+            &funder,
+            &funder_token_account,
+            &dist_account,
+            &dist_token_account,
+            None,
+            amount,
+            false,
+        ),
+    ] {
+        env.process(&instruction).unwrap();
+    }
+
+    Funded {
+        funder,
+        mint,
+        dist_account,
+        dist_token_account,
+    }
+}
+
+/// Creates and begins a distribution of `amount` tokens between
+/// `num_recipients` recipients, whose shares decay by `decay` if given.
+pub fn begin_distribution(
+    env: &mut Env,
+    amount: u64,
+    num_recipients: u16,
+    decay: Option<(u64, u16)>,
+) -> Funded {
+    let program_id = wave_dist::id();
+    let funded = fund_distribution(env, amount, num_recipients);
+    let Funded {
+        funder,
+        dist_account,
+        ..
+    } = funded;
+
+    let mut instructions = vec![];
+    if let Some((grace_epochs, bps_per_epoch)) = decay {
+        instructions.push(instruction::set_decay(
+            &program_id,
+            &dist_account,
+            &funder,
+            grace_epochs,
+            bps_per_epoch,
+        ));
+    }
+    instructions.push(instruction::begin_distribution(
+        &program_id,
+        &dist_account,
+        &funder,
+        None,
+        num_recipients,
+    ));
+    for instruction in instructions {
+        env.process(&instruction).unwrap();
+    }
+
+    funded
+}
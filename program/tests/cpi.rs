@@ -0,0 +1,378 @@
+//! Calls the program through the `cpi` module from a small caller program.
+//!
+//! The syscall stubs stand in for the runtime: they record each invoked
+//! instruction and run it with the program's processor, granting signatures
+//! of PDAs of the caller program.
+
+use std::{cell::RefCell, sync::Once};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hash,
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    system_program,
+};
+use wave_dist::{
+    cpi, instruction,
+    query::DistributionInfo,
+    state::{Distribution, FixedString, PdaSeed},
+};
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+    static RETURN_DATA: RefCell<Option<(Pubkey, Vec<u8>)>> = const { RefCell::new(None) };
+}
+
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller::id()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let accounts = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let mut account_info = account_infos
+                    .iter()
+                    .find(|account_info| *account_info.key == meta.pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+                    .clone();
+                account_info.is_signer =
+                    meta.is_signer && (account_info.is_signer || signers.contains(&meta.pubkey));
+                account_info.is_writable = meta.is_writable;
+                Ok(account_info)
+            })
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = None);
+        wave_dist::processor::process_instruction(
+            &instruction.program_id,
+            &accounts,
+            &instruction.data,
+        )
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RETURN_DATA
+            .with(|return_data| *return_data.borrow_mut() = Some((wave_dist::id(), data.to_vec())));
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RETURN_DATA.with(|return_data| return_data.borrow().clone())
+    }
+}
+
+fn setup() {
+    static STUBS: Once = Once::new();
+    STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(TestStubs));
+    });
+    INVOKED.with(|invoked| invoked.borrow_mut().clear());
+}
+
+fn invoked() -> Vec<Instruction> {
+    INVOKED.with(|invoked| invoked.borrow().clone())
+}
+
+/// A program that holds the dist authority of a distribution in a PDA and
+/// gates it on request.
+mod caller {
+    use super::*;
+
+    solana_program::declare_id!("Ca11er1111111111111111111111111111111111111");
+
+    pub const AUTHORITY_SEED: &[u8] = b"authority";
+
+    pub fn find_authority_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[AUTHORITY_SEED], &id())
+    }
+
+    /// Accounts: the distribution, the authority PDA and the wave-dist
+    /// program. Data: the gate mint and the minimum balance.
+    pub fn process(accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let dist_account_info = next_account_info(accounts_iter)?;
+        let authority_account_info = next_account_info(accounts_iter)?;
+        let wave_dist_program_info = next_account_info(accounts_iter)?;
+
+        if input.len() != 40 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let gate_mint = Pubkey::new(&input[..32]);
+        let min_balance = u64::from_le_bytes(input[32..].try_into().unwrap());
+
+        let (_, bump) = find_authority_address();
+        cpi::set_gate_signed(
+            wave_dist_program_info,
+            &cpi::DistAuthorityAccounts {
+                distribution: dist_account_info.clone(),
+                dist_authority: authority_account_info.clone(),
+            },
+            gate_mint,
+            min_balance,
+            &[&[AUTHORITY_SEED, &[bump]]],
+        )
+    }
+}
+
+fn account<'a>(
+    key: Pubkey,
+    owner: Pubkey,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+) -> AccountInfo<'a> {
+    AccountInfo::new(
+        Box::leak(Box::new(key)),
+        is_signer,
+        is_writable,
+        Box::leak(Box::new(0)),
+        Box::leak(data.into_boxed_slice()),
+        Box::leak(Box::new(owner)),
+        owner == bpf_loader(),
+        0,
+    )
+}
+
+fn bpf_loader() -> Pubkey {
+    solana_program::bpf_loader::id()
+}
+
+fn program_account<'a>(program_id: Pubkey) -> AccountInfo<'a> {
+    account(program_id, bpf_loader(), vec![], false, false)
+}
+
+fn distribution_account<'a>(dist_authority: Pubkey, gate_mint: Option<Pubkey>) -> AccountInfo<'a> {
+    let mut dist = Distribution::new(
+        PdaSeed::new(
+            Pubkey::new_unique(),
+            hash(b"test"),
+            Pubkey::new_unique(),
+            255,
+        ),
+        FixedString::new("test").unwrap(),
+        dist_authority,
+        Pubkey::new_unique(),
+        10,
+        0,
+    );
+    if let Some(gate_mint) = gate_mint {
+        dist.set_gate(gate_mint, 1);
+    }
+
+    let mut data = vec![0; Distribution::LEN];
+    Distribution::pack(dist, &mut data).unwrap();
+    account(Pubkey::new_unique(), wave_dist::id(), data, false, true)
+}
+
+#[test]
+fn caller_pda_signs_as_dist_authority() {
+    setup();
+
+    let (authority, _) = caller::find_authority_address();
+    let dist_account_info = distribution_account(authority, None);
+    let authority_account_info = account(authority, caller::id(), vec![], false, false);
+    let gate_mint = Pubkey::new_unique();
+
+    let mut input = gate_mint.to_bytes().to_vec();
+    input.extend_from_slice(&5u64.to_le_bytes());
+    caller::process(
+        &[
+            dist_account_info.clone(),
+            authority_account_info,
+            program_account(wave_dist::id()),
+        ],
+        &input,
+    )
+    .unwrap();
+
+    assert_eq!(
+        invoked(),
+        vec![instruction::set_gate(
+            &wave_dist::id(),
+            dist_account_info.key,
+            &authority,
+            &gate_mint,
+            5,
+        )]
+    );
+    let dist = Distribution::unpack(&dist_account_info.data.borrow()).unwrap();
+    assert_eq!(dist.gate(), Some((&gate_mint, 5)));
+}
+
+#[test]
+fn unsigned_dist_authority_is_rejected() {
+    setup();
+
+    let (authority, _) = caller::find_authority_address();
+    let accounts = cpi::DistAuthorityAccounts {
+        distribution: distribution_account(authority, None),
+        dist_authority: account(authority, caller::id(), vec![], false, false),
+    };
+
+    assert_eq!(
+        cpi::set_gate(
+            &program_account(wave_dist::id()),
+            &accounts,
+            Pubkey::new_unique(),
+            5,
+        ),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+}
+
+#[test]
+fn get_distribution_info_returns_answer() {
+    setup();
+
+    let dist_authority = Pubkey::new_unique();
+    let dist_account_info = distribution_account(dist_authority, None);
+
+    let DistributionInfo::V1(info) =
+        cpi::get_distribution_info(&program_account(wave_dist::id()), &dist_account_info).unwrap();
+    assert_eq!(info.project_name, "test");
+    assert_eq!(info.dist_authority, dist_authority);
+    assert_eq!(info.max_recipients, 10);
+}
+
+struct DistributeFixture<'a> {
+    accounts: cpi::DistributeAccounts<'a>,
+    recipient_wallet: Pubkey,
+}
+
+fn distribute_fixture<'a>(gate_mint: Option<Pubkey>) -> DistributeFixture<'a> {
+    let dist_authority = Pubkey::new_unique();
+    let distribution = distribution_account(dist_authority, gate_mint);
+    let recipient_wallet = Pubkey::new_unique();
+    let (receipt, _) =
+        wave_dist::find_receipt_address(&wave_dist::id(), distribution.key, &recipient_wallet);
+    let (denylist_entry, _) = wave_dist::find_denylist_address(&wave_dist::id(), &recipient_wallet);
+    let (forward_address, _) =
+        wave_dist::find_forward_address(&wave_dist::id(), distribution.key, &recipient_wallet);
+
+    let recipient = cpi::DistributeRecipient {
+        token_account: account(Pubkey::new_unique(), spl_token::id(), vec![], false, true),
+        receipt: account(receipt, system_program::id(), vec![], false, true),
+        denylist_entry: account(denylist_entry, system_program::id(), vec![], false, false),
+        forward_address: account(forward_address, system_program::id(), vec![], false, false),
+        gate_token_account: None,
+        hook_accounts: vec![],
+    };
+
+    DistributeFixture {
+        accounts: cpi::DistributeAccounts {
+            distribution,
+            dist_authority: account(dist_authority, system_program::id(), vec![], true, false),
+            token_program: program_account(spl_token::id()),
+            dist_token_account: account(Pubkey::new_unique(), spl_token::id(), vec![], false, true),
+            config: account(
+                wave_dist::find_config_address(&wave_dist::id()).0,
+                wave_dist::id(),
+                vec![],
+                false,
+                false,
+            ),
+            payer: account(
+                Pubkey::new_unique(),
+                system_program::id(),
+                vec![],
+                true,
+                true,
+            ),
+            system_program: program_account(system_program::id()),
+            hook: None,
+            recipients: vec![recipient],
+        },
+        recipient_wallet,
+    }
+}
+
+#[test]
+fn distribute_invokes_same_instruction_as_builder() {
+    setup();
+
+    let fixture = distribute_fixture(None);
+    let accounts = &fixture.accounts;
+    let _ = cpi::distribute(&program_account(wave_dist::id()), accounts, true);
+
+    assert_eq!(
+        invoked(),
+        vec![instruction::distribute(
+            &wave_dist::id(),
+            accounts.distribution.key,
+            accounts.dist_authority.key,
+            accounts.dist_token_account.key,
+            accounts.payer.key,
+            &[(
+                accounts.recipients[0].token_account.key,
+                &fixture.recipient_wallet,
+            )],
+            None,
+            None,
+            true,
+        )]
+    );
+}
+
+#[test]
+fn distribute_checks_gate_accounts() {
+    setup();
+
+    let gate_mint = Pubkey::new_unique();
+    let fixture = distribute_fixture(Some(gate_mint));
+    assert_eq!(
+        cpi::distribute(&program_account(wave_dist::id()), &fixture.accounts, false),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+
+    let mut fixture = distribute_fixture(None);
+    fixture.accounts.recipients[0].gate_token_account = Some(account(
+        Pubkey::new_unique(),
+        spl_token::id(),
+        vec![],
+        false,
+        false,
+    ));
+    assert_eq!(
+        cpi::distribute(&program_account(wave_dist::id()), &fixture.accounts, false),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    assert!(invoked().is_empty());
+}
+
+#[test]
+fn distribute_checks_program_and_writable_accounts() {
+    setup();
+
+    let mut fixture = distribute_fixture(None);
+    fixture.accounts.token_program = program_account(Pubkey::new_unique());
+    assert_eq!(
+        cpi::distribute(&program_account(wave_dist::id()), &fixture.accounts, false),
+        Err(ProgramError::IncorrectProgramId)
+    );
+
+    let mut fixture = distribute_fixture(None);
+    fixture.accounts.recipients[0].receipt.is_writable = false;
+    assert_eq!(
+        cpi::distribute(&program_account(wave_dist::id()), &fixture.accounts, false),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    assert!(invoked().is_empty());
+}
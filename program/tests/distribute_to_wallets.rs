@@ -0,0 +1,168 @@
+mod common;
+
+use common::{begin_distribution, Env, Funded};
+use solana_program::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address;
+use wave_dist::{error::DistError, instruction};
+
+#[test]
+fn creates_missing_associated_token_accounts() {
+    let mut env = Env::new();
+    let Funded {
+        funder,
+        mint,
+        dist_account,
+        dist_token_account,
+    } = begin_distribution(&mut env, 1_000, 2, None);
+
+    let wallets = [Pubkey::new_unique(), Pubkey::new_unique()];
+    for wallet in &wallets {
+        assert!(env
+            .account(&get_associated_token_address(wallet, &mint))
+            .data_is_empty());
+    }
+
+    env.process(&instruction::distribute_to_wallets(
+        &wave_dist::id(),
+        &dist_account,
+        &funder,
+        &dist_token_account,
+        &funder,
+        &mint,
+        &[(&wallets[0], None), (&wallets[1], None)],
+        None,
+        None,
+        false,
+    ))
+    .unwrap();
+
+    for wallet in &wallets {
+        let token_account_key = get_associated_token_address(wallet, &mint);
+        assert_eq!(*env.account(&token_account_key).owner, spl_token::id());
+
+        let token_account = env.token_account(&token_account_key);
+        assert_eq!(token_account.owner, *wallet);
+        assert_eq!(token_account.mint, mint);
+        assert_eq!(token_account.amount, 500);
+    }
+    assert_eq!(env.token_account(&dist_token_account).amount, 0);
+}
+
+#[test]
+fn rejects_other_token_accounts_of_the_distribution() {
+    let mut env = Env::new();
+    let Funded {
+        funder,
+        mint,
+        dist_account,
+        ..
+    } = begin_distribution(&mut env, 1_000, 2, None);
+
+    // Another token account the distribution owns, like a grant vault
+    let vault = Pubkey::new_unique();
+    env.add_token_account_at(vault, &dist_account, &mint, 1_000);
+
+    let wallet = Pubkey::new_unique();
+    assert_eq!(
+        env.process(&instruction::distribute_to_wallets(
+            &wave_dist::id(),
+            &dist_account,
+            &funder,
+            &vault,
+            &funder,
+            &mint,
+            &[(&wallet, None)],
+            None,
+            None,
+            false,
+        )),
+        Err(DistError::DistTokenAccountMismatch.into())
+    );
+    assert_eq!(env.token_account(&vault).amount, 1_000);
+}
+
+#[test]
+fn begun_distribution_rejects_an_attester() {
+    let mut env = Env::new();
+    let Funded {
+        funder,
+        dist_account,
+        ..
+    } = begin_distribution(&mut env, 1_000, 2, None);
+
+    assert_eq!(
+        env.process(&instruction::set_attester(
+            &wave_dist::id(),
+            &dist_account,
+            &funder,
+            &Pubkey::new_unique(),
+        )),
+        Err(DistError::SharesAndVouchersMixed.into())
+    );
+}
+
+#[test]
+fn rejects_the_forward_address_of_another_wallet() {
+    let mut env = Env::new();
+    let program_id = wave_dist::id();
+    let Funded {
+        funder,
+        mint,
+        dist_account,
+        dist_token_account,
+    } = begin_distribution(&mut env, 1_000, 2, None);
+
+    let forwarding_wallet = env.add_wallet();
+    let destination = env.add_token_account(&forwarding_wallet, &mint, 0);
+    env.process(&instruction::set_forward_address(
+        &program_id,
+        &dist_account,
+        &forwarding_wallet,
+        &destination,
+        &funder,
+    ))
+    .unwrap();
+
+    // Another wallet passed along with the accounts of the forwarding one
+    let wallet = Pubkey::new_unique();
+    let mut distribute = instruction::distribute_to_wallets(
+        &program_id,
+        &dist_account,
+        &funder,
+        &dist_token_account,
+        &funder,
+        &mint,
+        &[(&wallet, None)],
+        None,
+        None,
+        false,
+    );
+    let swaps = [
+        (get_associated_token_address(&wallet, &mint), destination),
+        (
+            wave_dist::find_receipt_address(&program_id, &dist_account, &wallet).0,
+            wave_dist::find_receipt_address(&program_id, &dist_account, &forwarding_wallet).0,
+        ),
+        (
+            wave_dist::find_denylist_address(&program_id, &wallet).0,
+            wave_dist::find_denylist_address(&program_id, &forwarding_wallet).0,
+        ),
+        (
+            wave_dist::find_forward_address(&program_id, &dist_account, &wallet).0,
+            wave_dist::find_forward_address(&program_id, &dist_account, &forwarding_wallet).0,
+        ),
+    ];
+    for account in distribute.accounts.iter_mut() {
+        for (from, to) in swaps {
+            if account.pubkey == from {
+                account.pubkey = to;
+            }
+        }
+    }
+
+    assert_eq!(
+        env.process(&distribute),
+        Err(DistError::InvalidForwardAccount.into())
+    );
+    assert_eq!(env.token_account(&destination).amount, 0);
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{add_program, begin_distribution, invoked, Env, Funded};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::AccountMeta, pubkey::Pubkey,
+};
+use wave_dist::{error::DistError, instruction, state::HookFailurePolicy};
+
+fn accept_payout(_program_id: &Pubkey, _accounts: &[AccountInfo], _input: &[u8]) -> ProgramResult {
+    Ok(())
+}
+
+/// Begins a distribution of 1000 tokens between 2 recipients, with a payout
+/// hook taking one extra account.
+fn hooked_distribution(env: &mut Env) -> (Funded, Pubkey) {
+    let funded = begin_distribution(env, 1_000, 2, None);
+
+    let hook_program = Pubkey::new_unique();
+    add_program(hook_program, accept_payout);
+    env.add_program_account(hook_program);
+
+    env.process(&instruction::set_hook(
+        &wave_dist::id(),
+        &funded.dist_account,
+        &funded.funder,
+        &hook_program,
+        1,
+        HookFailurePolicy::Abort,
+    ))
+    .unwrap();
+
+    (funded, hook_program)
+}
+
+#[test]
+fn distribute_to_wallets_calls_the_hook() {
+    let mut env = Env::new();
+    let (
+        Funded {
+            funder,
+            mint,
+            dist_account,
+            dist_token_account,
+        },
+        hook_program,
+    ) = hooked_distribution(&mut env);
+
+    let wallet = Pubkey::new_unique();
+    let extra_account = Pubkey::new_unique();
+    env.process(&instruction::distribute_to_wallets(
+        &wave_dist::id(),
+        &dist_account,
+        &funder,
+        &dist_token_account,
+        &funder,
+        &mint,
+        &[(&wallet, None)],
+        None,
+        Some((
+            &hook_program,
+            &[vec![AccountMeta::new(extra_account, false)]],
+        )),
+        false,
+    ))
+    .unwrap();
+
+    let hook_calls: Vec<_> = invoked()
+        .into_iter()
+        .filter(|instruction| instruction.program_id == hook_program)
+        .collect();
+    assert_eq!(hook_calls.len(), 1);
+    assert_eq!(
+        hook_calls[0].accounts.last(),
+        Some(&AccountMeta::new(extra_account, false))
+    );
+}
+
+#[test]
+fn payouts_that_skip_the_hook_are_rejected() {
+    let mut env = Env::new();
+    let (
+        Funded {
+            funder,
+            dist_account,
+            dist_token_account,
+            ..
+        },
+        _,
+    ) = hooked_distribution(&mut env);
+
+    assert_eq!(
+        env.process(&instruction::settle(
+            &wave_dist::id(),
+            &dist_account,
+            &funder,
+            &dist_token_account,
+            &[],
+        )),
+        Err(DistError::HookUnavailable.into())
+    );
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{fund_distribution, Env, Funded};
+use solana_program::program_error::ProgramError;
+use spl_associated_token_account::get_associated_token_address;
+use wave_dist::instruction;
+
+#[test]
+fn set_redemption_creates_the_input_vault() {
+    let mut env = Env::new();
+    let program_id = wave_dist::id();
+    let Funded {
+        funder,
+        dist_account,
+        ..
+    } = fund_distribution(&mut env, 1_000, 1);
+    let input_mint = env.add_mint(&funder, 0);
+
+    let set_redemption = instruction::set_redemption(
+        &program_id,
+        &dist_account,
+        &funder,
+        &funder,
+        &input_mint,
+        1,
+        2,
+        500,
+        false,
+    );
+    env.process(&set_redemption).unwrap();
+
+    let input_vault = get_associated_token_address(&dist_account, &input_mint);
+    assert_eq!(env.token_account(&input_vault).owner, dist_account);
+
+    // Updating the terms finds the vault already there
+    env.process(&set_redemption).unwrap();
+}
+
+#[test]
+fn rejects_a_zero_cap() {
+    let mut env = Env::new();
+    let Funded {
+        funder,
+        dist_account,
+        ..
+    } = fund_distribution(&mut env, 1_000, 1);
+    let input_mint = env.add_mint(&funder, 0);
+
+    assert_eq!(
+        env.process(&instruction::set_redemption(
+            &wave_dist::id(),
+            &dist_account,
+            &funder,
+            &funder,
+            &input_mint,
+            1,
+            2,
+            0,
+            true,
+        )),
+        Err(ProgramError::InvalidArgument)
+    );
+}
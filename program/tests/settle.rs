@@ -0,0 +1,70 @@
+mod common;
+
+use common::{begin_distribution, set_epoch, Env, Funded};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+use wave_dist::{error::DistError, instruction, state::RecipientReceipt};
+
+#[test]
+fn splits_forfeited_amount_and_keeps_receipts() {
+    let mut env = Env::new();
+    let program_id = wave_dist::id();
+    // Shares of 300 lose a tenth of themselves per epoch
+    let Funded {
+        funder,
+        mint,
+        dist_account,
+        dist_token_account,
+    } = begin_distribution(&mut env, 900, 3, Some((0, 1_000)));
+
+    let wallets = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let token_accounts = wallets.map(|wallet| get_associated_token_address(&wallet, &mint));
+    for (epoch, wallet) in [(0, &wallets[0]), (2, &wallets[1])] {
+        set_epoch(epoch);
+        env.process(&instruction::distribute_to_wallets(
+            &program_id,
+            &dist_account,
+            &funder,
+            &dist_token_account,
+            &funder,
+            &mint,
+            &[(wallet, None)],
+            None,
+            None,
+            false,
+        ))
+        .unwrap();
+    }
+    assert_eq!(env.token_account(&token_accounts[0]).amount, 300);
+    assert_eq!(env.token_account(&token_accounts[1]).amount, 240);
+
+    let settle = instruction::settle(
+        &program_id,
+        &dist_account,
+        &funder,
+        &dist_token_account,
+        &[
+            (&token_accounts[0], &wallets[0]),
+            (&token_accounts[1], &wallets[1]),
+        ],
+    );
+    env.process(&settle).unwrap();
+
+    // The 60 forfeited by the second recipient and the 60 the unclaimed
+    // share has lost by now are split, the rest of that share stays funded
+    assert_eq!(env.token_account(&token_accounts[0]).amount, 360);
+    assert_eq!(env.token_account(&token_accounts[1]).amount, 300);
+    assert_eq!(env.token_account(&dist_token_account).amount, 240);
+
+    for wallet in &wallets {
+        let receipt_account = wave_dist::find_receipt_address(&program_id, &dist_account, wallet).0;
+        let receipt =
+            RecipientReceipt::unpack(&env.account(&receipt_account).data.borrow()).unwrap();
+        assert_eq!(receipt.settle_bonus, 60);
+    }
+
+    assert_eq!(
+        env.process(&settle),
+        Err(DistError::SettleBonusAlreadyPaid.into())
+    );
+}